@@ -0,0 +1,121 @@
+use geometry_core::layout::{Constraint, ConstraintSet, Relation, Strength};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use types::RegionsType;
+
+const DEFAULT_FACING_ANGLE_DEG: f32 = 30.0;
+const DEFAULT_SIDE_ANGLE_DEG: f32 = 45.0;
+
+#[derive(Debug, Deserialize)]
+struct ConstraintConfig {
+    #[serde(default)]
+    constraints: Vec<ConstraintItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConstraintItem {
+    subject: String,
+    relation: String,
+    target: String,
+    /// Hard constraints prune the search; soft ones only lower the score.
+    #[serde(default)]
+    hard: bool,
+    #[serde(default = "default_weight")]
+    weight: f32,
+    max_distance_mm: Option<f32>,
+    min_distance_mm: Option<f32>,
+    max_angle_deg: Option<f32>,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// Loads relational constraints; region types are referenced by registry name.
+pub fn load_constraints(
+    path: &str,
+    regions_type_ids: &HashMap<String, RegionsType>,
+) -> Result<ConstraintSet, String> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| format!("read constraints config failed ({path}): {e}"))?;
+    parse_constraints(&data, regions_type_ids)
+}
+
+pub fn parse_constraints(
+    text: &str,
+    regions_type_ids: &HashMap<String, RegionsType>,
+) -> Result<ConstraintSet, String> {
+    let config: ConstraintConfig =
+        toml::from_str(text).map_err(|e| format!("parse constraints config failed: {e}"))?;
+    let constraints = config
+        .constraints
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| {
+            to_constraint(item, regions_type_ids).map_err(|e| format!("constraint #{index}: {e}"))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(ConstraintSet::new(constraints))
+}
+
+fn to_constraint(
+    item: ConstraintItem,
+    regions_type_ids: &HashMap<String, RegionsType>,
+) -> Result<Constraint, String> {
+    let lookup = |name: &str| {
+        regions_type_ids
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("regions type '{name}' not found in registry"))
+    };
+    let subject = lookup(&item.subject)?;
+    let target = lookup(&item.target)?;
+    let max_distance = || {
+        item.max_distance_mm
+            .ok_or_else(|| format!("relation '{}' requires max_distance_mm", item.relation))
+    };
+
+    let relation = match item.relation.as_str() {
+        "near" => Relation::Near {
+            max_distance_mm: max_distance()?,
+        },
+        "faces" => Relation::Faces {
+            max_distance_mm: max_distance()?,
+            max_angle_deg: item.max_angle_deg.unwrap_or(DEFAULT_FACING_ANGLE_DEG),
+        },
+        "opposite" => Relation::Opposite {
+            min_distance_mm: item.min_distance_mm.unwrap_or(0.0),
+            max_distance_mm: item.max_distance_mm.unwrap_or(f32::INFINITY),
+            max_angle_deg: item.max_angle_deg.unwrap_or(DEFAULT_FACING_ANGLE_DEG),
+        },
+        "flanks" => Relation::Flanks {
+            max_distance_mm: max_distance()?,
+            max_angle_deg: item.max_angle_deg.unwrap_or(DEFAULT_SIDE_ANGLE_DEG),
+        },
+        "on" if subject == target => {
+            return Err(format!("'{}' cannot be placed on itself", item.subject));
+        }
+        "on" => Relation::On,
+        other => return Err(format!("unknown relation '{other}'")),
+    };
+
+    let strength = if item.hard {
+        Strength::Hard
+    } else if item.weight > 0.0 {
+        Strength::Soft {
+            weight: item.weight,
+        }
+    } else {
+        return Err(format!("soft constraint weight must be positive, got {}", item.weight));
+    };
+
+    Ok(Constraint {
+        label: format!("{} {} {}", item.subject, item.relation, item.target),
+        subject,
+        target,
+        relation,
+        strength,
+    })
+}
@@ -1,7 +1,9 @@
+mod constraints;
 mod placement_region;
 mod space;
 mod usda_common;
 
+pub use constraints::{load_constraints, parse_constraints};
 pub use placement_region::{load_placement_region_model_from_usda, load_placement_regions_from_dir};
-pub use space::load_space_model_from_usda;
+pub use space::{build_shell_sdf, load_space_model_from_usda};
 pub use usda_common::{load_bounds, load_mesh, load_regions_type_registry, Bounds3, MeshData};
@@ -80,6 +80,8 @@ pub fn load_placement_region_model_from_usda(
         semantics: PlacementSemantics {
             regions_type,
             count: region.count.unwrap_or(1),
+            min_count: region.count_min,
+            reward: region.reward.unwrap_or(0.0),
         },
         visual: Visual {
             footprint_2d: footprint_mesh.unwrap_or_default(),
@@ -89,7 +91,7 @@ pub fn load_placement_region_model_from_usda(
     Ok(region)
 }
 
-fn build_sdf(positions: &[[f32; 3]], indices: &[u32]) -> Result<geometry_core::models::placement_region::SdfGrid, String> {
+pub(crate) fn build_sdf(positions: &[[f32; 3]], indices: &[u32]) -> Result<geometry_core::models::placement_region::SdfGrid, String> {
     const VOXEL_SIZE_MM: f32 = 20.0;
     ensure_vdb_init();
     info!(
@@ -245,7 +247,9 @@ fn log_placement_json(placements: &[(String, usize)], regions: &[PlacementRegion
                 },
                 "semantics": {
                     "regions_type": placement.semantics.regions_type,
-                    "count": placement.semantics.count
+                    "count": placement.semantics.count,
+                    "min_count": placement.semantics.min_count,
+                    "reward": placement.semantics.reward
                 },
                 "visual": {
                     "footprint_2d": {
@@ -1,4 +1,6 @@
+use crate::placement_region::build_sdf;
 use crate::usda_common::{mask_from_names, to_mesh_data_from_usd};
+use geometry_core::models::placement_region::SdfGrid;
 use geometry_core::models::space::{Mesh as SpaceMesh, Space, SurfaceMeta};
 use log::info;
 use serde_json::json;
@@ -44,6 +46,11 @@ pub fn load_space_model_from_usda(
     Ok(space)
 }
 
+/// Builds a signed distance field of a closed room shell mesh (see `RoomShell::closed_mesh`).
+pub fn build_shell_sdf(mesh: &SpaceMesh) -> Result<SdfGrid, String> {
+    build_sdf(&mesh.positions, &mesh.indices)
+}
+
 fn log_space_json(space: &Space) {
     let meshes = space
         .surface_metas
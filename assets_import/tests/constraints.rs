@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use geometry_core::layout::{Relation, Strength};
+
+#[test]
+fn parse_constraints_resolves_registry_names() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("assets_import has no parent dir");
+    let registry = root.join("assets/config/regions_types.toml");
+    let regions_type_ids = assets_import::load_regions_type_registry(registry.to_str().unwrap())
+        .expect("load_regions_type_registry failed");
+
+    let path = root.join("assets/config/constraints.toml");
+    let constraints = assets_import::load_constraints(path.to_str().unwrap(), &regions_type_ids)
+        .expect("load_constraints failed");
+    assert!(!constraints.is_empty());
+    let first = &constraints.constraints()[0];
+    assert_eq!(first.subject, regions_type_ids["chair"]);
+    assert_eq!(first.target, regions_type_ids["table"]);
+    assert_eq!(first.label, "chair faces table");
+    assert!(matches!(first.relation, Relation::Faces { max_distance_mm, .. } if max_distance_mm == 600.0));
+
+    let ids = HashMap::from([("desk".to_string(), 1), ("lamp".to_string(), 2)]);
+    let parsed = assets_import::parse_constraints(
+        "[[constraints]]\nsubject = \"lamp\"\nrelation = \"on\"\ntarget = \"desk\"\nhard = true\n",
+        &ids,
+    )
+    .unwrap();
+    assert_eq!(parsed.constraints()[0].relation, Relation::On);
+    assert_eq!(parsed.constraints()[0].strength, Strength::Hard);
+    assert!(parsed.stacks(2, 1) && !parsed.stacks(1, 2));
+
+    let rejected = [
+        "[[constraints]]\nsubject = \"sofa\"\nrelation = \"near\"\ntarget = \"desk\"\nmax_distance_mm = 1.0\n",
+        "[[constraints]]\nsubject = \"lamp\"\nrelation = \"above\"\ntarget = \"desk\"\n",
+        "[[constraints]]\nsubject = \"lamp\"\nrelation = \"near\"\ntarget = \"desk\"\n",
+        "[[constraints]]\nsubject = \"lamp\"\nrelation = \"on\"\ntarget = \"desk\"\nweight = 0.0\n",
+    ];
+    for text in rejected {
+        assert!(assets_import::parse_constraints(text, &ids).is_err(), "{text}");
+    }
+}
@@ -1,113 +1,255 @@
 use crate::geometry_ops::plane::fit_plane_pca;
 use crate::models::mesh::Mesh;
+use geo::orient::Direction;
+use geo::{Area, Contains, Orient};
+use geo_types::{Coord, LineString, Point, Polygon};
 use nalgebra::Vector3;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::f32::consts::TAU;
 
-/// Flattens a mesh onto its best-fit plane and returns the outer boundary loop vertices.
-pub fn flatten_outer_boundary(mesh: &Mesh) -> Vec<[f32; 3]> {
-    flatten_outer_boundary_common(&mesh.positions, &mesh.indices)
+/// Boundary of a planar mesh as a polygon in its best-fit plane.
+///
+/// `polygon` is in `(u, v)` plane coordinates: the exterior ring is counter-clockwise,
+/// holes (columns, islands cut out of a floor) are clockwise.
+#[derive(Clone, Debug)]
+pub struct PlanarPolygon {
+    pub origin: Vector3<f32>,
+    pub u_axis: Vector3<f32>,
+    pub v_axis: Vector3<f32>,
+    pub normal: Vector3<f32>,
+    pub polygon: Polygon<f64>,
 }
 
-fn flatten_outer_boundary_common(
-    positions: &[[f32; 3]],
-    indices: &[u32],
-) -> Vec<[f32; 3]> {
-    if positions.is_empty() || indices.len() < 3 {
-        return Vec::new();
+impl PlanarPolygon {
+    pub fn to_world(&self, uv: Coord<f64>) -> [f32; 3] {
+        let p = self.origin + self.u_axis * uv.x as f32 + self.v_axis * uv.y as f32;
+        [p.x, p.y, p.z]
     }
 
-    let (origin, u_axis, v_axis, _normal) = match fit_plane_pca(positions) {
-        Some(data) => data,
-        None => return Vec::new(),
-    };
+    /// World-space rings, exterior first; the closing vertex is not repeated.
+    pub fn rings_world(&self) -> Vec<Vec<[f32; 3]>> {
+        std::iter::once(self.polygon.exterior())
+            .chain(self.polygon.interiors())
+            .map(|ring| open_ring(ring).iter().map(|c| self.to_world(*c)).collect())
+            .collect()
+    }
 
-    let mut projected = Vec::with_capacity(positions.len());
-    for p in positions {
-        let v = Vector3::new(p[0], p[1], p[2]) - origin;
-        projected.push([v.dot(&u_axis), v.dot(&v_axis)]);
+    /// Maps every vertex through world space into another 2D frame (e.g. XZ),
+    /// re-orienting the rings for that frame.
+    pub fn reproject(&self, project: impl Fn([f32; 3]) -> [f64; 2]) -> Polygon<f64> {
+        let map = |ring: &LineString<f64>| {
+            LineString::from(
+                open_ring(ring)
+                    .iter()
+                    .map(|c| {
+                        let [x, y] = project(self.to_world(*c));
+                        Coord { x, y }
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        };
+        Polygon::new(
+            map(self.polygon.exterior()),
+            self.polygon.interiors().iter().map(map).collect(),
+        )
+        .orient(Direction::Default)
     }
+}
 
-    let loops = boundary_loops(indices, &projected);
-    let Some(loop_indices) = loops
-        .into_iter()
-        .max_by(|a, b| loop_perimeter(a, &projected).partial_cmp(&loop_perimeter(b, &projected)).unwrap())
-    else {
+/// Flattens a mesh onto its best-fit plane and returns the outer boundary loop vertices.
+///
+/// The outer loop is the boundary loop with the longest perimeter, in walk order; holes and
+/// other loops are ignored. Use `flatten_boundary_polygon` for the exterior and its holes.
+pub fn flatten_outer_boundary(mesh: &Mesh) -> Vec<[f32; 3]> {
+    let Some(flat) = Flattened::of(mesh) else {
         return Vec::new();
     };
+    flat.loops
+        .iter()
+        .max_by(|a, b| loop_perimeter(a, &flat.projected).total_cmp(&loop_perimeter(b, &flat.projected)))
+        .map(|l| l.iter().map(|&i| flat.to_world(flat.projected[i])).collect())
+        .unwrap_or_default()
+}
+
+/// Flattens a mesh onto its best-fit plane and returns its boundary with holes.
+///
+/// The exterior is the largest loop not enclosed by another; loops directly inside it
+/// become holes. Loops outside the exterior, or nested inside a hole, are dropped.
+pub fn flatten_boundary_polygon(mesh: &Mesh) -> Option<PlanarPolygon> {
+    let Flattened {
+        origin,
+        u_axis,
+        v_axis,
+        normal,
+        projected,
+        loops,
+    } = Flattened::of(mesh)?;
+    let rings: Vec<Polygon<f64>> = loops
+        .iter()
+        .map(|l| {
+            let coords: Vec<Coord<f64>> = l
+                .iter()
+                .map(|&i| Coord {
+                    x: projected[i][0] as f64,
+                    y: projected[i][1] as f64,
+                })
+                .collect();
+            Polygon::new(LineString::from(coords), Vec::new())
+        })
+        .collect();
+
+    // inside[i][j]: loop j lies inside loop i
+    let inside: Vec<Vec<bool>> = (0..loops.len())
+        .map(|i| {
+            (0..loops.len())
+                .map(|j| i != j && loop_inside(&loops[j], &loops[i], &rings[i], &projected))
+                .collect()
+        })
+        .collect();
+    let enclosed = |j: usize| (0..loops.len()).any(|i| inside[i][j]);
+
+    let exterior = (0..loops.len())
+        .filter(|&i| !enclosed(i))
+        .max_by(|&a, &b| {
+            rings[a]
+                .unsigned_area()
+                .total_cmp(&rings[b].unsigned_area())
+                .then(b.cmp(&a))
+        })?;
+    let holes: Vec<LineString<f64>> = (0..loops.len())
+        .filter(|&j| inside[exterior][j])
+        .filter(|&j| !(0..loops.len()).any(|k| k != exterior && inside[exterior][k] && inside[k][j]))
+        .map(|j| rings[j].exterior().clone())
+        .collect();
 
-    loop_indices
-        .into_iter()
-        .map(|idx| {
-            let uv = projected[idx];
-            let p3 = origin + u_axis * uv[0] + v_axis * uv[1];
-            [p3.x, p3.y, p3.z]
+    let polygon = Polygon::new(rings[exterior].exterior().clone(), holes).orient(Direction::Default);
+    Some(PlanarPolygon {
+        origin,
+        u_axis,
+        v_axis,
+        normal,
+        polygon,
+    })
+}
+
+/// A mesh projected onto its best-fit plane, with its boundary loops.
+struct Flattened {
+    origin: Vector3<f32>,
+    u_axis: Vector3<f32>,
+    v_axis: Vector3<f32>,
+    normal: Vector3<f32>,
+    projected: Vec<[f32; 2]>,
+    loops: Vec<Vec<usize>>,
+}
+
+impl Flattened {
+    fn of(mesh: &Mesh) -> Option<Self> {
+        if mesh.positions.is_empty() || mesh.indices.len() < 3 {
+            return None;
+        }
+        let (origin, u_axis, v_axis, normal) = fit_plane_pca(&mesh.positions)?;
+        let projected: Vec<[f32; 2]> = mesh
+            .positions
+            .iter()
+            .map(|p| {
+                let v = Vector3::new(p[0], p[1], p[2]) - origin;
+                [v.dot(&u_axis), v.dot(&v_axis)]
+            })
+            .collect();
+        let loops = boundary_loops(&mesh.indices, &projected);
+        Some(Self {
+            origin,
+            u_axis,
+            v_axis,
+            normal,
+            projected,
+            loops,
+        })
+    }
+
+    fn to_world(&self, uv: [f32; 2]) -> [f32; 3] {
+        let p = self.origin + self.u_axis * uv[0] + self.v_axis * uv[1];
+        [p.x, p.y, p.z]
+    }
+}
+
+fn loop_perimeter(indices: &[usize], verts: &[[f32; 2]]) -> f32 {
+    (0..indices.len())
+        .map(|i| {
+            let (a, b) = (verts[indices[i]], verts[indices[(i + 1) % indices.len()]]);
+            (a[0] - b[0]).hypot(a[1] - b[1])
         })
-        .collect()
+        .sum()
 }
 
+/// Closed boundary loops as vertex indices, each with the mesh on its left in `(u, v)`.
+///
+/// Every triangle is re-wound counter-clockwise in the plane first, so the result does not
+/// depend on the mesh's winding. At a vertex shared by several loops (a pinch point) the
+/// walk takes the sharpest left turn, which keeps each loop simple; ties fall back to the
+/// lowest vertex index.
 fn boundary_loops(indices: &[u32], verts: &[[f32; 2]]) -> Vec<Vec<usize>> {
     let mut edge_counts: HashMap<(usize, usize), u32> = HashMap::new();
+    let mut directed: Vec<(usize, usize)> = Vec::new();
 
-    let mut i = 0;
-    while i + 2 < indices.len() {
-        let a = indices[i] as usize;
-        let b = indices[i + 1] as usize;
-        let c = indices[i + 2] as usize;
-        i += 3;
+    for tri in indices.chunks_exact(3) {
+        let [a, b, c] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
         if a >= verts.len() || b >= verts.len() || c >= verts.len() {
             continue;
         }
-        add_edge(a, b, &mut edge_counts);
-        add_edge(b, c, &mut edge_counts);
-        add_edge(c, a, &mut edge_counts);
+        let (b, c) = if signed_area(verts[a], verts[b], verts[c]) < 0.0 {
+            (c, b)
+        } else {
+            (b, c)
+        };
+        for (u, v) in [(a, b), (b, c), (c, a)] {
+            add_edge(u, v, &mut edge_counts);
+            directed.push((u, v));
+        }
     }
 
-    let mut adjacency: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
-    for ((u, v), count) in edge_counts {
-        if count == 1 {
-            adjacency.entry(u).or_default().push(v);
-            adjacency.entry(v).or_default().push(u);
+    let mut outgoing: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (u, v) in directed {
+        if edge_counts.get(&edge_key(u, v)) == Some(&1) {
+            outgoing.entry(u).or_default().push(v);
         }
     }
+    for targets in outgoing.values_mut() {
+        targets.sort_unstable();
+    }
 
     let mut loops = Vec::new();
-    let mut visited_edges: HashMap<(usize, usize), bool> = HashMap::new();
+    let mut used: HashSet<(usize, usize)> = HashSet::new();
 
-    for (&start, neighbors) in &adjacency {
-        for &next in neighbors {
-            if visited_edges.get(&(start, next)).copied().unwrap_or(false) {
+    for (&start, targets) in &outgoing {
+        for &first in targets {
+            if !used.insert((start, first)) {
                 continue;
             }
-            let mut loop_vertices = Vec::new();
+            let mut loop_vertices = vec![start];
             let mut prev = start;
-            let mut curr = next;
-            loop_vertices.push(start);
-
-            loop {
-                visited_edges.insert((prev, curr), true);
-                visited_edges.insert((curr, prev), true);
-                loop_vertices.push(curr);
-
-                let next_candidates = adjacency.get(&curr).map(|v| v.as_slice()).unwrap_or(&[]);
-                let mut chosen = None;
-                for &cand in next_candidates {
-                    if cand != prev {
-                        chosen = Some(cand);
-                        break;
-                    }
-                }
-                let Some(next_v) = chosen else { break; };
-                prev = curr;
-                curr = next_v;
+            let mut curr = first;
+            let closed = loop {
                 if curr == start {
-                    break;
+                    break true;
                 }
-                if loop_vertices.len() > verts.len() + 1 {
-                    break;
+                if loop_vertices.len() > verts.len() {
+                    break false;
                 }
-            }
+                loop_vertices.push(curr);
 
-            if loop_vertices.len() >= 3 {
+                let candidates = outgoing.get(&curr).map(|v| v.as_slice()).unwrap_or(&[]);
+                let Some(next) = sharpest_left(verts, prev, curr, candidates, |v| {
+                    !used.contains(&(curr, v))
+                }) else {
+                    break false;
+                };
+                used.insert((curr, next));
+                prev = curr;
+                curr = next;
+            };
+
+            if closed && loop_vertices.len() >= 3 {
                 loops.push(loop_vertices);
             }
         }
@@ -116,22 +258,62 @@ fn boundary_loops(indices: &[u32], verts: &[[f32; 2]]) -> Vec<Vec<usize>> {
     loops
 }
 
-fn add_edge(a: usize, b: usize, edge_counts: &mut HashMap<(usize, usize), u32>) {
-    let key = if a < b { (a, b) } else { (b, a) };
-    *edge_counts.entry(key).or_insert(0) += 1;
+/// Among the unused outgoing edges at `curr`, the one reached first when turning clockwise
+/// from the edge just walked (`curr -> prev`).
+fn sharpest_left(
+    verts: &[[f32; 2]],
+    prev: usize,
+    curr: usize,
+    candidates: &[usize],
+    unused: impl Fn(usize) -> bool,
+) -> Option<usize> {
+    let angle = |to: usize| {
+        let d = [verts[to][0] - verts[curr][0], verts[to][1] - verts[curr][1]];
+        d[1].atan2(d[0])
+    };
+    let back = angle(prev);
+    let mut best: Option<(f32, usize)> = None;
+    for &cand in candidates {
+        if !unused(cand) {
+            continue;
+        }
+        let mut turn = (back - angle(cand)).rem_euclid(TAU);
+        if turn <= 0.0 {
+            turn = TAU;
+        }
+        if best.is_none_or(|(t, _)| turn < t) {
+            best = Some((turn, cand));
+        }
+    }
+    best.map(|(_, cand)| cand)
 }
 
-fn loop_perimeter(indices: &[usize], verts: &[[f32; 2]]) -> f32 {
-    if indices.len() < 2 {
-        return 0.0;
-    }
-    let mut sum = 0.0;
-    for i in 0..indices.len() {
-        let a = verts[indices[i]];
-        let b = verts[indices[(i + 1) % indices.len()]];
-        let dx = a[0] - b[0];
-        let dy = a[1] - b[1];
-        sum += (dx * dx + dy * dy).sqrt();
+/// Whether loop `inner` lies inside loop `outer`, judged by an `inner` vertex that is not
+/// on `outer` (the loops may share pinch vertices).
+fn loop_inside(inner: &[usize], outer: &[usize], outer_ring: &Polygon<f64>, verts: &[[f32; 2]]) -> bool {
+    let shared: HashSet<usize> = outer.iter().copied().collect();
+    inner
+        .iter()
+        .find(|i| !shared.contains(i))
+        .is_some_and(|&i| outer_ring.contains(&Point::new(verts[i][0] as f64, verts[i][1] as f64)))
+}
+
+fn open_ring(ring: &LineString<f64>) -> &[Coord<f64>] {
+    let coords = ring.0.as_slice();
+    match coords {
+        [first, .., last] if first == last => &coords[..coords.len() - 1],
+        _ => coords,
     }
-    sum
+}
+
+fn signed_area(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+fn add_edge(a: usize, b: usize, edge_counts: &mut HashMap<(usize, usize), u32>) {
+    *edge_counts.entry(edge_key(a, b)).or_insert(0) += 1;
 }
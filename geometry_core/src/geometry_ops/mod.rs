@@ -3,8 +3,11 @@ pub mod flatten;
 pub mod hull;
 pub mod plane;
 pub mod sampling;
+pub mod segment;
 
-pub use boundary::flatten_outer_boundary;
+pub use boundary::{flatten_boundary_polygon, flatten_outer_boundary, PlanarPolygon};
 pub use flatten::flatten_to_xz_points;
 pub use hull::convex_hull_xz;
+pub use plane::SurfaceKind;
 pub use sampling::sample_points_uv;
+pub use segment::{segment_planes, segment_space, PlanarPatch, SegmentConfig};
@@ -1,8 +1,35 @@
 use nalgebra::{Matrix3, SymmetricEigen, Vector3};
 
-pub fn fit_plane_pca(
-    points: &[[f32; 3]],
-) -> Option<(Vector3<f32>, Vector3<f32>, Vector3<f32>, Vector3<f32>)> {
+/// Cosine between a normal and vertical above which a plane counts as horizontal.
+const HORIZONTAL_COS: f32 = 0.9;
+
+/// Orientation class of a planar surface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SurfaceKind {
+    /// Normal points up.
+    Floor,
+    /// Normal is close to horizontal.
+    Wall,
+    /// Normal points down.
+    Ceiling,
+}
+
+impl SurfaceKind {
+    pub fn from_normal(normal: &Vector3<f32>) -> Self {
+        if normal.y >= HORIZONTAL_COS {
+            SurfaceKind::Floor
+        } else if normal.y <= -HORIZONTAL_COS {
+            SurfaceKind::Ceiling
+        } else {
+            SurfaceKind::Wall
+        }
+    }
+}
+
+/// Best-fit plane as `(origin, u_axis, v_axis, normal)`.
+pub type PlaneFit = (Vector3<f32>, Vector3<f32>, Vector3<f32>, Vector3<f32>);
+
+pub fn fit_plane_pca(points: &[[f32; 3]]) -> Option<PlaneFit> {
     if points.len() < 3 {
         return None;
     }
@@ -0,0 +1,317 @@
+use crate::geometry_ops::plane::SurfaceKind;
+use crate::models::mesh::Mesh;
+use crate::models::space::{Space, SurfaceMeta};
+use nalgebra::Vector3;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use types::RegionsTypeMask;
+
+/// Parameters of the planar segmentation pass.
+#[derive(Clone, Debug)]
+pub struct SegmentConfig {
+    /// Triangles whose normals differ by less than this join the same direction cluster.
+    pub angle_tolerance_deg: f32,
+    /// Max distance of a triangle centroid from the growing patch's plane.
+    pub distance_tolerance_mm: f32,
+    /// Smaller patches (clutter, trim, scan noise) are dropped.
+    pub min_area_mm2: f32,
+    /// Vertices closer than this are merged before looking for shared edges.
+    pub weld_mm: f32,
+    /// Default masks assigned per class.
+    pub floor_mask: RegionsTypeMask,
+    pub wall_mask: RegionsTypeMask,
+    pub ceiling_mask: RegionsTypeMask,
+}
+
+impl Default for SegmentConfig {
+    fn default() -> Self {
+        Self {
+            angle_tolerance_deg: 10.0,
+            distance_tolerance_mm: 30.0,
+            min_area_mm2: 250_000.0,
+            weld_mm: 1.0,
+            floor_mask: RegionsTypeMask::NONE,
+            wall_mask: RegionsTypeMask::NONE,
+            ceiling_mask: RegionsTypeMask::NONE,
+        }
+    }
+}
+
+impl SegmentConfig {
+    pub fn mask_for(&self, kind: SurfaceKind) -> RegionsTypeMask {
+        match kind {
+            SurfaceKind::Floor => self.floor_mask,
+            SurfaceKind::Wall => self.wall_mask,
+            SurfaceKind::Ceiling => self.ceiling_mask,
+        }
+    }
+}
+
+/// One planar piece of a room mesh.
+#[derive(Clone, Debug)]
+pub struct PlanarPatch {
+    pub kind: SurfaceKind,
+    /// Unit normal facing into the room; triangles are wound to match it.
+    pub normal: Vector3<f32>,
+    pub area: f32,
+    /// Welded vertices of this patch only.
+    pub mesh: Mesh,
+}
+
+/// Splits an arbitrary room mesh into planar patches and classifies each one.
+///
+/// Triangle normals are first clustered by direction (ignoring winding), then patches
+/// are grown over shared edges within a cluster while centroids stay near the plane.
+/// Horizontal patches in the lower half of the mesh's height range are floors, those in
+/// the upper half ceilings; everything else is a wall.
+pub fn segment_planes(mesh: &Mesh, config: &SegmentConfig) -> Vec<PlanarPatch> {
+    let (positions, triangles) = weld(mesh, config.weld_mm);
+    if triangles.is_empty() {
+        return Vec::new();
+    }
+
+    let faces: Vec<Face> = triangles.iter().filter_map(|t| Face::new(*t, &positions)).collect();
+    // Largest faces first, so clusters and seeds start from the most reliable normals.
+    let mut order: Vec<usize> = (0..faces.len()).collect();
+    order.sort_by(|&a, &b| faces[b].area.total_cmp(&faces[a].area).then(a.cmp(&b)));
+
+    let cos_tolerance = config.angle_tolerance_deg.to_radians().cos();
+    let cluster = cluster_normals(&faces, &order, cos_tolerance);
+
+    let mut edge_faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (index, face) in faces.iter().enumerate() {
+        for (a, b) in face.edges() {
+            edge_faces.entry(edge_key(a, b)).or_default().push(index);
+        }
+    }
+
+    let (min_y, max_y) = positions
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), p| (lo.min(p.y), hi.max(p.y)));
+    let center = positions.iter().fold(Vector3::zeros(), |acc, p| acc + p) / positions.len() as f32;
+
+    let mut assigned = vec![false; faces.len()];
+    let mut patches = Vec::new();
+    for &seed in &order {
+        if assigned[seed] {
+            continue;
+        }
+        let (normal, _) = cluster.directions[cluster.of_face[seed]];
+        let members = grow(seed, normal, &faces, &cluster.of_face, &edge_faces, &mut assigned, config);
+        let area: f32 = members.iter().map(|&f| faces[f].area).sum();
+        if area < config.min_area_mm2 {
+            continue;
+        }
+
+        let centroid = members
+            .iter()
+            .fold(Vector3::zeros(), |acc, &f| acc + faces[f].centroid * faces[f].area)
+            / area;
+        let up = if normal.y < 0.0 { -normal } else { normal };
+        let (kind, facing) = match SurfaceKind::from_normal(&up) {
+            SurfaceKind::Wall => {
+                let inward = if normal.dot(&(center - centroid)) >= 0.0 { normal } else { -normal };
+                (SurfaceKind::Wall, inward)
+            }
+            _ if centroid.y - min_y <= max_y - centroid.y => (SurfaceKind::Floor, up),
+            _ => (SurfaceKind::Ceiling, -up),
+        };
+        patches.push(PlanarPatch {
+            kind,
+            normal: facing,
+            area,
+            mesh: patch_mesh(&members, &faces, &positions, &facing),
+        });
+    }
+    patches
+}
+
+/// Replaces every unlabelled mesh (empty mask) of `space` with its planar patches,
+/// masked per class from `config`. Labelled meshes are kept as they are.
+///
+/// The unlabelled meshes are segmented together so floor and ceiling are told apart
+/// by the height range of the whole room.
+pub fn segment_space(space: &Space, config: &SegmentConfig) -> Space {
+    let mut meshes = Vec::new();
+    let mut surface_metas = Vec::new();
+    let mut raw = Mesh::default();
+    for (mesh, meta) in space.meshes.iter().zip(&space.surface_metas) {
+        if meta.regions_type_mask.is_empty() {
+            let base = raw.positions.len() as u32;
+            raw.positions.extend_from_slice(&mesh.positions);
+            raw.indices.extend(mesh.indices.iter().map(|i| i + base));
+        } else {
+            meshes.push(mesh.clone());
+            surface_metas.push(meta.clone());
+        }
+    }
+    for patch in segment_planes(&raw, config) {
+        surface_metas.push(SurfaceMeta {
+            regions_type_mask: config.mask_for(patch.kind),
+        });
+        meshes.push(patch.mesh);
+    }
+    Space {
+        meshes,
+        surface_metas,
+    }
+}
+
+struct Face {
+    vertices: [usize; 3],
+    /// Unit normal from the original winding.
+    normal: Vector3<f32>,
+    area: f32,
+    centroid: Vector3<f32>,
+}
+
+impl Face {
+    fn new(vertices: [usize; 3], positions: &[Vector3<f32>]) -> Option<Self> {
+        let [a, b, c] = vertices.map(|v| positions[v]);
+        let cross = (b - a).cross(&(c - a));
+        let double_area = cross.norm();
+        if double_area <= f32::EPSILON {
+            return None;
+        }
+        Some(Self {
+            vertices,
+            normal: cross / double_area,
+            area: double_area * 0.5,
+            centroid: (a + b + c) / 3.0,
+        })
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.vertices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+struct Clusters {
+    /// (unit direction, accumulated area-weighted normal)
+    directions: Vec<(Vector3<f32>, Vector3<f32>)>,
+    of_face: Vec<usize>,
+}
+
+/// Greedy direction clustering; a normal and its opposite fall in the same cluster.
+fn cluster_normals(faces: &[Face], order: &[usize], cos_tolerance: f32) -> Clusters {
+    let mut directions: Vec<(Vector3<f32>, Vector3<f32>)> = Vec::new();
+    let mut of_face = vec![0; faces.len()];
+    for &index in order {
+        let face = &faces[index];
+        let found = directions
+            .iter()
+            .position(|(dir, _)| dir.dot(&face.normal).abs() >= cos_tolerance);
+        match found {
+            Some(cluster) => {
+                let (dir, sum) = &mut directions[cluster];
+                let aligned = if dir.dot(&face.normal) < 0.0 { -face.normal } else { face.normal };
+                *sum += aligned * face.area;
+                *dir = sum.normalize();
+                of_face[index] = cluster;
+            }
+            None => {
+                of_face[index] = directions.len();
+                directions.push((face.normal, face.normal * face.area));
+            }
+        }
+    }
+    Clusters { directions, of_face }
+}
+
+/// Region growing from `seed` over shared edges, within the seed's cluster and plane.
+fn grow(
+    seed: usize,
+    normal: Vector3<f32>,
+    faces: &[Face],
+    cluster_of: &[usize],
+    edge_faces: &HashMap<(usize, usize), Vec<usize>>,
+    assigned: &mut [bool],
+    config: &SegmentConfig,
+) -> Vec<usize> {
+    let cluster = cluster_of[seed];
+    let mut offset = normal.dot(&faces[seed].centroid);
+    let mut weight = faces[seed].area;
+    let mut members = vec![seed];
+    let mut queue = VecDeque::from([seed]);
+    assigned[seed] = true;
+
+    while let Some(current) = queue.pop_front() {
+        for (a, b) in faces[current].edges() {
+            let Some(neighbors) = edge_faces.get(&edge_key(a, b)) else {
+                continue;
+            };
+            for &next in neighbors {
+                if assigned[next] || cluster_of[next] != cluster {
+                    continue;
+                }
+                let face = &faces[next];
+                let distance = normal.dot(&face.centroid);
+                if (distance - offset).abs() > config.distance_tolerance_mm {
+                    continue;
+                }
+                assigned[next] = true;
+                offset = (offset * weight + distance * face.area) / (weight + face.area);
+                weight += face.area;
+                members.push(next);
+                queue.push_back(next);
+            }
+        }
+    }
+    members
+}
+
+/// Compact mesh of the patch faces, wound so their normals agree with `facing`.
+fn patch_mesh(members: &[usize], faces: &[Face], positions: &[Vector3<f32>], facing: &Vector3<f32>) -> Mesh {
+    let mut sorted = members.to_vec();
+    sorted.sort_unstable();
+    let mut remap: BTreeMap<usize, u32> = BTreeMap::new();
+    let mut mesh = Mesh::default();
+    for face in sorted.iter().map(|&f| &faces[f]) {
+        let [a, b, c] = face.vertices;
+        let wound = if face.normal.dot(facing) < 0.0 { [a, c, b] } else { [a, b, c] };
+        for v in wound {
+            let index = *remap.entry(v).or_insert_with(|| {
+                let p = positions[v];
+                mesh.positions.push([p.x, p.y, p.z]);
+                (mesh.positions.len() - 1) as u32
+            });
+            mesh.indices.push(index);
+        }
+    }
+    mesh
+}
+
+/// Merges vertices on a `weld_mm` grid; triangles that collapse are dropped.
+fn weld(mesh: &Mesh, weld_mm: f32) -> (Vec<Vector3<f32>>, Vec<[usize; 3]>) {
+    let cell = weld_mm.max(1e-3);
+    let mut by_cell: HashMap<(i64, i64, i64), usize> = HashMap::new();
+    let mut positions = Vec::new();
+    let welded: Vec<usize> = mesh
+        .positions
+        .iter()
+        .map(|p| {
+            let key = (
+                (p[0] / cell).round() as i64,
+                (p[1] / cell).round() as i64,
+                (p[2] / cell).round() as i64,
+            );
+            *by_cell.entry(key).or_insert_with(|| {
+                positions.push(Vector3::new(p[0], p[1], p[2]));
+                positions.len() - 1
+            })
+        })
+        .collect();
+
+    let triangles = mesh
+        .indices
+        .chunks_exact(3)
+        .filter(|t| t.iter().all(|&i| (i as usize) < welded.len()))
+        .map(|t| [welded[t[0] as usize], welded[t[1] as usize], welded[t[2] as usize]])
+        .filter(|[a, b, c]| a != b && b != c && a != c)
+        .collect();
+    (positions, triangles)
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    if a < b { (a, b) } else { (b, a) }
+}
@@ -0,0 +1,430 @@
+use crate::layout::keep_out::{KeepOutKind, KeepOutZone};
+use geo::{BoundingRect, Contains};
+use geo_types::{Point, Polygon, Rect};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// 通行分析参数
+#[derive(Clone, Debug)]
+pub struct CirculationConfig {
+    /// 栅格边长（mm）
+    pub cell_mm: f32,
+    /// 通道最小宽度（mm）
+    pub min_corridor_mm: f32,
+    /// 禁止区底面高于该高度的家具（吊灯、壁挂）不阻挡通行（mm，相对地面）
+    pub headroom_mm: f32,
+    /// 作为硬约束：有门无法互通或家具无法从门口到达时剪枝
+    pub hard: bool,
+}
+
+impl Default for CirculationConfig {
+    fn default() -> Self {
+        Self {
+            cell_mm: 100.0,
+            min_corridor_mm: 800.0,
+            headroom_mm: 2000.0,
+            hard: false,
+        }
+    }
+}
+
+/// 一件家具的可达性
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemPath {
+    /// 在 `LayoutState::placed` 中的下标
+    pub index: usize,
+    pub reachable: bool,
+    /// 从最近的门到限制区的路径长度（mm）；不可达或无需通行时为 None
+    pub length_mm: Option<f32>,
+    /// 路径上（门前通行区之外）最窄处的通道宽度（mm）
+    pub min_width_mm: Option<f32>,
+    /// 路径折线（XZ，从门口到家具）
+    pub polyline: Vec<[f32; 2]>,
+}
+
+/// 整个布局的通行结果
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CirculationReport {
+    /// 参与计算的门数；为 0 时无从判断，所有家具视为可达
+    pub doors: usize,
+    /// 所有门之间是否有足够宽的通道互通
+    pub doors_connected: bool,
+    pub paths: Vec<ItemPath>,
+}
+
+impl CirculationReport {
+    pub fn is_walkable(&self) -> bool {
+        self.doors_connected && self.paths.iter().all(|p| p.reachable)
+    }
+
+    /// 可达家具的比例，没有家具时为 1
+    pub fn reachable_fraction(&self) -> f32 {
+        if self.paths.is_empty() {
+            return 1.0;
+        }
+        let reachable = self.paths.iter().filter(|p| p.reachable).count();
+        reachable as f32 / self.paths.len() as f32
+    }
+
+    /// 可达家具的路径折线，用于调试导出
+    pub fn polylines(&self) -> impl Iterator<Item = &[[f32; 2]]> {
+        self.paths
+            .iter()
+            .filter(|p| p.polyline.len() > 1)
+            .map(|p| p.polyline.as_slice())
+    }
+}
+
+/// 家具的通行目标：限制区中不属于禁止区的部分
+#[derive(Clone, Debug)]
+pub struct AccessZone {
+    pub restricted: Polygon<f64>,
+    pub forbidden: Polygon<f64>,
+}
+
+/// 地面的二维可通行栅格：房间轮廓减去占用区域
+///
+/// 每个格子以中心点判断是否被占用，并记录到最近占用格的距离（8 邻域近似欧氏距离）。
+#[derive(Clone, Debug)]
+pub struct FreeSpaceGrid {
+    origin: [f32; 2],
+    cell: f32,
+    cols: usize,
+    rows: usize,
+    free: Vec<bool>,
+    /// 格子中心到最近占用格中心的距离（mm），栅格外视为占用
+    clearance: Vec<f32>,
+}
+
+impl FreeSpaceGrid {
+    /// `floors` 为可行走的地面轮廓，`obstacles` 为占用区域（均为 XZ）
+    pub fn build<'a>(
+        floors: &[Polygon<f64>],
+        obstacles: impl IntoIterator<Item = &'a Polygon<f64>>,
+        cell_mm: f32,
+    ) -> Self {
+        let cell = cell_mm.max(1.0);
+        let bounds = floors.iter().filter_map(|f| f.bounding_rect()).reduce(|a, b| {
+            Rect::new(
+                (a.min().x.min(b.min().x), a.min().y.min(b.min().y)),
+                (a.max().x.max(b.max().x), a.max().y.max(b.max().y)),
+            )
+        });
+        let (origin, cols, rows) = match bounds {
+            Some(b) => (
+                [b.min().x as f32, b.min().y as f32],
+                ((b.width() as f32 / cell).ceil() as usize).max(1),
+                ((b.height() as f32 / cell).ceil() as usize).max(1),
+            ),
+            None => ([0.0; 2], 0, 0),
+        };
+
+        let mut grid = Self {
+            origin,
+            cell,
+            cols,
+            rows,
+            free: vec![false; cols * rows],
+            clearance: vec![0.0; cols * rows],
+        };
+        for idx in 0..cols * rows {
+            let p = grid.point(idx);
+            grid.free[idx] = floors.iter().any(|f| f.contains(&p));
+        }
+        for obstacle in obstacles {
+            for idx in grid.cells_in(obstacle) {
+                grid.free[idx] = false;
+            }
+        }
+
+        let blocked: Vec<usize> = (0..cols * rows).filter(|i| !grid.free[*i]).collect();
+        let distance = grid.propagate(&blocked, |_| true).distance;
+        grid.clearance = (0..cols * rows)
+            .map(|idx| {
+                let (c, r) = (idx % cols, idx / cols);
+                let edge = (c.min(r).min(cols - 1 - c).min(rows - 1 - r) + 1) as f32 * cell;
+                distance[idx].min(edge)
+            })
+            .collect();
+        grid
+    }
+
+    pub fn cell_mm(&self) -> f32 {
+        self.cell
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_free(&self, idx: usize) -> bool {
+        self.free.get(idx).copied().unwrap_or(false)
+    }
+
+    /// 以该格为中心的通道宽度（mm）：到两侧占用格边缘的距离之和
+    pub fn width_at(&self, idx: usize) -> f32 {
+        if !self.is_free(idx) {
+            return 0.0;
+        }
+        2.0 * (self.clearance[idx] - self.cell * 0.5).max(0.0)
+    }
+
+    /// 格子中心（XZ）
+    pub fn center(&self, idx: usize) -> [f32; 2] {
+        let (c, r) = (idx % self.cols, idx / self.cols);
+        [
+            self.origin[0] + (c as f32 + 0.5) * self.cell,
+            self.origin[1] + (r as f32 + 0.5) * self.cell,
+        ]
+    }
+
+    fn point(&self, idx: usize) -> Point<f64> {
+        let [x, z] = self.center(idx);
+        Point::new(x as f64, z as f64)
+    }
+
+    /// 中心落在 `polygon` 内的格子
+    pub fn cells_in(&self, polygon: &Polygon<f64>) -> Vec<usize> {
+        let Some(rect) = polygon.bounding_rect() else {
+            return Vec::new();
+        };
+        if self.cols == 0 {
+            return Vec::new();
+        }
+        let col = |x: f64| ((x as f32 - self.origin[0]) / self.cell - 0.5).floor();
+        let row = |z: f64| ((z as f32 - self.origin[1]) / self.cell - 0.5).floor();
+        let (c0, c1) = (col(rect.min().x).max(0.0), col(rect.max().x) + 1.0);
+        let (r0, r1) = (row(rect.min().y).max(0.0), row(rect.max().y) + 1.0);
+        if c1 < 0.0 || r1 < 0.0 {
+            return Vec::new();
+        }
+        let c1 = (c1 as usize).min(self.cols - 1);
+        let r1 = (r1 as usize).min(self.rows - 1);
+        let mut cells = Vec::new();
+        for r in r0 as usize..=r1 {
+            for c in c0 as usize..=c1 {
+                let idx = r * self.cols + c;
+                if polygon.contains(&self.point(idx)) {
+                    cells.push(idx);
+                }
+            }
+        }
+        cells
+    }
+
+    /// 多源最短路（8 邻域，代价为中心距离），只经过 `passable` 的格子
+    fn propagate(&self, sources: &[usize], passable: impl Fn(usize) -> bool) -> Field {
+        let n = self.cols * self.rows;
+        let mut field = Field {
+            distance: vec![f32::INFINITY; n],
+            previous: (0..n).collect(),
+            source: (0..n).collect(),
+        };
+        let mut heap = BinaryHeap::new();
+        for &s in sources {
+            if s < n && field.distance[s] > 0.0 {
+                field.distance[s] = 0.0;
+                heap.push(Visit { cost: 0.0, idx: s });
+            }
+        }
+        let diagonal = self.cell * std::f32::consts::SQRT_2;
+        while let Some(Visit { cost, idx }) = heap.pop() {
+            if cost > field.distance[idx] {
+                continue;
+            }
+            let (c, r) = ((idx % self.cols) as isize, (idx / self.cols) as isize);
+            for (dc, dr) in NEIGHBOURS {
+                let (nc, nr) = (c + dc, r + dr);
+                if nc < 0 || nr < 0 || nc >= self.cols as isize || nr >= self.rows as isize {
+                    continue;
+                }
+                let next = nr as usize * self.cols + nc as usize;
+                if !passable(next) {
+                    continue;
+                }
+                let step = if dc != 0 && dr != 0 { diagonal } else { self.cell };
+                let cost = cost + step;
+                if cost < field.distance[next] {
+                    field.distance[next] = cost;
+                    field.previous[next] = idx;
+                    field.source[next] = field.source[idx];
+                    heap.push(Visit { cost, idx: next });
+                }
+            }
+        }
+        field
+    }
+}
+
+/// 一次最短路的结果：距离、前驱格与出发的源格（源格的前驱是自身）
+struct Field {
+    distance: Vec<f32>,
+    previous: Vec<usize>,
+    source: Vec<usize>,
+}
+
+impl Field {
+    fn reached(&self, idx: usize) -> bool {
+        self.distance[idx].is_finite()
+    }
+
+    /// 从 `idx` 沿前驱走回源格
+    fn trace(&self, mut idx: usize) -> Vec<usize> {
+        let mut cells = vec![idx];
+        while self.previous[idx] != idx {
+            idx = self.previous[idx];
+            cells.push(idx);
+        }
+        cells
+    }
+}
+
+const NEIGHBOURS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(PartialEq)]
+struct Visit {
+    cost: f32,
+    idx: usize,
+}
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    // 最小堆：代价小的先出，代价相同按下标，保证结果确定
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.idx.cmp(&self.idx))
+    }
+}
+
+/// 从门口出发、沿宽度不小于 `min_corridor_mm` 的通道能否走到每件家具
+///
+/// 门前通行区内的格子总是可走（贴墙处本身不够宽）。走到的通道格向外
+/// 半个通道宽度内碰到家具的通行目标即视为可达；`access` 与已放家具一一对应，
+/// 通行目标内没有空闲格的家具（如没有限制区）无需通行，视为可达。
+pub fn analyze_circulation(
+    grid: &FreeSpaceGrid,
+    keep_outs: &[KeepOutZone],
+    access: &[AccessZone],
+    min_corridor_mm: f32,
+) -> CirculationReport {
+    let doors: Vec<Vec<usize>> = keep_outs
+        .iter()
+        .filter(|z| z.kind == KeepOutKind::DoorApproach)
+        .map(|z| {
+            let mut cells = grid.cells_in(&z.shape.polygon);
+            cells.retain(|idx| grid.is_free(*idx));
+            cells
+        })
+        .collect();
+    let targets: Vec<Vec<usize>> = access
+        .iter()
+        .map(|zone| {
+            let mut cells = grid.cells_in(&zone.restricted);
+            cells.retain(|idx| grid.is_free(*idx) && !zone.forbidden.contains(&grid.point(*idx)));
+            cells
+        })
+        .collect();
+    let unreachable = |index| ItemPath {
+        index,
+        reachable: false,
+        length_mm: None,
+        min_width_mm: None,
+        polyline: Vec::new(),
+    };
+    let vacuous = |index| ItemPath {
+        reachable: true,
+        ..unreachable(index)
+    };
+
+    if doors.is_empty() {
+        return CirculationReport {
+            doors: 0,
+            doors_connected: true,
+            paths: (0..access.len()).map(vacuous).collect(),
+        };
+    }
+
+    let mut door_cells = vec![false; grid.cols * grid.rows];
+    for &idx in doors.iter().flatten() {
+        door_cells[idx] = true;
+    }
+    let walkable = |idx: usize| door_cells[idx] || grid.width_at(idx) >= min_corridor_mm;
+
+    // 门之间的连通：从第一扇门出发能否走到其余每扇门
+    let first = grid.propagate(&doors[0], walkable);
+    let doors_connected = doors
+        .iter()
+        .all(|cells| cells.iter().any(|idx| first.reached(*idx)));
+
+    // 通道：从所有门出发；再从走到的通道格向外扩散，找到最近的目标格
+    let sources: Vec<usize> = doors.iter().flatten().copied().collect();
+    let corridor = grid.propagate(&sources, walkable);
+    let reached: Vec<usize> = (0..door_cells.len()).filter(|i| corridor.reached(*i)).collect();
+    let reach = min_corridor_mm * 0.5 + grid.cell * std::f32::consts::FRAC_1_SQRT_2;
+    let spread = grid.propagate(&reached, |idx| grid.is_free(idx));
+    let total = |idx: usize| spread.distance[idx] + corridor.distance[spread.source[idx]];
+
+    let paths = targets
+        .iter()
+        .enumerate()
+        .map(|(index, cells)| {
+            if cells.is_empty() {
+                return vacuous(index);
+            }
+            let Some(target) = cells
+                .iter()
+                .copied()
+                .filter(|idx| spread.distance[*idx] <= reach)
+                .min_by(|a, b| total(*a).total_cmp(&total(*b)).then_with(|| a.cmp(b)))
+            else {
+                return unreachable(index);
+            };
+            let mut path = corridor.trace(spread.source[target]);
+            path.reverse();
+            let min_width = path
+                .iter()
+                .filter(|idx| !door_cells[**idx])
+                .map(|idx| grid.width_at(*idx))
+                .reduce(f32::min)
+                .unwrap_or_else(|| grid.width_at(path[0]));
+            let mut tail = spread.trace(target);
+            tail.reverse();
+            path.extend(tail.into_iter().skip(1));
+            ItemPath {
+                index,
+                reachable: true,
+                length_mm: Some(total(target)),
+                min_width_mm: Some(min_width),
+                polyline: path.into_iter().map(|idx| grid.center(idx)).collect(),
+            }
+        })
+        .collect();
+
+    CirculationReport {
+        doors: doors.len(),
+        doors_connected,
+        paths,
+    }
+}
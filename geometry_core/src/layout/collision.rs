@@ -0,0 +1,238 @@
+use crate::models::mesh::Mesh;
+use crate::models::placement_region::{PlacementRegion, Region, SdfGrid};
+use nalgebra::{Matrix4, Point3, Vector3};
+
+/// 穿透小于该值（mm）视为贴合而非重叠，吸收 SDF 插值误差
+pub const CONTACT_TOLERANCE_MM: f32 = 1.0;
+
+/// SDF 窄带的半宽（体素数），与 vdb_core 建网格时的 band 一致；窄带外的值被截断
+pub const SDF_BAND_VOXELS: f32 = 3.0;
+
+/// 两个区域的相交结果
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Contact {
+    pub overlaps: bool,
+    /// 穿透深度（mm），不相交时为 0；超出 SDF 窄带时取包围盒的穿透深度
+    pub penetration: f32,
+}
+
+impl Contact {
+    fn from_depth(depth: f32) -> Self {
+        let penetration = depth.max(0.0);
+        Self {
+            overlaps: penetration > CONTACT_TOLERANCE_MM,
+            penetration,
+        }
+    }
+}
+
+/// 两件家具之间的全部区域关系
+///
+/// 规则：禁止区与禁止区不得相交；限制区可以与限制区相交，
+/// 但不得与另一件家具的禁止区相交。
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PairCollision {
+    /// a 禁止区 vs b 禁止区
+    pub forbidden: Contact,
+    /// a 限制区 vs b 禁止区
+    pub a_clearance: Contact,
+    /// b 限制区 vs a 禁止区
+    pub b_clearance: Contact,
+    /// a 限制区 vs b 限制区（允许相交，仅供参考）
+    pub clearances: Contact,
+}
+
+impl PairCollision {
+    pub fn is_legal(&self) -> bool {
+        !self.forbidden.overlaps && !self.a_clearance.overlaps && !self.b_clearance.overlaps
+    }
+
+    /// 违规关系中的最大穿透深度，合法时为 0
+    pub fn violation_depth(&self) -> f32 {
+        [self.forbidden, self.a_clearance, self.b_clearance]
+            .iter()
+            .filter(|c| c.overlaps)
+            .map(|c| c.penetration)
+            .fold(0.0, f32::max)
+    }
+}
+
+/// 按世界矩阵（列主序，`m[3]` 为平移）摆放两件家具并检查所有区域关系
+pub fn check_pair(
+    a: &PlacementRegion,
+    pose_a: &[[f32; 4]; 4],
+    b: &PlacementRegion,
+    pose_b: &[[f32; 4]; 4],
+) -> PairCollision {
+    let (ra, rb) = (&a.regions, &b.regions);
+    PairCollision {
+        forbidden: region_contact(&ra.forbidden_region, pose_a, &rb.forbidden_region, pose_b),
+        a_clearance: region_contact(&ra.restricted_region, pose_a, &rb.forbidden_region, pose_b),
+        b_clearance: region_contact(&rb.restricted_region, pose_b, &ra.forbidden_region, pose_a),
+        clearances: region_contact(&ra.restricted_region, pose_a, &rb.restricted_region, pose_b),
+    }
+}
+
+/// 两个摆放后的区域是否相交
+///
+/// 先做网格包围盒（OBB）的分离轴检测，分离即不相交；包围盒相交且两侧都有 SDF 时，
+/// 用一方表面（顶点、棱边与面内）的密集采样点查询另一方的 SDF，双向取最深，细化结果。
+/// SDF 只在窄带内有效：采样深度到达窄带边缘时真实深度未知，退回包围盒的穿透深度。
+pub fn region_contact(
+    a: &Region,
+    pose_a: &[[f32; 4]; 4],
+    b: &Region,
+    pose_b: &[[f32; 4]; 4],
+) -> Contact {
+    if a.mesh.positions.is_empty() || b.mesh.positions.is_empty() {
+        return Contact::default();
+    }
+    let (ma, mb) = (to_matrix(pose_a), to_matrix(pose_b));
+
+    let Some(obb_depth) = Obb::of(&a.mesh, &ma).penetration(&Obb::of(&b.mesh, &mb)) else {
+        return Contact::default();
+    };
+    if let (Some(sdf_a), Some(sdf_b)) = (&a.sdf, &b.sdf) {
+        let ab = sdf_depth(&a.mesh, &ma, b, sdf_b, &mb);
+        let ba = sdf_depth(&b.mesh, &mb, a, sdf_a, &ma);
+        if let (Some(ab), Some(ba)) = (ab, ba) {
+            let band = SDF_BAND_VOXELS * sdf_a.voxel_size.min(sdf_b.voxel_size);
+            let depth = ab.max(ba);
+            if depth < band - CONTACT_TOLERANCE_MM {
+                return Contact::from_depth(depth);
+            }
+            return Contact::from_depth(obb_depth.max(depth));
+        }
+    }
+    Contact::from_depth(obb_depth)
+}
+
+pub(crate) fn to_matrix(m: &[[f32; 4]; 4]) -> Matrix4<f32> {
+    Matrix4::from_fn(|row, col| m[col][row])
+}
+
+/// `mesh` 的表面点在 `target` 的 SDF 中的最大穿透深度；采样失败时为 None
+///
+/// 采样间距取 SDF 的体素尺寸，两件家具交叉（顶点都不在对方内部）时也能发现相交。
+/// 只采样落在 `target` 网格包围盒内的部分，包围盒外的点不会有穿透。
+fn sdf_depth(mesh: &Mesh, pose: &Matrix4<f32>, target: &Region, sdf: &SdfGrid, sdf_pose: &Matrix4<f32>) -> Option<f32> {
+    let to_sdf = sdf_pose.try_inverse()? * pose;
+    let (min, max) = mesh_bounds(&target.mesh);
+    let inside = |p: &Vector3<f32>| (0..3).all(|i| p[i] >= min[i] && p[i] <= max[i]);
+    let overlaps = |lo: &Vector3<f32>, hi: &Vector3<f32>| (0..3).all(|i| lo[i] <= max[i] && hi[i] >= min[i]);
+
+    let mut points: Vec<[f32; 3]> = Vec::new();
+    for tri in mesh.indices.chunks_exact(3) {
+        let corner = |i: u32| {
+            let p = mesh.positions.get(i as usize)?;
+            Some(to_sdf.transform_point(&Point3::from(*p)).coords)
+        };
+        let (Some(a), Some(b), Some(c)) = (corner(tri[0]), corner(tri[1]), corner(tri[2])) else {
+            continue;
+        };
+        if !overlaps(&a.inf(&b).inf(&c), &a.sup(&b).sup(&c)) {
+            continue;
+        }
+        points.extend(
+            triangle_samples(&a, &b, &c, sdf.voxel_size)
+                .filter(inside)
+                .map(|p| [p.x, p.y, p.z]),
+        );
+    }
+    if points.is_empty() {
+        return Some(0.0);
+    }
+    let values = sdf.grid.sample_world(&points).ok()?;
+    Some(values.into_iter().fold(0.0, |depth, d| depth.max(-d)))
+}
+
+/// 顶点加三角形重心
+pub(crate) fn surface_samples(mesh: &Mesh) -> impl Iterator<Item = Vector3<f32>> + '_ {
+    let vertices = mesh.positions.iter().map(|p| Vector3::from(*p));
+    let centroids = mesh.indices.chunks_exact(3).filter_map(|tri| {
+        let corner = |i: u32| mesh.positions.get(i as usize).map(|p| Vector3::from(*p));
+        Some((corner(tri[0])? + corner(tri[1])? + corner(tri[2])?) / 3.0)
+    });
+    vertices.chain(centroids)
+}
+
+/// 三角形按重心坐标网格采样（含顶点与棱边），相邻点间距不超过 `spacing`
+fn triangle_samples<'a>(
+    a: &'a Vector3<f32>,
+    b: &'a Vector3<f32>,
+    c: &'a Vector3<f32>,
+    spacing: f32,
+) -> impl Iterator<Item = Vector3<f32>> + 'a {
+    let longest = (b - a).norm().max((c - b).norm()).max((a - c).norm());
+    let n = (longest / spacing.max(1.0)).ceil().max(1.0) as usize;
+    (0..=n).flat_map(move |i| {
+        (0..=n - i).map(move |j| {
+            let (u, v) = (i as f32 / n as f32, j as f32 / n as f32);
+            a + (b - a) * u + (c - a) * v
+        })
+    })
+}
+
+fn mesh_bounds(mesh: &Mesh) -> (Vector3<f32>, Vector3<f32>) {
+    mesh.positions.iter().fold(
+        (Vector3::repeat(f32::INFINITY), Vector3::repeat(f32::NEG_INFINITY)),
+        |(min, max), p| (min.inf(&Vector3::from(*p)), max.sup(&Vector3::from(*p))),
+    )
+}
+
+/// 有向包围盒：网格局部 AABB 经过位姿变换
+struct Obb {
+    center: Vector3<f32>,
+    axes: [Vector3<f32>; 3],
+    half: [f32; 3],
+}
+
+impl Obb {
+    fn of(mesh: &Mesh, pose: &Matrix4<f32>) -> Self {
+        let (min, max) = mesh_bounds(mesh);
+        let local_center = (min + max) * 0.5;
+        let local_half = (max - min) * 0.5;
+        let center = pose.transform_point(&Point3::from(local_center)).coords;
+
+        let mut axes = [Vector3::x(), Vector3::y(), Vector3::z()];
+        let mut half = [0.0; 3];
+        for i in 0..3 {
+            let column = pose.fixed_view::<3, 1>(0, i).into_owned();
+            let scale = column.norm();
+            if scale > f32::EPSILON {
+                axes[i] = column / scale;
+            }
+            half[i] = local_half[i] * scale;
+        }
+        Self { center, axes, half }
+    }
+
+    fn radius(&self, axis: &Vector3<f32>) -> f32 {
+        (0..3).map(|i| self.half[i] * self.axes[i].dot(axis).abs()).sum()
+    }
+
+    /// 分离轴检测：分离时为 None，否则为各轴上的最小重叠量
+    fn penetration(&self, other: &Obb) -> Option<f32> {
+        let offset = other.center - self.center;
+        let mut candidates: Vec<Vector3<f32>> = self.axes.iter().chain(&other.axes).copied().collect();
+        for a in &self.axes {
+            for b in &other.axes {
+                let cross = a.cross(b);
+                let norm = cross.norm();
+                if norm > 1e-4 {
+                    candidates.push(cross / norm);
+                }
+            }
+        }
+
+        let mut depth = f32::INFINITY;
+        for axis in &candidates {
+            let overlap = self.radius(axis) + other.radius(axis) - offset.dot(axis).abs();
+            if overlap <= 0.0 {
+                return None;
+            }
+            depth = depth.min(overlap);
+        }
+        Some(depth)
+    }
+}
@@ -0,0 +1,357 @@
+use crate::layout::context::LayoutContext;
+use crate::layout::placement::Placement;
+use geo::{Centroid, Contains, EuclideanDistance};
+use geo_types::{Point, Polygon};
+use std::collections::HashMap;
+use types::RegionsType;
+
+/// 家具局部坐标系中的正前方（+Z），侧向为 ±X
+const FORWARD_LOCAL: [f32; 2] = [0.0, 1.0];
+const SIDE_LOCAL: [f32; 2] = [1.0, 0.0];
+
+/// 约束强度：硬约束违反即剪枝，软约束按权重计入评分
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Strength {
+    Hard,
+    Soft { weight: f32 },
+}
+
+/// 主体（subject）相对目标（target）的关系；距离为禁止区轮廓之间的间隙（mm）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Relation {
+    /// 间隙不超过 `max_distance_mm`
+    Near { max_distance_mm: f32 },
+    /// 靠近且正前方指向目标中心
+    Faces { max_distance_mm: f32, max_angle_deg: f32 },
+    /// 主体与目标互相正对，中心距离在区间内
+    Opposite {
+        min_distance_mm: f32,
+        max_distance_mm: f32,
+        max_angle_deg: f32,
+    },
+    /// 主体位于目标的左侧或右侧；每个目标两侧都要有主体
+    Flanks { max_distance_mm: f32, max_angle_deg: f32 },
+    /// 主体叠放在目标上（中心落在目标轮廓内），两者之间不做碰撞检查
+    On,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Constraint {
+    /// 报告用的描述，如 "chair faces table"
+    pub label: String,
+    pub subject: RegionsType,
+    pub target: RegionsType,
+    pub relation: Relation,
+    pub strength: Strength,
+}
+
+impl Constraint {
+    pub fn is_hard(&self) -> bool {
+        self.strength == Strength::Hard
+    }
+}
+
+/// 单条约束在一组摆放上的结果（按实例计数）
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ConstraintStatus {
+    pub satisfied: usize,
+    /// 不满足且已无可放的目标
+    pub violated: usize,
+    /// 尚未放置，或等待目标放置后才能判断
+    pub pending: usize,
+}
+
+impl ConstraintStatus {
+    pub fn total(&self) -> usize {
+        self.satisfied + self.violated + self.pending
+    }
+
+    /// 满足比例，没有任何实例时为 1
+    pub fn fraction(&self) -> f32 {
+        match self.total() {
+            0 => 1.0,
+            total => self.satisfied as f32 / total as f32,
+        }
+    }
+}
+
+/// 一组关系约束，按 `regions_type` 作用于所有同类家具
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintSet {
+    constraints: Vec<Constraint>,
+}
+
+impl ConstraintSet {
+    pub fn new(constraints: Vec<Constraint>) -> Self {
+        Self { constraints }
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    pub fn len(&self) -> usize {
+        self.constraints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+
+    pub fn has_hard(&self) -> bool {
+        self.constraints.iter().any(Constraint::is_hard)
+    }
+
+    pub fn has_soft(&self) -> bool {
+        self.constraints.iter().any(|c| !c.is_hard())
+    }
+
+    /// `subject` 是否可以叠放在 `target` 上
+    pub fn stacks(&self, subject: RegionsType, target: RegionsType) -> bool {
+        self.constraints
+            .iter()
+            .any(|c| c.relation == Relation::On && c.subject == subject && c.target == target)
+    }
+
+    /// 逐条评估；`pending` 为 item_id → 待放数量
+    pub fn evaluate(
+        &self,
+        context: &LayoutContext,
+        placed: &[Placement],
+        pending: &HashMap<u32, u32>,
+    ) -> Vec<ConstraintStatus> {
+        let posed = posed_items(context, placed);
+        let remaining = pending_by_type(context, pending);
+        self.constraints
+            .iter()
+            .map(|c| evaluate_one(c, &posed, &remaining))
+            .collect()
+    }
+
+    /// 是否有硬约束已被违反
+    pub fn violates_hard(
+        &self,
+        context: &LayoutContext,
+        placed: &[Placement],
+        pending: &HashMap<u32, u32>,
+    ) -> bool {
+        if !self.has_hard() {
+            return false;
+        }
+        let posed = posed_items(context, placed);
+        let remaining = pending_by_type(context, pending);
+        self.constraints
+            .iter()
+            .filter(|c| c.is_hard())
+            .any(|c| evaluate_one(c, &posed, &remaining).violated > 0)
+    }
+
+    /// 软约束的加权满足比例，没有软约束时为 1
+    pub fn soft_score(
+        &self,
+        context: &LayoutContext,
+        placed: &[Placement],
+        pending: &HashMap<u32, u32>,
+    ) -> f32 {
+        let posed = posed_items(context, placed);
+        let remaining = pending_by_type(context, pending);
+        let (mut sum, mut total) = (0.0, 0.0);
+        for c in &self.constraints {
+            let Strength::Soft { weight } = c.strength else {
+                continue;
+            };
+            sum += weight * evaluate_one(c, &posed, &remaining).fraction();
+            total += weight;
+        }
+        if total > 0.0 { sum / total } else { 1.0 }
+    }
+}
+
+/// 摆放后用于关系判断的几何
+struct PosedItem {
+    regions_type: RegionsType,
+    center: [f32; 2],
+    theta: f32,
+    outline: Option<Polygon<f64>>,
+}
+
+impl PosedItem {
+    fn axis(&self, local: [f32; 2]) -> [f32; 2] {
+        let (s, c) = self.theta.sin_cos();
+        [local[0] * c - local[1] * s, local[0] * s + local[1] * c]
+    }
+
+    fn gap(&self, other: &PosedItem) -> f32 {
+        match (&self.outline, &other.outline) {
+            (Some(a), Some(b)) => a.euclidean_distance(b) as f32,
+            _ => distance(self.center, other.center),
+        }
+    }
+
+    /// 从自身中心看向 `other` 中心的方向与 `local` 轴的夹角（度）
+    fn angle_to(&self, local: [f32; 2], other: &PosedItem) -> f32 {
+        angle_deg(self.axis(local), sub(other.center, self.center))
+    }
+
+    fn supports(&self, other: &PosedItem) -> bool {
+        let (Some(outline), Some(top)) = (&self.outline, &other.outline) else {
+            return false;
+        };
+        contains_centroid(outline, top)
+    }
+
+    /// `other` 在自身的哪一侧（+1 / -1），不在两侧时为 None
+    fn side_of(&self, other: &PosedItem, max_angle_deg: f32) -> Option<i8> {
+        let dir = sub(other.center, self.center);
+        let side = self.axis(SIDE_LOCAL);
+        if angle_deg(side, dir) <= max_angle_deg {
+            Some(1)
+        } else if angle_deg([-side[0], -side[1]], dir) <= max_angle_deg {
+            Some(-1)
+        } else {
+            None
+        }
+    }
+}
+
+impl Relation {
+    fn holds(&self, subject: &PosedItem, target: &PosedItem) -> bool {
+        match *self {
+            Relation::Near { max_distance_mm } => subject.gap(target) <= max_distance_mm,
+            Relation::Faces {
+                max_distance_mm,
+                max_angle_deg,
+            } => {
+                subject.gap(target) <= max_distance_mm
+                    && subject.angle_to(FORWARD_LOCAL, target) <= max_angle_deg
+            }
+            Relation::Opposite {
+                min_distance_mm,
+                max_distance_mm,
+                max_angle_deg,
+            } => {
+                let d = distance(subject.center, target.center);
+                (min_distance_mm..=max_distance_mm).contains(&d)
+                    && subject.angle_to(FORWARD_LOCAL, target) <= max_angle_deg
+                    && target.angle_to(FORWARD_LOCAL, subject) <= max_angle_deg
+            }
+            Relation::Flanks {
+                max_distance_mm,
+                max_angle_deg,
+            } => {
+                subject.gap(target) <= max_distance_mm
+                    && target.side_of(subject, max_angle_deg).is_some()
+            }
+            Relation::On => target.supports(subject),
+        }
+    }
+}
+
+fn evaluate_one(
+    constraint: &Constraint,
+    posed: &[PosedItem],
+    remaining: &HashMap<RegionsType, u32>,
+) -> ConstraintStatus {
+    let subjects: Vec<usize> = (0..posed.len())
+        .filter(|i| posed[*i].regions_type == constraint.subject)
+        .collect();
+    let targets: Vec<usize> = (0..posed.len())
+        .filter(|i| posed[*i].regions_type == constraint.target)
+        .collect();
+    let subjects_left = remaining.get(&constraint.subject).copied().unwrap_or(0);
+    let targets_left = remaining.get(&constraint.target).copied().unwrap_or(0) > 0;
+
+    let mut status = ConstraintStatus {
+        pending: subjects_left as usize,
+        ..ConstraintStatus::default()
+    };
+    for &s in &subjects {
+        let ok = targets
+            .iter()
+            .any(|&t| t != s && constraint.relation.holds(&posed[s], &posed[t]));
+        if ok {
+            status.satisfied += 1;
+        } else if targets_left {
+            status.pending += 1;
+        } else {
+            status.violated += 1;
+        }
+    }
+
+    // 两侧夹放：每个目标的左右两侧都要有主体
+    if let Relation::Flanks { max_angle_deg, .. } = constraint.relation {
+        for &t in &targets {
+            let mut sides = [false; 2];
+            for &s in &subjects {
+                if s != t && constraint.relation.holds(&posed[s], &posed[t]) {
+                    match posed[t].side_of(&posed[s], max_angle_deg) {
+                        Some(1) => sides[0] = true,
+                        Some(_) => sides[1] = true,
+                        None => {}
+                    }
+                }
+            }
+            if sides[0] && sides[1] {
+                status.satisfied += 1;
+            } else if subjects_left > 0 {
+                status.pending += 1;
+            } else {
+                status.violated += 1;
+            }
+        }
+    }
+    status
+}
+
+fn posed_items(context: &LayoutContext, placed: &[Placement]) -> Vec<PosedItem> {
+    placed
+        .iter()
+        .filter_map(|p| {
+            let regions_type = context.regions_type(p.item_id)?;
+            let outline = context
+                .footprint(p.item_id)
+                .filter(|fp| !fp.is_empty())
+                .map(|fp| fp.posed(&p.pose));
+            Some(PosedItem {
+                regions_type,
+                center: [p.pose.x, p.pose.y],
+                theta: p.pose.theta,
+                outline,
+            })
+        })
+        .collect()
+}
+
+fn pending_by_type(context: &LayoutContext, pending: &HashMap<u32, u32>) -> HashMap<RegionsType, u32> {
+    let mut by_type = HashMap::new();
+    for (&item_id, &count) in pending {
+        if let Some(regions_type) = context.regions_type(item_id) {
+            *by_type.entry(regions_type).or_insert(0) += count;
+        }
+    }
+    by_type
+}
+
+fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    let d = sub(a, b);
+    d[0].hypot(d[1])
+}
+
+/// 两个方向的夹角（度），零向量视为 0°
+fn angle_deg(a: [f32; 2], b: [f32; 2]) -> f32 {
+    let norm = a[0].hypot(a[1]) * b[0].hypot(b[1]);
+    if norm <= f32::EPSILON {
+        return 0.0;
+    }
+    let cos = (a[0] * b[0] + a[1] * b[1]) / norm;
+    cos.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// `outer` 是否包含 `inner` 的形心，用于判断叠放
+pub(crate) fn contains_centroid(outer: &Polygon<f64>, inner: &Polygon<f64>) -> bool {
+    inner.centroid().is_some_and(|c: Point<f64>| outer.contains(&c))
+}
@@ -0,0 +1,511 @@
+use crate::geometry_ops::hull::convex_hull_xz;
+use crate::geometry_ops::sampling::sample_points_uv;
+use crate::layout::circulation::CirculationConfig;
+use crate::layout::constraints::ConstraintSet;
+use crate::layout::geometry::{Footprint, PosedShape};
+use crate::layout::group::ItemGroup;
+use crate::layout::inventory::{initial_inventory, Demand};
+use crate::layout::keep_out::{derive_keep_outs, KeepOutConfig, KeepOutZone};
+use crate::layout::key::KeyConfig;
+use crate::layout::objective::{Objective, ObjectiveWeights};
+use crate::layout::placement::{PinnedPlacement, Placement};
+use crate::layout::shell::{Containment, RoomShell};
+use crate::layout::surface_index::{IndexedSurface, SurfaceIndex};
+use crate::layout::surface_pose::{SurfaceFrame, SurfaceKind, SurfacePose};
+use crate::models::mesh::Mesh;
+use crate::models::placement_region::{PlacementRegion, SdfGrid};
+use crate::models::space::Space;
+use geo::Contains;
+use geo_types::{Coord, LineString, Point, Polygon};
+use nalgebra::Vector3;
+use std::collections::HashMap;
+use types::{RegionsType, RegionsTypeMask};
+
+/// 候选生成参数
+#[derive(Clone, Debug)]
+pub struct CandidateConfig {
+    /// 表面采样步长（mm）
+    pub sample_step_mm: f32,
+    /// 候选旋转角（弧度）
+    pub thetas: Vec<f32>,
+    /// 每个节点最多保留的子节点数
+    pub max_children: usize,
+    /// 采样点的随机抖动幅度（mm），0 表示严格网格
+    pub jitter_mm: f32,
+}
+
+impl Default for CandidateConfig {
+    fn default() -> Self {
+        Self {
+            sample_step_mm: 100.0,
+            thetas: vec![
+                0.0,
+                std::f32::consts::FRAC_PI_2,
+                std::f32::consts::PI,
+                std::f32::consts::PI * 1.5,
+            ],
+            max_children: 128,
+            jitter_mm: 0.0,
+        }
+    }
+}
+
+/// 可承载家具的表面：地面、墙面或顶面
+#[derive(Clone, Debug)]
+pub struct SupportSurface {
+    pub mesh_index: usize,
+    pub mask: RegionsTypeMask,
+    pub frame: SurfaceFrame,
+    /// 轮廓，内环为洞（柱子、门窗洞口等），家具不能压到：
+    /// 水平表面为 XZ 平面上的俯视轮廓，墙面为 `frame` 的 (u, v)
+    pub outline: Polygon<f64>,
+    /// 表面的平均 Y 高度
+    pub elevation: f32,
+    /// 采样得到的候选位置，坐标为 `frame` 的 (u, v)
+    pub samples: Vec<[f32; 2]>,
+}
+
+impl SupportSurface {
+    pub fn kind(&self) -> SurfaceKind {
+        self.frame.kind
+    }
+}
+
+/// 搜索期间只读、所有节点共享的上下文
+#[derive(Debug)]
+pub struct LayoutContext {
+    pub space: Space,
+    /// item_id 即下标
+    pub items: Vec<PlacementRegion>,
+    /// 每个 item 的需求数量，与 `items` 一一对应
+    pub demands: Vec<Demand>,
+    /// 禁止区轮廓，与 `items` 一一对应
+    pub footprints: Vec<Footprint>,
+    /// 限制区（留空）轮廓，与 `items` 一一对应
+    pub clearances: Vec<Footprint>,
+    pub surfaces: Vec<SupportSurface>,
+    /// 每个 `space.meshes` 的局部坐标系，非平面网格为 None
+    pub frames: Vec<Option<SurfaceFrame>>,
+    /// RegionsType → 兼容表面（含墙面、顶面）
+    pub surface_index: SurfaceIndex,
+    /// 房间壳体，用于检查家具是否留在房间内
+    pub shell: RoomShell,
+    /// 门窗前的留空区，作为固定障碍参与碰撞检查
+    pub keep_outs: Vec<KeepOutZone>,
+    /// 通行分析参数
+    pub circulation: CirculationConfig,
+    pub candidates: CandidateConfig,
+    pub objective: Objective,
+    /// 家具之间的关系约束
+    pub constraints: ConstraintSet,
+    /// 组合家具：组合的 item_id → 组合定义（组合追加在 `items` 末尾）
+    pub groups: HashMap<u32, ItemGroup>,
+    /// 固定的摆放：每个状态的 `placed` 都以它们开头，不占待放清单
+    pub pinned: Vec<PinnedPlacement>,
+    /// 去重 key 的量化参数
+    pub key: KeyConfig,
+    /// 随机种子：同一种子得到相同的候选与搜索顺序
+    pub seed: u64,
+}
+
+impl LayoutContext {
+    pub fn new(space: Space, items: Vec<PlacementRegion>, candidates: CandidateConfig) -> Self {
+        let footprints = items
+            .iter()
+            .map(|item| footprint_of(&item.regions.forbidden_region.mesh))
+            .collect();
+        let clearances = items
+            .iter()
+            .map(|item| footprint_of(&item.regions.restricted_region.mesh))
+            .collect();
+        let demands = items.iter().map(|item| Demand::from_semantics(&item.semantics)).collect();
+        let frames = build_surface_frames(&space);
+        let surface_index = SurfaceIndex::build(&space, &frames);
+        let surfaces = build_support_surfaces(&space, &surface_index, candidates.sample_step_mm);
+        let shell = RoomShell::build(&space, &frames);
+        Self {
+            space,
+            items,
+            demands,
+            footprints,
+            clearances,
+            surfaces,
+            frames,
+            surface_index,
+            shell,
+            keep_outs: Vec::new(),
+            circulation: CirculationConfig::default(),
+            candidates,
+            objective: Objective::from_weights(&ObjectiveWeights::default(), RegionsTypeMask::NONE),
+            constraints: ConstraintSet::default(),
+            groups: HashMap::new(),
+            pinned: Vec::new(),
+            key: KeyConfig::default(),
+            seed: 0,
+        }
+    }
+
+    pub fn with_objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// 按门窗类型从 `space` 生成留空区（覆盖之前的设置）
+    pub fn with_keep_outs(mut self, config: &KeepOutConfig) -> Self {
+        self.keep_outs = derive_keep_outs(&self.space, &self.frames, self.floor_y(), config);
+        self
+    }
+
+    /// 最低的地面高度：占用与留空区的高度区间都相对它
+    pub fn floor_y(&self) -> f32 {
+        self.floor_surfaces().map(|s| s.elevation).reduce(f32::min).unwrap_or(0.0)
+    }
+
+    /// 朝上的承载表面
+    pub fn floor_surfaces(&self) -> impl Iterator<Item = &SupportSurface> {
+        self.surfaces.iter().filter(|s| s.kind() == SurfaceKind::Floor)
+    }
+
+    pub fn with_circulation(mut self, circulation: CirculationConfig) -> Self {
+        self.circulation = circulation;
+        self
+    }
+
+    /// 可行走的地面轮廓：优先用房间壳体的地面，否则退回承载表面
+    pub fn walkable_floors(&self) -> Vec<Polygon<f64>> {
+        if self.shell.floors().is_empty() {
+            self.floor_surfaces().map(|s| s.outline.clone()).collect()
+        } else {
+            self.shell.floors().to_vec()
+        }
+    }
+
+    /// 把每个组合作为一件新家具追加到 `items`，并从成员的需求中扣除组合占用的数量
+    ///
+    /// 成员的需求上限不足以组成 `count` 个组合时返回错误。
+    pub fn with_groups(mut self, groups: Vec<ItemGroup>) -> Result<Self, String> {
+        for group in groups {
+            if group.count == 0 {
+                continue;
+            }
+            let combined = group.combine(&self.items)?;
+            let mut used: HashMap<u32, u32> = HashMap::new();
+            for member in &group.members {
+                *used.entry(member.item_id).or_insert(0) += group.count;
+            }
+            let mut used: Vec<(u32, u32)> = used.into_iter().collect();
+            used.sort_unstable();
+            for (item_id, n) in used {
+                let demand = &mut self.demands[item_id as usize];
+                if demand.max < n {
+                    return Err(format!(
+                        "group '{}' needs {n} of item {item_id}, but at most {} are requested",
+                        group.name, demand.max
+                    ));
+                }
+                *demand = Demand::range(demand.min.saturating_sub(n), demand.max - n)
+                    .with_reward(demand.reward);
+            }
+
+            let item_id = self.items.len() as u32;
+            self.footprints.push(footprint_of(&combined.regions.forbidden_region.mesh));
+            self.clearances.push(footprint_of(&combined.regions.restricted_region.mesh));
+            self.demands.push(Demand::exact(group.count));
+            self.items.push(combined);
+            self.groups.insert(item_id, group);
+        }
+        Ok(self)
+    }
+
+    /// 固定一组摆放（覆盖之前的设置），item_id 不存在时返回错误
+    ///
+    /// 固定的家具作为障碍参与碰撞检查，本身不做合法性检查，也不会被退火移动。
+    /// 只有世界矩阵的固定摆放按矩阵恢复所在的墙面/顶面（见 `surface_pose_from_matrix`）。
+    pub fn with_pinned(mut self, pinned: Vec<PinnedPlacement>) -> Result<Self, String> {
+        if let Some(p) = pinned.iter().find(|p| p.placement.item_id as usize >= self.items.len()) {
+            return Err(format!("pinned item {} not found", p.placement.item_id));
+        }
+        self.pinned = pinned
+            .into_iter()
+            .map(|mut pin| {
+                let item_id = pin.placement.item_id;
+                let recovered = pin
+                    .matrix
+                    .filter(|_| pin.placement.surface.is_none())
+                    .and_then(|matrix| self.surface_pose_from_matrix(item_id, &matrix))
+                    .and_then(|pose| {
+                        let frame = self.surface_frame(pose.surface)?;
+                        Some(Placement::on_surface(item_id, pose, frame))
+                    });
+                if let Some(placement) = recovered {
+                    pin.placement = placement;
+                }
+                pin
+            })
+            .collect();
+        Ok(self)
+    }
+
+    /// `placed` 中第 `index` 件是否是固定的摆放
+    pub fn is_pinned(&self, index: usize, placement: &Placement) -> bool {
+        self.pinned.get(index).is_some_and(|pin| {
+            let (a, b) = (&pin.placement, placement);
+            a.item_id == b.item_id
+                && a.pose.x == b.pose.x
+                && a.pose.y == b.pose.y
+                && a.pose.theta == b.pose.theta
+                && a.surface == b.surface
+        })
+    }
+
+    /// 从世界矩阵恢复 item_id 在墙面/顶面上的表面位姿，地面摆放返回 None
+    ///
+    /// 在 item_id 可用的墙面和顶面中，取朝向与表面坐标系一致（墙面上局部 +Z 沿法向，
+    /// 顶面上局部 +Y 逆着法向）、原点投影落在表面轮廓内、且离表面最近的一个。
+    pub fn surface_pose_from_matrix(&self, item_id: u32, matrix: &[[f32; 4]; 4]) -> Option<SurfacePose> {
+        let axis = |i: usize| Vector3::new(matrix[i][0], matrix[i][1], matrix[i][2]);
+        let origin = axis(3);
+        self.surfaces_for(item_id)
+            .filter_map(|surface| {
+                let frame = &surface.frame;
+                let d = origin - frame.origin;
+                let (u, v, offset) = (d.dot(&frame.u_axis), d.dot(&frame.v_axis), d.dot(&frame.normal));
+                let (aligned, point) = match frame.kind {
+                    SurfaceKind::Floor => return None,
+                    SurfaceKind::Wall => (axis(2).dot(&frame.normal), Point::new(u as f64, v as f64)),
+                    SurfaceKind::Ceiling => (-axis(1).dot(&frame.normal), Point::new(origin.x as f64, origin.z as f64)),
+                };
+                if aligned < 0.99 || !surface.outline.contains(&point) {
+                    return None;
+                }
+                let x = axis(0);
+                let rotation = x.dot(&frame.v_axis).atan2(x.dot(&frame.u_axis));
+                let pose = SurfacePose::new(surface.mesh_index, u, v, rotation).with_offset(offset);
+                Some((offset.abs(), pose))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, pose)| pose)
+    }
+
+    pub fn group(&self, item_id: u32) -> Option<&ItemGroup> {
+        self.groups.get(&item_id)
+    }
+
+    pub fn with_constraints(mut self, constraints: ConstraintSet) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_key_config(mut self, key: KeyConfig) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// 附加房间壳体的 SDF（网格闭合时内部为负）
+    pub fn with_shell_sdf(mut self, sdf: SdfGrid) -> Self {
+        self.shell = std::mem::take(&mut self.shell).with_sdf(sdf);
+        self
+    }
+
+    pub fn demand(&self, item_id: u32) -> Option<&Demand> {
+        self.demands.get(item_id as usize)
+    }
+
+    /// 根节点的待放清单：每个 item 按上限数量，扣除已固定的件数
+    pub fn initial_inventory(&self) -> HashMap<u32, u32> {
+        let mut inventory = initial_inventory(&self.demands);
+        for pin in &self.pinned {
+            if let Some(count) = inventory.get_mut(&pin.placement.item_id) {
+                *count = count.saturating_sub(1);
+            }
+        }
+        inventory.retain(|_, count| *count > 0);
+        inventory
+    }
+
+    pub fn regions_type(&self, item_id: u32) -> Option<RegionsType> {
+        self.items.get(item_id as usize).map(|item| item.semantics.regions_type)
+    }
+
+    /// 可以放置 item_id 的承载表面：按表面索引查兼容表面，候选生成与退火都从这里取
+    pub fn surfaces_for(&self, item_id: u32) -> impl Iterator<Item = &SupportSurface> {
+        self.compatible_surfaces(item_id)
+            .filter_map(|surface| self.support_surface(surface.mesh_index))
+    }
+
+    /// 表面索引中可以放置 item_id 的表面
+    pub fn compatible_surfaces(&self, item_id: u32) -> impl Iterator<Item = &IndexedSurface> {
+        let regions_type = self.regions_type(item_id);
+        regions_type
+            .into_iter()
+            .flat_map(|id| self.surface_index.compatible(id))
+    }
+
+    /// `Space::meshes` 下标对应的承载表面
+    pub fn support_surface(&self, mesh_index: usize) -> Option<&SupportSurface> {
+        // `surfaces` 与表面索引一一对应，按 mesh 顺序排列
+        self.surfaces
+            .binary_search_by_key(&mesh_index, |s| s.mesh_index)
+            .ok()
+            .map(|slot| &self.surfaces[slot])
+    }
+
+    /// XZ 位置所在地面的高度，不在任何地面上时为 0
+    pub fn elevation_at(&self, x: f32, z: f32) -> f32 {
+        let point = Point::new(x as f64, z as f64);
+        self.floor_surfaces()
+            .find(|s| s.outline.contains(&point))
+            .map_or(0.0, |s| s.elevation)
+    }
+
+    pub fn surface_frame(&self, mesh_index: usize) -> Option<&SurfaceFrame> {
+        self.frames.get(mesh_index)?.as_ref()
+    }
+
+    /// 相对表面位姿的世界矩阵；表面不存在或非平面时为 None
+    pub fn surface_matrix(&self, pose: &SurfacePose) -> Option<[[f32; 4]; 4]> {
+        self.surface_frame(pose.surface).map(|frame| pose.to_matrix(frame))
+    }
+
+    /// 把 item_id 放到表面位姿上，沿法向的偏移按表面类型重新计算
+    ///
+    /// 地面上原点落在表面；墙面上禁止区背面贴墙；顶面上禁止区顶面贴顶（吊挂）。
+    pub fn place_on(&self, item_id: u32, pose: SurfacePose) -> Option<Placement> {
+        let frame = self.surface_frame(pose.surface)?;
+        let footprint = self.footprint(item_id)?;
+        let offset = match frame.kind {
+            SurfaceKind::Floor => 0.0,
+            SurfaceKind::Wall => -footprint.points.iter().map(|p| p[1]).fold(0.0, f32::min),
+            SurfaceKind::Ceiling => footprint.max_y,
+        };
+        Some(Placement::on_surface(item_id, pose.with_offset(offset), frame))
+    }
+
+    /// 摆放的原点相对 `floor_y` 的高度：地面摆放为所在地面的高度差
+    pub fn lift(&self, placement: &Placement) -> f32 {
+        let y = match &placement.surface {
+            Some(pose) => self.surface_matrix(pose).map_or(0.0, |m| m[3][1]),
+            None => self.elevation_at(placement.pose.x, placement.pose.y),
+        };
+        y - self.floor_y()
+    }
+
+    /// 把地面摆放换算成所在表面上的相对位姿；已带表面位姿的原样返回
+    pub fn surface_pose(&self, placement: &Placement) -> Option<SurfacePose> {
+        if let Some(pose) = &placement.surface {
+            return Some(pose.clone());
+        }
+        let pose = &placement.pose;
+        let point = Point::new(pose.x as f64, pose.y as f64);
+        let surface = self.floor_surfaces().find(|s| s.outline.contains(&point))?;
+        let frame = &surface.frame;
+        let d = Vector3::new(pose.x, frame.origin.y, pose.y) - frame.origin;
+        Some(SurfacePose::new(
+            surface.mesh_index,
+            d.dot(&frame.u_axis),
+            d.dot(&frame.v_axis),
+            pose.theta,
+        ))
+    }
+
+    /// 摆放的世界矩阵：优先按所在表面的坐标系计算，否则退回 `elevation_at`
+    pub fn placement_matrix(&self, placement: &Placement) -> [[f32; 4]; 4] {
+        self.surface_pose(placement)
+            .and_then(|pose| self.surface_matrix(&pose))
+            .unwrap_or_else(|| {
+                let pose = &placement.pose;
+                pose.to_matrix(self.elevation_at(pose.x, pose.y))
+            })
+    }
+
+    /// 摆放后的占用体积：俯视轮廓加上抬高后的高度区间
+    pub fn posed_shape(&self, footprint: &Footprint, placement: &Placement) -> PosedShape {
+        let shape = footprint.posed_shape(&placement.pose);
+        let lift = self.lift(placement);
+        PosedShape::new(shape.polygon, shape.min_y + lift, shape.max_y + lift)
+    }
+
+    /// 摆放后的禁止区是否留在房间内，以及到每面墙的距离
+    pub fn containment(&self, placement: &Placement) -> Option<Containment> {
+        let item = self.items.get(placement.item_id as usize)?;
+        Some(self.shell.contain(item, &self.placement_matrix(placement)))
+    }
+
+    /// 禁止区不穿过墙面、顶面，也不落到房间外；未知家具不做限制
+    pub fn is_contained(&self, placement: &Placement) -> bool {
+        self.containment(placement).is_none_or(|c| c.is_contained())
+    }
+
+    /// `shape` 是否压到门窗留空区
+    pub fn blocks_keep_out(&self, shape: &PosedShape) -> bool {
+        self.keep_outs.iter().any(|zone| zone.shape.overlaps(shape))
+    }
+
+    pub fn footprint(&self, item_id: u32) -> Option<&Footprint> {
+        self.footprints.get(item_id as usize)
+    }
+
+    pub fn clearance(&self, item_id: u32) -> Option<&Footprint> {
+        self.clearances.get(item_id as usize)
+    }
+}
+
+fn footprint_of(mesh: &Mesh) -> Footprint {
+    Footprint::from_hull_and_mesh(&convex_hull_xz(mesh), mesh)
+}
+
+fn build_surface_frames(space: &Space) -> Vec<Option<SurfaceFrame>> {
+    let interior = space.interior_point().map(Vector3::from).unwrap_or_default();
+    space
+        .meshes
+        .iter()
+        .map(|mesh| SurfaceFrame::fit(mesh, &interior))
+        .collect()
+}
+
+fn build_support_surfaces(space: &Space, index: &SurfaceIndex, step_mm: f32) -> Vec<SupportSurface> {
+    index
+        .surfaces()
+        .iter()
+        .map(|surface| {
+            let frame = &surface.frame;
+            // 水平表面用俯视轮廓做碰撞，墙面保留面内轮廓
+            let outline = if frame.kind == SurfaceKind::Wall {
+                surface.outline.clone()
+            } else {
+                let to_xz = |ring: &LineString<f64>| -> LineString<f64> {
+                    ring.coords()
+                        .map(|c| {
+                            let p = frame.point(c.x as f32, c.y as f32, 0.0);
+                            Coord {
+                                x: p.x as f64,
+                                y: p.z as f64,
+                            }
+                        })
+                        .collect()
+                };
+                Polygon::new(
+                    to_xz(surface.outline.exterior()),
+                    surface.outline.interiors().iter().map(to_xz).collect(),
+                )
+            };
+            let samples = sample_points_uv(&space.meshes[surface.mesh_index], step_mm)
+                .into_iter()
+                .map(|p| surface.to_uv(p))
+                .collect();
+            SupportSurface {
+                mesh_index: surface.mesh_index,
+                mask: surface.mask,
+                frame: frame.clone(),
+                outline,
+                elevation: frame.origin.y,
+                samples,
+            }
+        })
+        .collect()
+}
@@ -1,2 +1,319 @@
+use crate::layout::placement::Pose2D;
+use crate::models::mesh::Mesh;
+use geo::{Area, BoundingRect, Contains, Intersects};
+use geo_types::{Coord, LineString, Polygon};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// 已放家具的占用缓存：禁止区与限制区分层存放
+///
+/// 每层是持久化链表加网格索引，插入只新建少量节点，子状态与父状态共享其余部分，
+/// 因此 clone 只是增加引用计数。
+#[derive(Clone, Debug, Default)]
+pub struct GeometryCache {
+    forbidden: OccupancyLayer,
+    restricted: OccupancyLayer,
+}
+
+impl GeometryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一件已放置家具的禁止区与限制区
+    pub fn insert(&mut self, item_id: u32, forbidden: PosedShape, restricted: PosedShape) {
+        self.forbidden.insert(item_id, forbidden);
+        self.restricted.insert(item_id, restricted);
+    }
+
+    pub fn len(&self) -> usize {
+        self.forbidden.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn forbidden(&self) -> &OccupancyLayer {
+        &self.forbidden
+    }
+
+    pub fn restricted(&self) -> &OccupancyLayer {
+        &self.restricted
+    }
+
+    /// `shape` 是否与任何已放家具的禁止区重叠
+    pub fn overlaps_forbidden(&self, shape: &PosedShape) -> bool {
+        self.forbidden.overlaps(shape)
+    }
+
+    /// `shape` 是否与任何已放家具的限制区重叠
+    pub fn overlaps_restricted(&self, shape: &PosedShape) -> bool {
+        self.restricted.overlaps(shape)
+    }
+}
+
+/// 网格索引的格子边长（mm），与中等家具的尺寸相当，多数家具只落在 1~4 个格子里
+const GRID_CELL_MM: f64 = 1000.0;
+
+/// 单层占用：按插入顺序的持久化单链表（头部为最近插入），加一个按格子分桶的网格索引
+///
+/// 网格的每个格子也是持久化链表，插入时只复制格子目录，各格子的链表与父状态共享；
+/// 查询只访问与包围盒相交的格子。
+#[derive(Clone, Debug, Default)]
+pub struct OccupancyLayer {
+    head: Option<Arc<OccupancyNode>>,
+    len: usize,
+    grid: Arc<BTreeMap<(i64, i64), Arc<OccupancyNode>>>,
+}
+
+#[derive(Debug)]
+struct OccupancyNode {
+    entry: Arc<OccupiedEntry>,
+    next: Option<Arc<OccupancyNode>>,
+}
+
 #[derive(Clone, Debug)]
-pub struct GeometryCache;
+pub struct OccupiedEntry {
+    pub item_id: u32,
+    /// 插入顺序，即该家具在 `LayoutState::placed` 中的下标
+    pub index: usize,
+    pub shape: PosedShape,
+}
+
+impl OccupancyLayer {
+    pub fn insert(&mut self, item_id: u32, shape: PosedShape) {
+        let entry = Arc::new(OccupiedEntry { item_id, index: self.len, shape });
+        // 空轮廓的包围盒无效，不与任何东西相交，不进网格
+        if let Some(cells) = grid_cells(&entry.shape.bounds) {
+            let grid = Arc::make_mut(&mut self.grid);
+            for cell in cells {
+                let next = grid.remove(&cell);
+                let node = OccupancyNode {
+                    entry: Arc::clone(&entry),
+                    next,
+                };
+                grid.insert(cell, Arc::new(node));
+            }
+        }
+        let node = OccupancyNode {
+            entry,
+            next: self.head.take(),
+        };
+        self.head = Some(Arc::new(node));
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> OccupancyIter<'_> {
+        OccupancyIter {
+            node: self.head.as_deref(),
+        }
+    }
+
+    /// 包围盒与 `bounds` 相交的条目，每个条目只出现一次
+    ///
+    /// 跨多个格子的条目只在两个包围盒交集的左下角所在的格子里返回。
+    pub fn query<'a>(&'a self, bounds: &'a Aabb2) -> impl Iterator<Item = &'a OccupiedEntry> + 'a {
+        grid_cells(bounds).into_iter().flatten().flat_map(move |cell| {
+            let node = self.grid.get(&cell).map(|node| node.as_ref());
+            OccupancyIter { node }.filter(move |e| {
+                let b = &e.shape.bounds;
+                b.intersects(bounds) && cell_of([b.min[0].max(bounds.min[0]), b.min[1].max(bounds.min[1])]) == cell
+            })
+        })
+    }
+
+    pub fn overlaps(&self, shape: &PosedShape) -> bool {
+        self.overlaps_except(shape, |_| false)
+    }
+
+    /// 与 `shape` 重叠、且未被 `ignore` 排除的条目是否存在
+    pub fn overlaps_except(&self, shape: &PosedShape, ignore: impl Fn(&OccupiedEntry) -> bool) -> bool {
+        self.query(&shape.bounds)
+            .any(|e| e.shape.overlaps(shape) && !ignore(e))
+    }
+}
+
+pub struct OccupancyIter<'a> {
+    node: Option<&'a OccupancyNode>,
+}
+
+impl<'a> Iterator for OccupancyIter<'a> {
+    type Item = &'a OccupiedEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.node?;
+        self.node = node.next.as_deref();
+        Some(&node.entry)
+    }
+}
+
+fn cell_of(p: [f64; 2]) -> (i64, i64) {
+    ((p[0] / GRID_CELL_MM).floor() as i64, (p[1] / GRID_CELL_MM).floor() as i64)
+}
+
+/// 包围盒覆盖的格子；包围盒无效（空轮廓）时为 None
+fn grid_cells(bounds: &Aabb2) -> Option<impl Iterator<Item = (i64, i64)>> {
+    let finite = bounds.min.iter().chain(&bounds.max).all(|v| v.is_finite());
+    if !finite || bounds.min[0] > bounds.max[0] || bounds.min[1] > bounds.max[1] {
+        return None;
+    }
+    let (x0, z0) = cell_of(bounds.min);
+    let (x1, z1) = cell_of(bounds.max);
+    Some((x0..=x1).flat_map(move |x| (z0..=z1).map(move |z| (x, z))))
+}
+
+/// 摆放后的体积：XZ 多边形 + Y 高度区间
+#[derive(Clone, Debug)]
+pub struct PosedShape {
+    pub polygon: Polygon<f64>,
+    pub min_y: f32,
+    pub max_y: f32,
+    pub bounds: Aabb2,
+}
+
+impl PosedShape {
+    pub fn new(polygon: Polygon<f64>, min_y: f32, max_y: f32) -> Self {
+        let bounds = Aabb2::of(&polygon);
+        Self {
+            polygon,
+            min_y,
+            max_y,
+            bounds,
+        }
+    }
+
+    /// 高度区间与 XZ 轮廓都相交才算重叠（接触也算）
+    pub fn overlaps(&self, other: &PosedShape) -> bool {
+        self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+            && self.bounds.intersects(&other.bounds)
+            && self.polygon.intersects(&other.polygon)
+    }
+}
+
+/// XZ 平面上的轴对齐包围盒
+#[derive(Clone, Copy, Debug)]
+pub struct Aabb2 {
+    pub min: [f64; 2],
+    pub max: [f64; 2],
+}
+
+impl Aabb2 {
+    pub fn of(polygon: &Polygon<f64>) -> Self {
+        match polygon.bounding_rect() {
+            Some(rect) => Self {
+                min: [rect.min().x, rect.min().y],
+                max: [rect.max().x, rect.max().y],
+            },
+            None => Self {
+                min: [f64::INFINITY; 2],
+                max: [f64::NEG_INFINITY; 2],
+            },
+        }
+    }
+
+    pub fn union(&self, other: &Aabb2) -> Aabb2 {
+        Aabb2 {
+            min: [self.min[0].min(other.min[0]), self.min[1].min(other.min[1])],
+            max: [self.max[0].max(other.max[0]), self.max[1].max(other.max[1])],
+        }
+    }
+
+    pub fn intersects(&self, other: &Aabb2) -> bool {
+        self.min[0] <= other.max[0]
+            && other.min[0] <= self.max[0]
+            && self.min[1] <= other.max[1]
+            && other.min[1] <= self.max[1]
+    }
+}
+
+/// 家具在 XZ 平面上的局部轮廓（未摆放时的坐标），附带 Y 高度区间
+#[derive(Clone, Debug, Default)]
+pub struct Footprint {
+    pub points: Vec<[f32; 2]>,
+    pub min_y: f32,
+    pub max_y: f32,
+}
+
+impl Footprint {
+    /// 从 3D 点取 XZ 分量构造轮廓
+    pub fn from_points_xz(points: &[[f32; 3]]) -> Self {
+        let (min_y, max_y) = y_range(points);
+        Self {
+            points: points.iter().map(|p| [p[0], p[2]]).collect(),
+            min_y,
+            max_y,
+        }
+    }
+
+    /// 轮廓取 `hull`（XZ），高度取 `mesh` 的 Y 范围
+    pub fn from_hull_and_mesh(hull: &[[f32; 3]], mesh: &Mesh) -> Self {
+        let (min_y, max_y) = y_range(&mesh.positions);
+        Self {
+            points: hull.iter().map(|p| [p[0], p[2]]).collect(),
+            min_y,
+            max_y,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.len() < 3
+    }
+
+    pub fn area(&self) -> f32 {
+        self.polygon().unsigned_area() as f32
+    }
+
+    /// 局部坐标下的多边形
+    pub fn polygon(&self) -> Polygon<f64> {
+        to_polygon(self.points.iter().copied())
+    }
+
+    /// 按位姿变换到世界坐标
+    pub fn posed(&self, pose: &Pose2D) -> Polygon<f64> {
+        to_polygon(self.points.iter().map(|p| pose.transform_point(*p)))
+    }
+
+    /// 按位姿变换到世界坐标，保留高度区间
+    pub fn posed_shape(&self, pose: &Pose2D) -> PosedShape {
+        PosedShape::new(self.posed(pose), self.min_y, self.max_y)
+    }
+}
+
+/// 两个多边形是否相交（接触也算）
+pub fn polygons_overlap(a: &Polygon<f64>, b: &Polygon<f64>) -> bool {
+    a.intersects(b)
+}
+
+/// `inner` 是否完全位于 `outer` 内
+pub fn polygon_inside(outer: &Polygon<f64>, inner: &Polygon<f64>) -> bool {
+    outer.contains(inner)
+}
+
+fn to_polygon(points: impl Iterator<Item = [f32; 2]>) -> Polygon<f64> {
+    let coords: Vec<Coord<f64>> = points
+        .map(|p| Coord {
+            x: p[0] as f64,
+            y: p[1] as f64,
+        })
+        .collect();
+    Polygon::new(LineString::from(coords), vec![])
+}
+
+fn y_range(points: &[[f32; 3]]) -> (f32, f32) {
+    if points.is_empty() {
+        return (0.0, 0.0);
+    }
+    points.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), p| {
+        (lo.min(p[1]), hi.max(p[1]))
+    })
+}
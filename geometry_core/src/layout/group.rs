@@ -0,0 +1,168 @@
+use crate::layout::geometry::{polygon_inside, polygons_overlap, Footprint};
+use crate::layout::placement::{Placement, Pose2D};
+use crate::models::mesh::Mesh;
+use crate::models::placement_region::{
+    HeightRange, PlacementRegion, PlacementSemantics, Region, Regions, Visual,
+};
+use crate::rng::SeededRng;
+use geo_types::Polygon;
+
+/// 精修时每个成员尝试的随机偏移次数
+const JITTER_ATTEMPTS: usize = 8;
+
+/// 组合中的一件家具：相对组合原点的位姿
+#[derive(Clone, Debug)]
+pub struct GroupMember {
+    /// 成员对应的 item_id（`LayoutContext::items` 下标）
+    pub item_id: u32,
+    pub offset: Pose2D,
+}
+
+/// 家具组合（如餐桌椅、床与床头柜）：搜索时作为一个整体摆放
+///
+/// 第一个成员为锚点，组合的 `regions_type` 与之相同；
+/// 组合的禁止区/限制区是各成员按相对位姿合并后的网格。
+#[derive(Clone, Debug)]
+pub struct ItemGroup {
+    pub name: String,
+    pub members: Vec<GroupMember>,
+    /// 需要放置的组合数量
+    pub count: u32,
+    /// 精修时成员相对组合位置的最大随机偏移（mm），0 表示不精修
+    pub jitter_mm: f32,
+}
+
+impl ItemGroup {
+    /// 合并成员的网格，得到可作为单件家具搜索的 PlacementRegion
+    pub fn combine(&self, items: &[PlacementRegion]) -> Result<PlacementRegion, String> {
+        let members = self
+            .members
+            .iter()
+            .map(|m| {
+                items
+                    .get(m.item_id as usize)
+                    .map(|item| (item, &m.offset))
+                    .ok_or_else(|| format!("group '{}': item {} not found", self.name, m.item_id))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let Some((anchor, _)) = members.first() else {
+            return Err(format!("group '{}' has no members", self.name));
+        };
+
+        let merge = |mesh_of: fn(&PlacementRegion) -> &Mesh| {
+            let mut merged = Mesh::default();
+            for (item, offset) in &members {
+                append_posed(&mut merged, mesh_of(item), offset);
+            }
+            merged
+        };
+        let height_range = HeightRange {
+            min_y: members
+                .iter()
+                .map(|(item, _)| item.visual.height_range.min_y)
+                .fold(f32::INFINITY, f32::min),
+            max_y: members
+                .iter()
+                .map(|(item, _)| item.visual.height_range.max_y)
+                .fold(f32::NEG_INFINITY, f32::max),
+        };
+        Ok(PlacementRegion {
+            regions: Regions {
+                forbidden_region: Region {
+                    mesh: merge(|item| &item.regions.forbidden_region.mesh),
+                    sdf: None,
+                },
+                restricted_region: Region {
+                    mesh: merge(|item| &item.regions.restricted_region.mesh),
+                    sdf: None,
+                },
+            },
+            semantics: PlacementSemantics {
+                regions_type: anchor.semantics.regions_type,
+                count: self.count as i32,
+                min_count: None,
+                reward: 0.0,
+            },
+            visual: Visual {
+                footprint_2d: merge(|item| &item.visual.footprint_2d),
+                height_range,
+            },
+        })
+    }
+
+    /// 组合摆在 `pose` 时各成员的摆放
+    pub fn member_placements(&self, pose: &Pose2D) -> Vec<Placement> {
+        self.members
+            .iter()
+            .map(|m| Placement::new(m.item_id, compose(pose, &m.offset)))
+            .collect()
+    }
+
+    /// 在组合整体的禁止区内给成员加随机偏移
+    ///
+    /// 偏移后的成员禁止区必须仍在 `hull`（组合禁止区）内且不与其他成员相交；
+    /// 成员的限制区可能移出组合的限制区，因此还要通过 `accepts`，由调用方按成员自身的
+    /// 禁止区和限制区检查组合外的占用。尝试都失败时保持原位。
+    pub fn jitter_members(
+        &self,
+        pose: &Pose2D,
+        hull: &Polygon<f64>,
+        footprints: &[Footprint],
+        rng: &mut SeededRng,
+        accepts: impl Fn(&Placement) -> bool,
+    ) -> Vec<Placement> {
+        let exact = self.member_placements(pose);
+        if self.jitter_mm <= 0.0 {
+            return exact;
+        }
+        let outline = |p: &Placement| {
+            footprints
+                .get(p.item_id as usize)
+                .filter(|fp| !fp.is_empty())
+                .map(|fp| fp.posed(&p.pose))
+        };
+        let mut placed: Vec<Placement> = Vec::with_capacity(exact.len());
+        for (index, member) in exact.iter().enumerate() {
+            // 兄弟成员：已精修的用新位置，未精修的用原位置
+            let siblings: Vec<Polygon<f64>> = placed
+                .iter()
+                .chain(&exact[index + 1..])
+                .filter_map(outline)
+                .collect();
+            let fits = |p: &Placement| match outline(p) {
+                Some(shape) => {
+                    polygon_inside(hull, &shape) && !siblings.iter().any(|s| polygons_overlap(s, &shape))
+                }
+                None => true,
+            };
+            let moved = (0..JITTER_ATTEMPTS)
+                .map(|_| {
+                    let pose = Pose2D::new(
+                        member.pose.x + rng.range_f32(-self.jitter_mm, self.jitter_mm),
+                        member.pose.y + rng.range_f32(-self.jitter_mm, self.jitter_mm),
+                        member.pose.theta,
+                    );
+                    Placement::new(member.item_id, pose)
+                })
+                .find(|p| fits(p) && accepts(p));
+            placed.push(moved.unwrap_or_else(|| member.clone()));
+        }
+        placed
+    }
+}
+
+/// 位姿复合：先按 `inner` 放在组合局部坐标系，再整体按 `outer` 摆放
+pub fn compose(outer: &Pose2D, inner: &Pose2D) -> Pose2D {
+    let [x, y] = outer.transform_point([inner.x, inner.y]);
+    Pose2D::new(x, y, outer.theta + inner.theta)
+}
+
+/// 把 `mesh` 按 `offset`（绕 Y 旋转后平移）变换后追加到 `merged`
+fn append_posed(merged: &mut Mesh, mesh: &Mesh, offset: &Pose2D) {
+    let base = merged.positions.len() as u32;
+    merged.positions.extend(mesh.positions.iter().map(|p| {
+        let [x, z] = offset.transform_point([p[0], p[2]]);
+        [x, p[1], z]
+    }));
+    merged.indices.extend(mesh.indices.iter().map(|i| i + base));
+}
@@ -0,0 +1,91 @@
+use crate::layout::placement::Placement;
+use crate::models::placement_region::PlacementSemantics;
+use std::collections::HashMap;
+use types::RegionsType;
+
+/// 一类家具的需求数量：至少 `min` 件、至多 `max` 件
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Demand {
+    pub min: u32,
+    pub max: u32,
+    /// 超出 `min` 后每多放一件的奖励
+    pub reward: f32,
+}
+
+impl Demand {
+    pub fn exact(count: u32) -> Self {
+        Self::range(count, count)
+    }
+
+    /// 调用方需保证 `min <= max`（配置解析时已拒绝不合法的区间）
+    pub fn range(min: u32, max: u32) -> Self {
+        debug_assert!(min <= max, "demand min {min} exceeds max {max}");
+        Self {
+            min,
+            max,
+            reward: 0.0,
+        }
+    }
+
+    /// 可选家具：不放也算满足，每放一件得到 `reward`
+    pub fn optional(max: u32, reward: f32) -> Self {
+        Self::range(0, max).with_reward(reward)
+    }
+
+    pub fn with_reward(mut self, reward: f32) -> Self {
+        self.reward = reward.max(0.0);
+        self
+    }
+
+    /// `min_count` 为 None 时按 `count` 精确放置，负数视为 0；
+    /// 资产里的 `countMin` 未经校验，大于 `count` 时截到 `count`
+    pub fn from_semantics(semantics: &PlacementSemantics) -> Self {
+        let max = semantics.count.max(0) as u32;
+        let min = semantics.min_count.map_or(max, |m| (m.max(0) as u32).min(max));
+        Self::range(min, max).with_reward(semantics.reward)
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.min == 0 && self.max > 0
+    }
+
+    pub fn is_met(&self, placed: u32) -> bool {
+        (self.min..=self.max).contains(&placed)
+    }
+}
+
+/// 单类家具的数量结果
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CountReport {
+    pub item_id: u32,
+    pub regions_type: RegionsType,
+    pub demand: Demand,
+    pub placed: u32,
+}
+
+impl CountReport {
+    pub fn is_met(&self) -> bool {
+        self.demand.is_met(self.placed)
+    }
+}
+
+/// 初始待放清单：item_id → 上限数量（为 0 的不进入清单）
+pub fn initial_inventory(demands: &[Demand]) -> HashMap<u32, u32> {
+    demands
+        .iter()
+        .enumerate()
+        .filter(|(_, d)| d.max > 0)
+        .map(|(id, d)| (id as u32, d.max))
+        .collect()
+}
+
+/// 按 item_id 统计已放数量
+pub fn placed_counts(placed: &[Placement], len: usize) -> Vec<u32> {
+    let mut counts = vec![0u32; len];
+    for p in placed {
+        if let Some(n) = counts.get_mut(p.item_id as usize) {
+            *n += 1;
+        }
+    }
+    counts
+}
@@ -0,0 +1,173 @@
+use crate::layout::geometry::PosedShape;
+use crate::layout::surface_pose::{SurfaceFrame, SurfaceKind};
+use crate::models::space::Space;
+use geo_types::{LineString, Polygon};
+use nalgebra::Vector3;
+use types::RegionsTypeMask;
+
+/// 门窗留空区的参数
+#[derive(Clone, Debug)]
+pub struct KeepOutConfig {
+    /// 视为门的表面类型
+    pub doors: RegionsTypeMask,
+    /// 视为窗的表面类型
+    pub windows: RegionsTypeMask,
+    /// 门前通行区的进深（mm）
+    pub door_approach_mm: f32,
+    /// 窗前留空带的进深（mm）
+    pub window_depth_mm: f32,
+    /// 低于窗台的家具可以放在窗前
+    pub allow_low_under_windows: bool,
+    /// 门扇开启弧线的分段数
+    pub arc_segments: usize,
+}
+
+impl Default for KeepOutConfig {
+    fn default() -> Self {
+        Self {
+            doors: RegionsTypeMask::NONE,
+            windows: RegionsTypeMask::NONE,
+            door_approach_mm: 600.0,
+            window_depth_mm: 400.0,
+            allow_low_under_windows: true,
+            arc_segments: 8,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeepOutKind {
+    /// 门扇开启扫过的四分之一圆（铰链在门洞 u 较小的一侧）
+    DoorSwing,
+    /// 门前的通行区
+    DoorApproach,
+    /// 窗前从窗台到窗顶的留空带
+    WindowBand,
+}
+
+/// 固定障碍：任何家具的禁止区都不得与之相交
+#[derive(Clone, Debug)]
+pub struct KeepOutZone {
+    pub kind: KeepOutKind,
+    /// 生成该区域的 `Space::meshes` 下标
+    pub mesh_index: usize,
+    /// 高度相对 `floor_y`，与家具轮廓的高度区间一致
+    pub shape: PosedShape,
+}
+
+/// 由门窗网格生成留空区；只处理拟合为墙面的网格，`frames` 与 `space.meshes` 一一对应
+pub fn derive_keep_outs(
+    space: &Space,
+    frames: &[Option<SurfaceFrame>],
+    floor_y: f32,
+    config: &KeepOutConfig,
+) -> Vec<KeepOutZone> {
+    let mut zones = Vec::new();
+    let metas = space.meshes.iter().zip(space.surface_metas.iter()).zip(frames);
+    for (mesh_index, ((mesh, meta), frame)) in metas.enumerate() {
+        let Some(frame) = frame.as_ref().filter(|f| f.kind == SurfaceKind::Wall) else {
+            continue;
+        };
+        let is_door = meta.regions_type_mask.intersects(config.doors);
+        let is_window = meta.regions_type_mask.intersects(config.windows);
+        if !is_door && !is_window {
+            continue;
+        }
+        let Some(opening) = Opening::of(&mesh.positions, frame) else {
+            continue;
+        };
+        let bottom = opening.bottom - floor_y;
+        let top = opening.top - floor_y;
+
+        if is_door {
+            let zone = |kind, polygon| KeepOutZone {
+                kind,
+                mesh_index,
+                shape: PosedShape::new(polygon, bottom, top),
+            };
+            zones.push(zone(KeepOutKind::DoorSwing, opening.swing(config.arc_segments)));
+            zones.push(zone(KeepOutKind::DoorApproach, opening.band(config.door_approach_mm)));
+        }
+        if is_window {
+            let low = if config.allow_low_under_windows { bottom } else { 0.0 };
+            zones.push(KeepOutZone {
+                kind: KeepOutKind::WindowBand,
+                mesh_index,
+                shape: PosedShape::new(opening.band(config.window_depth_mm), low, top),
+            });
+        }
+    }
+    zones
+}
+
+/// 墙上的门窗洞口：两侧边框在 XZ 上的位置、朝向房间内部的水平法向与高度范围
+struct Opening {
+    start: [f32; 2],
+    end: [f32; 2],
+    inward: [f32; 2],
+    bottom: f32,
+    top: f32,
+}
+
+impl Opening {
+    fn of(positions: &[[f32; 3]], frame: &SurfaceFrame) -> Option<Self> {
+        let mut u_range = (f32::INFINITY, f32::NEG_INFINITY);
+        let mut y_range = (f32::INFINITY, f32::NEG_INFINITY);
+        for p in positions {
+            let u = (Vector3::from(*p) - frame.origin).dot(&frame.u_axis);
+            u_range = (u_range.0.min(u), u_range.1.max(u));
+            y_range = (y_range.0.min(p[1]), y_range.1.max(p[1]));
+        }
+        let inward = Vector3::new(frame.normal.x, 0.0, frame.normal.z);
+        if u_range.1 - u_range.0 <= f32::EPSILON || inward.norm() <= f32::EPSILON {
+            return None;
+        }
+        let inward = inward.normalize();
+        let at = |u: f32| {
+            let p = frame.origin + frame.u_axis * u;
+            [p.x, p.z]
+        };
+        Some(Self {
+            start: at(u_range.0),
+            end: at(u_range.1),
+            inward: [inward.x, inward.z],
+            bottom: y_range.0,
+            top: y_range.1,
+        })
+    }
+
+    fn width(&self) -> f32 {
+        (self.end[0] - self.start[0]).hypot(self.end[1] - self.start[1])
+    }
+
+    /// 洞口前方 `depth` 进深的矩形
+    fn band(&self, depth: f32) -> Polygon<f64> {
+        let [nx, nz] = self.inward;
+        let shift = |p: [f32; 2]| [p[0] + nx * depth, p[1] + nz * depth];
+        polygon(&[self.start, self.end, shift(self.end), shift(self.start)])
+    }
+
+    /// 以 `start` 为铰链、门宽为半径，从关闭（沿墙）转到全开（垂直于墙）扫过的区域
+    fn swing(&self, segments: usize) -> Polygon<f64> {
+        let radius = self.width();
+        let along = [
+            (self.end[0] - self.start[0]) / radius,
+            (self.end[1] - self.start[1]) / radius,
+        ];
+        let segments = segments.max(1);
+        let mut points = vec![self.start];
+        for i in 0..=segments {
+            let (s, c) = (std::f32::consts::FRAC_PI_2 * i as f32 / segments as f32).sin_cos();
+            points.push([
+                self.start[0] + radius * (along[0] * c + self.inward[0] * s),
+                self.start[1] + radius * (along[1] * c + self.inward[1] * s),
+            ]);
+        }
+        polygon(&points)
+    }
+}
+
+fn polygon(points: &[[f32; 2]]) -> Polygon<f64> {
+    let ring: Vec<(f64, f64)> = points.iter().map(|p| (p[0] as f64, p[1] as f64)).collect();
+    Polygon::new(LineString::from(ring), Vec::new())
+}
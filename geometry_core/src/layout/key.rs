@@ -0,0 +1,103 @@
+use crate::layout::Placement;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// 去重 key 的量化参数
+#[derive(Clone, Debug)]
+pub struct KeyConfig {
+    /// 位置量化步长（mm）
+    pub grid_mm: f32,
+    /// 角度量化步长（弧度）
+    pub angle_step: f32,
+}
+
+impl Default for KeyConfig {
+    fn default() -> Self {
+        Self {
+            grid_mm: 10.0,
+            angle_step: 1.0_f32.to_radians(),
+        }
+    }
+}
+
+impl KeyConfig {
+    /// 量化后的 (item_id, x, y, theta, 表面上的 v)；墙面上俯视位姿相同的摆放靠 v 区分高度
+    pub fn quantize(&self, placement: &Placement) -> (u32, i64, i64, i64, i64) {
+        let grid = self.grid_mm.max(f32::EPSILON) as f64;
+        let step = self.angle_step.max(f32::EPSILON) as f64;
+        let turns = (std::f64::consts::TAU / step).round().max(1.0) as i64;
+        let theta = (placement.pose.theta as f64 / step).round() as i64;
+        (
+            placement.item_id,
+            (placement.pose.x as f64 / grid).round() as i64,
+            (placement.pose.y as f64 / grid).round() as i64,
+            theta.rem_euclid(turns),
+            placement.surface.as_ref().map_or(0, |s| (s.v as f64 / grid).round() as i64),
+        )
+    }
+}
+
+/// 与放置顺序无关、跨进程稳定的布局 key
+pub fn layout_key(placed: &[Placement], inventory: &HashMap<u32, u32>, config: &KeyConfig) -> u64 {
+    let mut quantized: Vec<_> = placed.iter().map(|p| config.quantize(p)).collect();
+    quantized.sort_unstable();
+    let mut remaining: Vec<_> = inventory.iter().map(|(id, n)| (*id, *n)).collect();
+    remaining.sort_unstable();
+
+    let mut hasher = StableHasher::new();
+    quantized.hash(&mut hasher);
+    remaining.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// FNV-1a 64 位哈希，不带随机种子，整数统一按小端序写入
+#[derive(Clone, Debug)]
+pub struct StableHasher {
+    state: u64,
+}
+
+impl StableHasher {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub fn new() -> Self {
+        Self {
+            state: Self::OFFSET,
+        }
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.state ^= *b as u64;
+            self.state = self.state.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write(&(i as u64).to_le_bytes());
+    }
+}
@@ -1,7 +1,35 @@
 pub mod state;
 pub mod placement;
 pub mod geometry;
+pub mod context;
+pub mod circulation;
+pub mod collision;
+pub mod constraints;
+pub mod group;
+pub mod inventory;
+pub mod objective;
+pub mod keep_out;
+pub mod key;
+pub mod moves;
+pub mod shell;
+pub mod surface_index;
+pub mod surface_pose;
 
-pub use state::LayoutState;
-pub use placement::Placement;
-pub use geometry::GeometryCache;
+pub use state::{LayoutSnapshot, LayoutState};
+pub use placement::{PinnedPlacement, Placement, Pose2D};
+pub use geometry::{Footprint, GeometryCache, OccupancyLayer, PosedShape};
+pub use circulation::{
+    analyze_circulation, AccessZone, CirculationConfig, CirculationReport, FreeSpaceGrid, ItemPath,
+};
+pub use collision::{check_pair, region_contact, Contact, PairCollision};
+pub use constraints::{Constraint, ConstraintSet, ConstraintStatus, Relation, Strength};
+pub use context::{CandidateConfig, LayoutContext, SupportSurface};
+pub use group::{compose, GroupMember, ItemGroup};
+pub use inventory::{CountReport, Demand};
+pub use objective::{Objective, ObjectiveWeights, ScoreBreakdown, ScoreTerm};
+pub use keep_out::{derive_keep_outs, KeepOutConfig, KeepOutKind, KeepOutZone};
+pub use key::{layout_key, KeyConfig, StableHasher};
+pub use moves::MoveKind;
+pub use shell::{Containment, RoomShell, WallDistance};
+pub use surface_index::{IndexedSurface, SurfaceIndex};
+pub use surface_pose::{SurfaceFrame, SurfaceKind, SurfacePose};
@@ -0,0 +1,130 @@
+use crate::layout::{LayoutState, Placement, SupportSurface, SurfaceKind, SurfacePose};
+use crate::rng::SeededRng;
+use crate::search::Neighborhood;
+
+/// 退火使用的局部调整操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveKind {
+    /// 在采样步长范围内平移一件家具
+    Move,
+    /// 换成另一个候选旋转角
+    Rotate,
+    /// 交换两件不同家具的位姿
+    Swap,
+    /// 重新放到兼容表面的任意采样点上
+    Reseat,
+}
+
+impl MoveKind {
+    pub const ALL: [MoveKind; 4] = [
+        MoveKind::Move,
+        MoveKind::Rotate,
+        MoveKind::Swap,
+        MoveKind::Reseat,
+    ];
+}
+
+impl LayoutState {
+    /// 对第 `index` 件家具施加一次调整，结果不合法或涉及固定的家具时返回 None
+    pub fn apply_move(&self, kind: MoveKind, index: usize, rng: &mut SeededRng) -> Option<Self> {
+        let context = self.context();
+        let movable = self.movable_from();
+        if index < movable {
+            return None;
+        }
+        let mut placed: Vec<Placement> = self.placed().to_vec();
+        let target = placed.get(index)?.clone();
+
+        // 有表面位姿的在表面 (u, v) 上调整，其余按俯视位姿调整
+        match kind {
+            MoveKind::Move => {
+                let step = context.candidates.sample_step_mm;
+                let (du, dv) = (rng.range_f32(-step, step), rng.range_f32(-step, step));
+                match &target.surface {
+                    Some(pose) => {
+                        let mut pose = pose.clone();
+                        pose.u += du;
+                        pose.v += dv;
+                        placed[index] = context.place_on(target.item_id, pose)?;
+                    }
+                    None => {
+                        let pose = &mut placed[index].pose;
+                        pose.x += du;
+                        pose.y += dv;
+                    }
+                }
+            }
+            MoveKind::Rotate => {
+                let thetas = &context.candidates.thetas;
+                if thetas.len() < 2 {
+                    return None;
+                }
+                let theta = thetas[rng.below(thetas.len())];
+                match &target.surface {
+                    Some(pose) => {
+                        let wall = context
+                            .surface_frame(pose.surface)
+                            .is_some_and(|frame| frame.kind == SurfaceKind::Wall);
+                        if wall || theta == pose.rotation {
+                            return None;
+                        }
+                        let mut pose = pose.clone();
+                        pose.rotation = theta;
+                        placed[index] = context.place_on(target.item_id, pose)?;
+                    }
+                    None => {
+                        if theta == target.pose.theta {
+                            return None;
+                        }
+                        placed[index].pose.theta = theta;
+                    }
+                }
+            }
+            MoveKind::Swap => {
+                let other = movable + rng.below(placed.len() - movable);
+                if placed[other].item_id == target.item_id {
+                    return None;
+                }
+                // 表面偏移与家具尺寸有关，交换后按各自的家具重新计算
+                let moved_to = |item_id: u32, to: &Placement| match &to.surface {
+                    Some(pose) => context.place_on(item_id, pose.clone()),
+                    None => Some(Placement::new(item_id, to.pose.clone())),
+                };
+                let swapped = moved_to(placed[other].item_id, &target)?;
+                placed[index] = moved_to(target.item_id, &placed[other])?;
+                placed[other] = swapped;
+            }
+            MoveKind::Reseat => {
+                let samples: Vec<(&SupportSurface, [f32; 2])> = context
+                    .surfaces_for(target.item_id)
+                    .flat_map(|s| s.samples.iter().map(move |sample| (s, *sample)))
+                    .collect();
+                let thetas = &context.candidates.thetas;
+                if samples.is_empty() || thetas.is_empty() {
+                    return None;
+                }
+                let (surface, sample) = samples[rng.below(samples.len())];
+                let theta = match surface.kind() {
+                    SurfaceKind::Wall => 0.0,
+                    _ => thetas[rng.below(thetas.len())],
+                };
+                let pose = SurfacePose::new(surface.mesh_index, sample[0], sample[1], theta);
+                placed[index] = context.place_on(target.item_id, pose)?;
+            }
+        }
+
+        self.with_placements(placed)
+    }
+}
+
+impl Neighborhood for LayoutState {
+    fn neighbor(&self, rng: &mut SeededRng) -> Option<Self> {
+        let movable = self.movable_from();
+        if self.placed().len() <= movable {
+            return None;
+        }
+        let kind = MoveKind::ALL[rng.below(MoveKind::ALL.len())];
+        let index = movable + rng.below(self.placed().len() - movable);
+        self.apply_move(kind, index, rng)
+    }
+}
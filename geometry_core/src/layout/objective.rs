@@ -0,0 +1,398 @@
+use crate::layout::geometry::polygon_inside;
+use crate::layout::inventory::placed_counts;
+use crate::layout::LayoutState;
+use geo::Contains;
+use geo_types::{Point, Polygon};
+use std::fmt::Debug;
+use types::RegionsTypeMask;
+
+/// 贴墙评分的衰减距离（mm）
+const WALL_FALLOFF_MM: f32 = 500.0;
+/// 距门窗超过该距离即视为满分（mm）
+const OPENING_KEEP_MM: f32 = 1000.0;
+
+/// 单个评分项，返回值约定在 [0, 1]，越大越好
+pub trait ScoreTerm: Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+    fn evaluate(&self, state: &LayoutState) -> f32;
+}
+
+/// 各评分项的权重（可由配置文件加载）
+#[derive(Clone, Debug)]
+pub struct ObjectiveWeights {
+    pub count: f32,
+    pub wall_proximity: f32,
+    pub clearance: f32,
+    pub alignment: f32,
+    pub openings: f32,
+    pub relations: f32,
+    pub circulation: f32,
+}
+
+impl Default for ObjectiveWeights {
+    fn default() -> Self {
+        Self {
+            count: 1.0,
+            wall_proximity: 0.2,
+            clearance: 0.3,
+            alignment: 0.1,
+            openings: 0.1,
+            relations: 0.5,
+            circulation: 0.0,
+        }
+    }
+}
+
+/// 加权评分项的组合
+#[derive(Debug, Default)]
+pub struct Objective {
+    terms: Vec<WeightedTerm>,
+}
+
+#[derive(Debug)]
+pub struct WeightedTerm {
+    pub weight: f32,
+    pub term: Box<dyn ScoreTerm>,
+}
+
+/// 单项得分明细
+#[derive(Clone, Debug)]
+pub struct TermScore {
+    pub name: &'static str,
+    pub weight: f32,
+    pub value: f32,
+}
+
+/// 总分与各项明细，用于报告
+#[derive(Clone, Debug, Default)]
+pub struct ScoreBreakdown {
+    pub total: f32,
+    pub terms: Vec<TermScore>,
+}
+
+impl Objective {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按权重构造内置评分项；`openings` 为门窗类型的掩码
+    pub fn from_weights(weights: &ObjectiveWeights, openings: RegionsTypeMask) -> Self {
+        Self::new()
+            .with_term(weights.count, CountFulfilment)
+            .with_term(weights.wall_proximity, WallProximity)
+            .with_term(weights.clearance, ClearanceSatisfaction)
+            .with_term(weights.alignment, AxisAlignment)
+            .with_term(weights.openings, OpeningDistance { openings })
+            .with_term(weights.relations, RelationSatisfaction)
+            .with_term(weights.circulation, Walkability)
+    }
+
+    /// 追加一个评分项，权重为 0 的项会被忽略
+    pub fn with_term(mut self, weight: f32, term: impl ScoreTerm + 'static) -> Self {
+        if weight != 0.0 {
+            self.terms.push(WeightedTerm {
+                weight,
+                term: Box::new(term),
+            });
+        }
+        self
+    }
+
+    pub fn terms(&self) -> &[WeightedTerm] {
+        &self.terms
+    }
+
+    pub fn evaluate(&self, state: &LayoutState) -> f32 {
+        self.terms
+            .iter()
+            .map(|t| t.weight * t.term.evaluate(state))
+            .sum()
+    }
+
+    pub fn breakdown(&self, state: &LayoutState) -> ScoreBreakdown {
+        let terms: Vec<TermScore> = self
+            .terms
+            .iter()
+            .map(|t| TermScore {
+                name: t.term.name(),
+                weight: t.weight,
+                value: t.term.evaluate(state),
+            })
+            .collect();
+        let total = terms.iter().map(|t| t.weight * t.value).sum();
+        ScoreBreakdown { total, terms }
+    }
+}
+
+/// 需求数量的满足程度：下限内按件计数，超出下限的按奖励加权
+///
+/// 精确数量时即已放数量 / 要求数量；没有任何需求时为 1。
+#[derive(Debug)]
+pub struct CountFulfilment;
+
+impl ScoreTerm for CountFulfilment {
+    fn name(&self) -> &'static str {
+        "count"
+    }
+
+    fn evaluate(&self, state: &LayoutState) -> f32 {
+        let demands = &state.context().demands;
+        let placed = placed_counts(state.placed(), demands.len());
+        let mut met = 0.0;
+        let mut requested = 0.0;
+        for (demand, n) in demands.iter().zip(placed) {
+            let extra = n.min(demand.max).saturating_sub(demand.min);
+            met += n.min(demand.min) as f32 + demand.reward * extra as f32;
+            requested += demand.min as f32 + demand.reward * (demand.max - demand.min) as f32;
+        }
+        if requested <= 0.0 {
+            return 1.0;
+        }
+        met / requested
+    }
+}
+
+/// 禁止区离房间边界越近越好
+#[derive(Debug)]
+pub struct WallProximity;
+
+impl ScoreTerm for WallProximity {
+    fn name(&self) -> &'static str {
+        "wall_proximity"
+    }
+
+    fn evaluate(&self, state: &LayoutState) -> f32 {
+        let outlines: Vec<&Polygon<f64>> =
+            state.context().floor_surfaces().map(|s| &s.outline).collect();
+        mean_over_placed(state, |entry| {
+            let d = outlines
+                .iter()
+                .map(|outline| ring_distance(&entry.shape.polygon, outline))
+                .fold(f32::INFINITY, f32::min);
+            if d.is_finite() {
+                1.0 / (1.0 + d / WALL_FALLOFF_MM)
+            } else {
+                0.0
+            }
+        })
+    }
+}
+
+/// 限制区（留空）不压到其他家具、且不超出房间
+#[derive(Debug)]
+pub struct ClearanceSatisfaction;
+
+impl ScoreTerm for ClearanceSatisfaction {
+    fn name(&self) -> &'static str {
+        "clearance"
+    }
+
+    fn evaluate(&self, state: &LayoutState) -> f32 {
+        let occupancy = state.occupancy();
+        let context = state.context();
+        let clearances = occupancy.restricted();
+        if clearances.is_empty() {
+            return 0.0;
+        }
+        // 两层按相同顺序插入，`index` 相同即同一件家具
+        let satisfied = clearances
+            .iter()
+            .filter(|clearance| {
+                let blocked = occupancy
+                    .forbidden()
+                    .query(&clearance.shape.bounds)
+                    .any(|other| other.index != clearance.index && other.shape.overlaps(&clearance.shape));
+                let inside = context
+                    .floor_surfaces()
+                    .any(|s| polygon_inside(&s.outline, &clearance.shape.polygon));
+                !blocked && inside
+            })
+            .count();
+        satisfied as f32 / clearances.len() as f32
+    }
+}
+
+/// 旋转角与房间主轴对齐（以 90° 为周期）
+#[derive(Debug)]
+pub struct AxisAlignment;
+
+impl ScoreTerm for AxisAlignment {
+    fn name(&self) -> &'static str {
+        "alignment"
+    }
+
+    fn evaluate(&self, state: &LayoutState) -> f32 {
+        let Some(axis) = dominant_axis(state) else {
+            return 0.0;
+        };
+        let placed = state.placed();
+        if placed.is_empty() {
+            return 0.0;
+        }
+        let quarter = std::f32::consts::FRAC_PI_2;
+        let sum: f32 = placed
+            .iter()
+            .map(|p| {
+                let delta = (p.pose.theta - axis).rem_euclid(quarter);
+                let delta = delta.min(quarter - delta);
+                1.0 - delta / (quarter * 0.5)
+            })
+            .sum();
+        sum / placed.len() as f32
+    }
+}
+
+/// 离门窗越远越好，超过 `OPENING_KEEP_MM` 即满分
+#[derive(Debug)]
+pub struct OpeningDistance {
+    pub openings: RegionsTypeMask,
+}
+
+impl ScoreTerm for OpeningDistance {
+    fn name(&self) -> &'static str {
+        "openings"
+    }
+
+    fn evaluate(&self, state: &LayoutState) -> f32 {
+        let space = &state.context().space;
+        let centers: Vec<[f32; 2]> = space
+            .meshes
+            .iter()
+            .zip(space.surface_metas.iter())
+            .filter(|(_, meta)| meta.regions_type_mask.intersects(self.openings))
+            .filter_map(|(mesh, _)| centroid_xz(&mesh.positions))
+            .collect();
+        if centers.is_empty() {
+            return 1.0;
+        }
+        mean_over_placed(state, |entry| {
+            let d = centers
+                .iter()
+                .map(|c| point_polygon_distance(*c, &entry.shape.polygon))
+                .fold(f32::INFINITY, f32::min);
+            (d / OPENING_KEEP_MM).min(1.0)
+        })
+    }
+}
+
+/// 软约束的加权满足比例（见 `ConstraintSet::soft_score`）
+#[derive(Debug)]
+pub struct RelationSatisfaction;
+
+impl ScoreTerm for RelationSatisfaction {
+    fn name(&self) -> &'static str {
+        "relations"
+    }
+
+    fn evaluate(&self, state: &LayoutState) -> f32 {
+        let context = state.context();
+        if !context.constraints.has_soft() {
+            return 1.0;
+        }
+        context
+            .constraints
+            .soft_score(context, state.placed(), state.inventory())
+    }
+}
+
+/// 可从门口沿足够宽的通道到达的家具比例（见 `LayoutState::circulation_report`）
+///
+/// 门之间不互通时为 0；没有门时无从判断，为 1。
+#[derive(Debug)]
+pub struct Walkability;
+
+impl ScoreTerm for Walkability {
+    fn name(&self) -> &'static str {
+        "circulation"
+    }
+
+    fn evaluate(&self, state: &LayoutState) -> f32 {
+        let report = state.circulation_report();
+        if !report.doors_connected {
+            return 0.0;
+        }
+        report.reachable_fraction()
+    }
+}
+
+fn mean_over_placed(
+    state: &LayoutState,
+    f: impl Fn(&crate::layout::geometry::OccupiedEntry) -> f32,
+) -> f32 {
+    let layer = state.occupancy().forbidden();
+    if layer.is_empty() {
+        return 0.0;
+    }
+    layer.iter().map(f).sum::<f32>() / layer.len() as f32
+}
+
+/// 房间主轴：地面轮廓最长边的方向
+fn dominant_axis(state: &LayoutState) -> Option<f32> {
+    let mut best: Option<(f64, f64)> = None;
+    for surface in state.context().floor_surfaces() {
+        for line in surface.outline.exterior().lines() {
+            let dx = line.end.x - line.start.x;
+            let dy = line.end.y - line.start.y;
+            let len = (dx * dx + dy * dy).sqrt();
+            if best.is_none_or(|(l, _)| len > l) {
+                best = Some((len, dy.atan2(dx)));
+            }
+        }
+    }
+    best.map(|(_, angle)| angle as f32)
+}
+
+fn centroid_xz(points: &[[f32; 3]]) -> Option<[f32; 2]> {
+    if points.is_empty() {
+        return None;
+    }
+    let n = points.len() as f32;
+    let (sx, sz) = points.iter().fold((0.0, 0.0), |(sx, sz), p| (sx + p[0], sz + p[2]));
+    Some([sx / n, sz / n])
+}
+
+/// 两个多边形外环之间的最小距离（顶点到边，双向）
+fn ring_distance(a: &Polygon<f64>, b: &Polygon<f64>) -> f32 {
+    let one_way = |from: &Polygon<f64>, to: &Polygon<f64>| {
+        from.exterior()
+            .coords()
+            .map(|c| point_ring_distance([c.x as f32, c.y as f32], to))
+            .fold(f32::INFINITY, f32::min)
+    };
+    one_way(a, b).min(one_way(b, a))
+}
+
+fn point_polygon_distance(p: [f32; 2], polygon: &Polygon<f64>) -> f32 {
+    if polygon.contains(&Point::new(p[0] as f64, p[1] as f64)) {
+        return 0.0;
+    }
+    point_ring_distance(p, polygon)
+}
+
+fn point_ring_distance(p: [f32; 2], polygon: &Polygon<f64>) -> f32 {
+    polygon
+        .exterior()
+        .lines()
+        .map(|line| {
+            point_segment_distance(
+                p,
+                [line.start.x as f32, line.start.y as f32],
+                [line.end.x as f32, line.end.y as f32],
+            )
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+fn point_segment_distance(p: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    let ab = [b[0] - a[0], b[1] - a[1]];
+    let ap = [p[0] - a[0], p[1] - a[1]];
+    let len2 = ab[0] * ab[0] + ab[1] * ab[1];
+    let t = if len2 > 0.0 {
+        ((ap[0] * ab[0] + ap[1] * ab[1]) / len2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let dx = ap[0] - ab[0] * t;
+    let dy = ap[1] - ab[1] * t;
+    (dx * dx + dy * dy).sqrt()
+}
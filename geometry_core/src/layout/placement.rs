@@ -1,18 +1,102 @@
+use crate::layout::surface_pose::{SurfaceFrame, SurfacePose};
+use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Placement {
     pub item_id: u32,
+    /// 俯视位姿（XZ 平移与绕 Y 旋转），碰撞、约束与评分都用它
     pub pose: Pose2D,
+    /// 所在表面上的位姿，导出时按表面坐标系生成世界矩阵；
+    /// None 表示放在 `pose` 下方的地面上（组合成员、固定摆放）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub surface: Option<SurfacePose>,
 }
 
-#[derive(Clone, Debug)]
+impl Placement {
+    /// 地面摆放：所在表面按 XZ 位置查找
+    pub fn new(item_id: u32, pose: Pose2D) -> Self {
+        Self {
+            item_id,
+            pose,
+            surface: None,
+        }
+    }
+
+    /// 表面摆放：俯视位姿由 `frame` 下的世界矩阵推出
+    pub fn on_surface(item_id: u32, surface: SurfacePose, frame: &SurfaceFrame) -> Self {
+        Self {
+            item_id,
+            pose: Pose2D::from_matrix(&surface.to_matrix(frame)),
+            surface: Some(surface),
+        }
+    }
+}
+
+/// 地面位姿：`x`/`y` 对应世界坐标的 X/Z，`theta` 为绕 Y 轴的旋转（弧度，从 +X 转向 +Z）
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Pose2D {
     pub x: f32,
     pub y: f32,
     pub theta: f32,
 }
 
+impl Pose2D {
+    pub fn new(x: f32, y: f32, theta: f32) -> Self {
+        Self { x, y, theta }
+    }
+
+    /// 把局部 XZ 坐标变换到世界 XZ 坐标
+    pub fn transform_point(&self, p: [f32; 2]) -> [f32; 2] {
+        let (s, c) = self.theta.sin_cos();
+        [p[0] * c - p[1] * s + self.x, p[0] * s + p[1] * c + self.y]
+    }
+
+    /// 世界变换矩阵（列主序，`m[3]` 为平移），`elevation` 为所在表面的 Y 高度
+    pub fn to_matrix(&self, elevation: f32) -> [[f32; 4]; 4] {
+        let (s, c) = self.theta.sin_cos();
+        [
+            [c, 0.0, s, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [-s, 0.0, c, 0.0],
+            [self.x, elevation, self.y, 1.0],
+        ]
+    }
+}
+
+/// 固定的摆放：搜索与退火都不会移动它，导出时原样使用 `matrix`
+#[derive(Clone, Debug)]
+pub struct PinnedPlacement {
+    pub placement: Placement,
+    /// 原始世界矩阵（来自已有的 transforms.json）；None 时按位姿计算
+    pub matrix: Option<[[f32; 4]; 4]>,
+}
+
+impl PinnedPlacement {
+    pub fn new(item_id: u32, pose: Pose2D) -> Self {
+        Self {
+            placement: Placement::new(item_id, pose),
+            matrix: None,
+        }
+    }
+
+    /// 从世界矩阵恢复地面位姿，并保留矩阵用于导出；
+    /// 墙面/顶面上的表面位姿在 `LayoutContext::with_pinned` 中按矩阵恢复
+    pub fn from_matrix(item_id: u32, matrix: [[f32; 4]; 4]) -> Self {
+        Self {
+            placement: Placement::new(item_id, Pose2D::from_matrix(&matrix)),
+            matrix: Some(matrix),
+        }
+    }
+}
+
+impl Pose2D {
+    /// `to_matrix` 的逆：取 XZ 平移与绕 Y 轴的旋转，忽略高度
+    pub fn from_matrix(m: &[[f32; 4]; 4]) -> Self {
+        Self::new(m[3][0], m[3][2], m[0][2].atan2(m[0][0]))
+    }
+}
+
 impl Hash for Pose2D {
     fn hash<H: Hasher>(&self, state: &mut H) {
         // 对 Pose2D 中的字段进行哈希
@@ -27,5 +111,10 @@ impl Hash for Placement {
         // 对 item_id 和 pose 进行哈希
         self.item_id.hash(state);
         self.pose.hash(state); // Pose2D 会通过它自己的 `hash` 方法来处理
+        if let Some(surface) = &self.surface {
+            // 俯视位姿相同的墙面摆放只差在高度上
+            surface.surface.hash(state);
+            surface.v.to_bits().hash(state);
+        }
     }
 }
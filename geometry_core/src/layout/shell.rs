@@ -0,0 +1,327 @@
+use crate::geometry_ops::boundary::flatten_boundary_polygon;
+use crate::layout::collision::{surface_samples, to_matrix, CONTACT_TOLERANCE_MM};
+use crate::layout::surface_pose::{SurfaceFrame, SurfaceKind};
+use crate::models::mesh::Mesh;
+use crate::models::placement_region::{PlacementRegion, SdfGrid};
+use crate::models::space::Space;
+use geo::{EuclideanDistance, TriangulateEarcut};
+use geo_types::{Coord, LineString, Point, Polygon};
+use nalgebra::{Point3, Vector3};
+
+/// 家具到一面墙的最近距离
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WallDistance {
+    /// `Space::meshes` 下标
+    pub mesh_index: usize,
+    /// 最近距离（mm）；负值表示有点落到墙面外侧
+    pub distance: f32,
+}
+
+/// 摆放后的家具与房间壳体的关系
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Containment {
+    /// 禁止区全部落在地面轮廓上方、顶面以下
+    pub inside: bool,
+    /// 禁止区穿过地面、墙面或顶面
+    pub intersects: bool,
+    /// 最深的穿透（mm），不相交时为 0
+    pub penetration: f32,
+    /// 按 mesh 顺序排列的每面墙的距离
+    pub walls: Vec<WallDistance>,
+}
+
+impl Containment {
+    pub fn is_contained(&self) -> bool {
+        self.inside && !self.intersects
+    }
+
+    /// 离最近一面墙的距离，房间没有墙时为 None
+    pub fn min_wall_distance(&self) -> Option<f32> {
+        self.walls.iter().map(|w| w.distance).min_by(f32::total_cmp)
+    }
+}
+
+/// 房间壳体的一个平面片
+#[derive(Clone, Debug)]
+struct ShellPatch {
+    mesh_index: usize,
+    frame: SurfaceFrame,
+    /// 轮廓（含洞），坐标为 `frame` 的 (u, v)
+    outline: Polygon<f64>,
+}
+
+/// 单个平面片上的采样统计
+struct PatchDistance {
+    /// 最近距离：投影落在轮廓内时取到平面的有向距离（房间内侧为正），否则取到轮廓的欧氏距离
+    nearest: f32,
+    /// 投影落在轮廓内的点是否分布在平面两侧
+    crosses: bool,
+    /// 平面外侧最深的点（mm）
+    depth: f32,
+}
+
+impl ShellPatch {
+    fn distance(&self, points: &[Vector3<f32>]) -> PatchDistance {
+        let mut nearest = f32::INFINITY;
+        let (mut below, mut above) = (f32::INFINITY, f32::NEG_INFINITY);
+        for p in points {
+            let d = p - self.frame.origin;
+            let height = d.dot(&self.frame.normal);
+            let uv = Point::new(d.dot(&self.frame.u_axis) as f64, d.dot(&self.frame.v_axis) as f64);
+            let offset = uv.euclidean_distance(&self.outline) as f32;
+            if offset > 0.0 {
+                nearest = nearest.min(offset.hypot(height));
+            } else {
+                nearest = nearest.min(height);
+                below = below.min(height);
+                above = above.max(height);
+            }
+        }
+        PatchDistance {
+            nearest,
+            crosses: below < -CONTACT_TOLERANCE_MM && above > CONTACT_TOLERANCE_MM,
+            depth: (-below).max(0.0),
+        }
+    }
+}
+
+/// 房间壳体：地面轮廓 + 地面、墙面、顶面的平面片，可选整体 SDF
+///
+/// 平面片的法向由 `SurfaceFrame::fit` 指向房间内部。SDF（内部为负）由 `closed_mesh`
+/// 的拉伸体构建，用于补充平面片漏掉的穿插。
+#[derive(Clone, Debug, Default)]
+pub struct RoomShell {
+    /// 地面轮廓（XZ，含柱子等洞）
+    floors: Vec<Polygon<f64>>,
+    /// 最低地面的 Y 高度
+    floor_y: Option<f32>,
+    /// 最高顶面的 Y 高度，没有顶面时为 None
+    ceiling_y: Option<f32>,
+    /// 所有壳体网格的最高点
+    top_y: Option<f32>,
+    patches: Vec<ShellPatch>,
+    sdf: Option<SdfGrid>,
+}
+
+impl RoomShell {
+    /// `frames` 与 `space.meshes` 一一对应，非平面网格（None）不参与
+    pub fn build(space: &Space, frames: &[Option<SurfaceFrame>]) -> Self {
+        let mut shell = Self::default();
+        for (mesh_index, (mesh, frame)) in space.meshes.iter().zip(frames).enumerate() {
+            for p in &mesh.positions {
+                shell.top_y = Some(shell.top_y.map_or(p[1], |y| y.max(p[1])));
+            }
+            let Some(frame) = frame else {
+                continue;
+            };
+            let Some(boundary) = flatten_boundary_polygon(mesh) else {
+                continue;
+            };
+
+            let origin_y = frame.origin.y;
+            match frame.kind {
+                SurfaceKind::Floor => {
+                    shell.floors.push(boundary.reproject(|p| [p[0] as f64, p[2] as f64]));
+                    shell.floor_y = Some(shell.floor_y.map_or(origin_y, |y| y.min(origin_y)));
+                }
+                SurfaceKind::Ceiling => {
+                    shell.ceiling_y = Some(shell.ceiling_y.map_or(origin_y, |y| y.max(origin_y)));
+                }
+                SurfaceKind::Wall => {}
+            }
+
+            let outline = boundary.reproject(|p| {
+                let d = Vector3::from(p) - frame.origin;
+                [d.dot(&frame.u_axis) as f64, d.dot(&frame.v_axis) as f64]
+            });
+            shell.patches.push(ShellPatch {
+                mesh_index,
+                frame: frame.clone(),
+                outline,
+            });
+        }
+        shell
+    }
+
+    /// 自行拟合每个网格的平面后构建
+    pub fn from_space(space: &Space) -> Self {
+        let interior = space.interior_point().map(Vector3::from).unwrap_or_default();
+        let frames: Vec<_> = space
+            .meshes
+            .iter()
+            .map(|mesh| SurfaceFrame::fit(mesh, &interior))
+            .collect();
+        Self::build(space, &frames)
+    }
+
+    /// 地面轮廓（含洞）从最低地面竖直拉伸到顶面的闭合网格，用来构建壳体 SDF
+    ///
+    /// 原始壳体网格在平面片相接处有 T 形接缝、墙上有门窗开口，直接构建的 SDF 内外符号
+    /// 不可靠；拉伸体总是闭合的。斜墙、斜顶与拉伸体的差别由平面片检查补上。
+    /// 没有地面或没有高度时为 None。
+    pub fn closed_mesh(&self) -> Option<Mesh> {
+        let bottom = self.floor_y?;
+        let top = self.ceiling_y.or(self.top_y)?;
+        if top <= bottom + CONTACT_TOLERANCE_MM || self.floors.is_empty() {
+            return None;
+        }
+        let mut mesh = Mesh::default();
+        for floor in &self.floors {
+            extrude(floor, bottom, top, &mut mesh);
+        }
+        Some(mesh)
+    }
+
+    /// 附加整个房间壳体的 SDF（内部为负）
+    pub fn with_sdf(mut self, sdf: SdfGrid) -> Self {
+        self.sdf = Some(sdf);
+        self
+    }
+
+    pub fn floors(&self) -> &[Polygon<f64>] {
+        &self.floors
+    }
+
+    pub fn has_sdf(&self) -> bool {
+        self.sdf.is_some()
+    }
+
+    /// 按世界矩阵（列主序，`m[3]` 为平移）摆放 `item`，检查其禁止区与房间壳体的关系
+    pub fn contain(&self, item: &PlacementRegion, pose: &[[f32; 4]; 4]) -> Containment {
+        let mesh = &item.regions.forbidden_region.mesh;
+        let matrix = to_matrix(pose);
+        let points: Vec<Vector3<f32>> = surface_samples(mesh)
+            .map(|p| matrix.transform_point(&Point3::from(p)).coords)
+            .collect();
+        self.contain_points(&points)
+    }
+
+    fn contain_points(&self, points: &[Vector3<f32>]) -> Containment {
+        if points.is_empty() {
+            return Containment {
+                inside: true,
+                ..Containment::default()
+            };
+        }
+
+        let mut containment = Containment {
+            inside: !self.floors.is_empty() && points.iter().all(|p| self.above_floor(p)),
+            ..Containment::default()
+        };
+        for patch in &self.patches {
+            let distance = patch.distance(points);
+            if distance.crosses {
+                containment.intersects = true;
+                containment.penetration = containment.penetration.max(distance.depth);
+            }
+            if patch.frame.kind == SurfaceKind::Wall {
+                containment.walls.push(WallDistance {
+                    mesh_index: patch.mesh_index,
+                    distance: distance.nearest,
+                });
+            }
+        }
+
+        // SDF 内部为负：任何采样点为正说明穿出了壳体
+        if let Some(sdf) = &self.sdf {
+            let query: Vec<[f32; 3]> = points.iter().map(|p| [p.x, p.y, p.z]).collect();
+            if let Ok(values) = sdf.grid.sample_world(&query) {
+                let outside = values.into_iter().fold(0.0, f32::max);
+                if outside > CONTACT_TOLERANCE_MM {
+                    containment.intersects = true;
+                    containment.penetration = containment.penetration.max(outside);
+                }
+            }
+        }
+        containment
+    }
+
+    /// 点的 XZ 落在某个地面轮廓内（含边界容差），且高度在地面与顶面之间
+    fn above_floor(&self, p: &Vector3<f32>) -> bool {
+        let tolerance = CONTACT_TOLERANCE_MM;
+        if self.floor_y.is_some_and(|y| p.y < y - tolerance) {
+            return false;
+        }
+        if self.ceiling_y.is_some_and(|y| p.y > y + tolerance) {
+            return false;
+        }
+        let point = Point::new(p.x as f64, p.z as f64);
+        self.floors
+            .iter()
+            .any(|floor| point.euclidean_distance(floor) <= tolerance as f64)
+    }
+}
+
+/// 把 XZ 多边形从 `bottom` 拉伸到 `top`，追加到 `mesh`：上下两个盖面加每个环的侧面
+fn extrude(polygon: &Polygon<f64>, bottom: f32, top: f32, mesh: &mut Mesh) {
+    // earcut 会跳过共线顶点，侧面也得用同样的顶点，否则盖面与侧面之间出现 T 形接缝
+    let rings: Vec<Vec<Coord<f64>>> = std::iter::once(polygon.exterior())
+        .chain(polygon.interiors())
+        .map(|ring| drop_collinear(open_ring(ring)))
+        .collect();
+    if rings.iter().any(|ring| ring.len() < 3) {
+        return;
+    }
+    let closed = |ring: &Vec<Coord<f64>>| LineString::from(ring.clone());
+    let polygon = Polygon::new(closed(&rings[0]), rings[1..].iter().map(closed).collect());
+    let count: usize = rings.iter().map(|ring| ring.len()).sum();
+    let base = mesh.positions.len() as u32;
+    for y in [bottom, top] {
+        for ring in &rings {
+            mesh.positions.extend(ring.iter().map(|c| [c.x as f32, y, c.y as f32]));
+        }
+    }
+    let bottom_of = |i: usize| base + i as u32;
+    let top_of = |i: usize| base + (count + i) as u32;
+
+    // earcut 的顶点按环依次排列且每个环带闭合点，映射回各环的开放顶点
+    let mut ring_of_raw = Vec::new();
+    let mut start = 0;
+    for ring in &rings {
+        ring_of_raw.extend((0..ring.len()).map(|i| start + i));
+        ring_of_raw.push(start);
+        start += ring.len();
+    }
+    let caps = polygon.earcut_triangles_raw().triangle_indices;
+    for tri in caps.chunks_exact(3) {
+        let (Some(&a), Some(&b), Some(&c)) = (ring_of_raw.get(tri[0]), ring_of_raw.get(tri[1]), ring_of_raw.get(tri[2]))
+        else {
+            continue;
+        };
+        mesh.indices.extend([bottom_of(a), bottom_of(c), bottom_of(b)]);
+        mesh.indices.extend([top_of(a), top_of(b), top_of(c)]);
+    }
+
+    let mut start = 0;
+    for ring in &rings {
+        let n = ring.len();
+        for i in 0..n {
+            let (p, q) = (start + i, start + (i + 1) % n);
+            mesh.indices.extend([bottom_of(p), bottom_of(q), top_of(q)]);
+            mesh.indices.extend([bottom_of(p), top_of(q), top_of(p)]);
+        }
+        start += n;
+    }
+}
+
+/// 去掉与前后顶点共线（含重合）的顶点
+fn drop_collinear(ring: &[Coord<f64>]) -> Vec<Coord<f64>> {
+    let n = ring.len();
+    (0..n)
+        .filter(|&i| {
+            let (prev, p, next) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+            let (a, b) = (p - prev, next - p);
+            let cross = a.x * b.y - a.y * b.x;
+            cross.abs() > 1e-9 * (a.x.hypot(a.y) * b.x.hypot(b.y)).max(1.0)
+        })
+        .map(|i| ring[i])
+        .collect()
+}
+
+/// 去掉闭合点的环顶点
+fn open_ring(ring: &LineString<f64>) -> &[Coord<f64>] {
+    match ring.0.as_slice() {
+        [first, .., last] if first == last => &ring.0[..ring.0.len() - 1],
+        coords => coords,
+    }
+}
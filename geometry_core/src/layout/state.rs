@@ -1,29 +1,535 @@
-use crate::layout::{GeometryCache, Placement};
-use crate::search::SearchNode;
-use std::collections::{BinaryHeap, HashMap};
-use std::hash::{Hash, Hasher};
+use crate::layout::circulation::{analyze_circulation, AccessZone, CirculationReport, FreeSpaceGrid};
+use crate::layout::collision::check_pair;
+use crate::layout::constraints::{contains_centroid, ConstraintStatus};
+use crate::layout::geometry::{polygon_inside, OccupiedEntry, PosedShape};
+use crate::layout::inventory::{placed_counts, CountReport};
+use crate::layout::keep_out::KeepOutKind;
+use crate::layout::key::layout_key;
+use crate::layout::objective::ScoreBreakdown;
+use crate::layout::{
+    Footprint, GeometryCache, LayoutContext, Placement, SupportSurface, SurfaceKind, SurfacePose,
+};
+use crate::models::placement_region::PlacementRegion;
+use crate::rng::SeededRng;
+use crate::search::{Diverse, SearchNode, Snapshot, Traceable};
+use geo::Intersects;
+use geo_types::{LineString, Polygon};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
 use std::cmp::Ordering;
+use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct LayoutState {
+    context: Arc<LayoutContext>, // 共享的只读上下文
     placed: Vec<Placement>, // 已放家具
-    occupancy: GeometryCache,
+    occupancy: GeometryCache, // 已放家具的占用（与父节点共享）
     inventory: HashMap<u32, u32>, // 用 HashMap 记录家具类型和数量
     score: f32,
 }
 
+impl LayoutState {
+    /// 根节点：只含固定的摆放，`inventory` 为 item_id → 待放数量
+    pub fn new(context: Arc<LayoutContext>, inventory: HashMap<u32, u32>) -> Self {
+        let inventory = inventory.into_iter().filter(|(_, count)| *count > 0).collect();
+        let mut state = Self {
+            context: Arc::clone(&context),
+            placed: Vec::new(),
+            occupancy: GeometryCache::new(),
+            inventory,
+            score: 0.0,
+        };
+        for pin in &context.pinned {
+            state.push_placement(pin.placement.clone());
+        }
+        state.score = state.evaluate();
+        state
+    }
+
+    pub fn context(&self) -> &Arc<LayoutContext> {
+        &self.context
+    }
+
+    pub fn placed(&self) -> &[Placement] {
+        &self.placed
+    }
+
+    pub fn occupancy(&self) -> &GeometryCache {
+        &self.occupancy
+    }
+
+    pub fn inventory(&self) -> &HashMap<u32, u32> {
+        &self.inventory
+    }
+
+    /// 是否所有家具都已放置（或剩余的可选家具已放弃）
+    pub fn is_complete(&self) -> bool {
+        self.inventory.is_empty()
+    }
+
+    /// 下一个要放的家具：占地面积大的优先，面积相同时按 item_id
+    pub fn next_item(&self) -> Option<u32> {
+        self.inventory
+            .keys()
+            .copied()
+            .map(|id| (id, self.footprint_area(id)))
+            .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+            .map(|(id, _)| id)
+    }
+
+    /// 在给定表面上放下 `placement` 是否合法
+    ///
+    /// 禁止区不越界、不压到表面上的洞和门窗留空区、不与任何已放家具的禁止区或限制区相交；
+    /// 限制区不压到已放家具的禁止区（限制区之间允许相交）。
+    /// 有 `On` 约束的两件家具叠放时彼此不做碰撞检查；俯视轮廓相交的两件家具都有 SDF 时，
+    /// 以 `check_pair` 的 3D 结果为准。禁止区还不得穿出房间壳体（见 `RoomShell::contain`）。
+    pub fn is_valid(&self, placement: &Placement, surface: &SupportSurface) -> bool {
+        let item_id = placement.item_id;
+        let Some(footprint) = self.context.footprint(item_id) else {
+            return false;
+        };
+        if footprint.is_empty() {
+            return true;
+        }
+
+        let posed = self.context.posed_shape(footprint, placement);
+        let outline = match surface.kind() {
+            SurfaceKind::Wall => match wall_outline(footprint, placement, surface) {
+                Some(outline) => outline,
+                None => return false,
+            },
+            _ => posed.polygon.clone(),
+        };
+        if !polygon_inside(&surface.outline, &outline)
+            || surface.outline.interiors().iter().any(|hole| hole.intersects(&outline))
+            || self.context.blocks_keep_out(&posed)
+        {
+            return false;
+        }
+        let allowed = |entry: &OccupiedEntry| {
+            self.is_stacked(item_id, &posed, entry) || self.clears_in_3d(placement, entry)
+        };
+        let occupancy = &self.occupancy;
+        if occupancy.forbidden().overlaps_except(&posed, allowed)
+            || occupancy.restricted().overlaps_except(&posed, allowed)
+        {
+            return false;
+        }
+
+        let clearance = self.context.clearance(item_id).filter(|c| !c.is_empty());
+        if clearance.is_some_and(|c| {
+            occupancy
+                .forbidden()
+                .overlaps_except(&self.context.posed_shape(c, placement), allowed)
+        }) {
+            return false;
+        }
+        // 俯视轮廓只保证落在支撑面内；高出顶面、穿过斜墙等由壳体检查，开销较大放在最后
+        self.context.is_contained(placement)
+    }
+
+    /// 两件家具的禁止区都有 SDF 时，用 `check_pair` 复核俯视轮廓的相交（如椅子塞进桌下）
+    ///
+    /// 凸包轮廓只是保守的近似，3D 检查认为互不冲突时放行；没有 SDF 时维持俯视结果。
+    fn clears_in_3d(&self, placement: &Placement, entry: &OccupiedEntry) -> bool {
+        let context = &self.context;
+        let (Some(item), Some(other), Some(placed)) = (
+            context.items.get(placement.item_id as usize),
+            context.items.get(entry.item_id as usize),
+            self.placed.get(entry.index),
+        ) else {
+            return false;
+        };
+        let has_sdf = |item: &PlacementRegion| item.regions.forbidden_region.sdf.is_some();
+        if !has_sdf(item) || !has_sdf(other) {
+            return false;
+        }
+        let other_matrix = context
+            .pinned
+            .get(entry.index)
+            .and_then(|pin| pin.matrix)
+            .unwrap_or_else(|| self.placement_matrix(placed));
+        check_pair(item, &self.placement_matrix(placement), other, &other_matrix).is_legal()
+    }
+
+    /// 新放的 item_id（禁止区为 `shape`）与已放的 `entry` 是否构成叠放
+    fn is_stacked(&self, item_id: u32, shape: &PosedShape, entry: &OccupiedEntry) -> bool {
+        let constraints = &self.context.constraints;
+        if constraints.is_empty() {
+            return false;
+        }
+        let (Some(a), Some(b)) = (
+            self.context.regions_type(item_id),
+            self.context.regions_type(entry.item_id),
+        ) else {
+            return false;
+        };
+        (constraints.stacks(a, b) && contains_centroid(&entry.shape.polygon, &shape.polygon))
+            || (constraints.stacks(b, a) && contains_centroid(&shape.polygon, &entry.shape.polygon))
+    }
+
+    /// 放入 `placement` 后是否仍满足所有硬约束
+    pub fn satisfies_constraints(&self, placement: &Placement) -> bool {
+        let constraints = &self.context.constraints;
+        if !constraints.has_hard() {
+            return true;
+        }
+        let mut placed = self.placed.clone();
+        placed.push(placement.clone());
+        let mut pending = self.inventory.clone();
+        if let Some(count) = pending.get_mut(&placement.item_id) {
+            *count = count.saturating_sub(1);
+        }
+        !constraints.violates_hard(&self.context, &placed, &pending)
+    }
+
+    /// 每个 item 的需求数量与实际放置数量
+    pub fn count_report(&self) -> Vec<CountReport> {
+        let demands = &self.context.demands;
+        let placed = placed_counts(&self.placed, demands.len());
+        demands
+            .iter()
+            .zip(placed)
+            .enumerate()
+            .map(|(item_id, (demand, placed))| CountReport {
+                item_id: item_id as u32,
+                regions_type: self.context.items[item_id].semantics.regions_type,
+                demand: *demand,
+                placed,
+            })
+            .collect()
+    }
+
+    /// 所有需求数量都已满足
+    pub fn counts_met(&self) -> bool {
+        self.count_report().iter().all(CountReport::is_met)
+    }
+
+    /// 当前摆放上每条约束的结果，与 `context.constraints` 一一对应
+    pub fn constraint_report(&self) -> Vec<ConstraintStatus> {
+        self.context
+            .constraints
+            .evaluate(&self.context, &self.placed, &self.inventory)
+    }
+
+    /// 从门口到每件已放家具的通行分析（门来自 `context.keep_outs` 的门前通行区）
+    ///
+    /// 禁止区底面低于 `headroom_mm` 的家具占用地面栅格，限制区之外可通行。
+    pub fn circulation_report(&self) -> CirculationReport {
+        let context = &self.context;
+        let config = &context.circulation;
+        let obstacles: Vec<_> = self
+            .occupancy
+            .forbidden()
+            .iter()
+            .filter(|entry| entry.shape.min_y < config.headroom_mm)
+            .map(|entry| &entry.shape.polygon)
+            .collect();
+        let grid = FreeSpaceGrid::build(&context.walkable_floors(), obstacles, config.cell_mm);
+        let access: Vec<AccessZone> = self
+            .placed
+            .iter()
+            .map(|p| {
+                // 没有轮廓时用空多边形，对应家具无需通行
+                let outline = |fp: Option<&Footprint>| match fp.filter(|fp| !fp.is_empty()) {
+                    Some(fp) => fp.posed(&p.pose),
+                    None => Polygon::new(LineString::new(Vec::new()), Vec::new()),
+                };
+                AccessZone {
+                    restricted: outline(context.clearance(p.item_id)),
+                    forbidden: outline(context.footprint(p.item_id)),
+                }
+            })
+            .collect();
+        analyze_circulation(&grid, &context.keep_outs, &access, config.min_corridor_mm)
+    }
+
+    /// 通行是否满足：门之间互通且每件家具都可从门口到达
+    pub fn is_walkable(&self) -> bool {
+        self.circulation_report().is_walkable()
+    }
+
+    /// 通行作为硬约束时是否被违反；没有门时不检查
+    fn blocks_circulation(&self) -> bool {
+        let context = &self.context;
+        context.circulation.hard
+            && context.keep_outs.iter().any(|z| z.kind == KeepOutKind::DoorApproach)
+            && !self.is_walkable()
+    }
+
+    /// 用一组摆放重建状态（待放清单不变），任一摆放不合法或违反硬约束时返回 None
+    ///
+    /// 依次放入并逐个做碰撞/越界检查，与构造式搜索使用相同的合法性判断。
+    pub fn with_placements(&self, placed: Vec<Placement>) -> Option<Self> {
+        let mut state = Self {
+            context: Arc::clone(&self.context),
+            placed: Vec::with_capacity(placed.len()),
+            occupancy: GeometryCache::new(),
+            inventory: self.inventory.clone(),
+            score: 0.0,
+        };
+        for (index, placement) in placed.into_iter().enumerate() {
+            let pinned = state.context.is_pinned(index, &placement);
+            if !pinned && !state.fits(&placement) {
+                return None;
+            }
+            state.push_placement(placement);
+        }
+        // 固定的摆放必须原样保留在开头
+        if state.placed.len() < state.context.pinned.len()
+            || !(0..state.context.pinned.len()).all(|i| state.context.is_pinned(i, &state.placed[i]))
+        {
+            return None;
+        }
+        // 硬约束对整组摆放判断，放入顺序不影响结果
+        let context = &state.context;
+        if context.constraints.violates_hard(context, &state.placed, &state.inventory)
+            || state.blocks_circulation()
+        {
+            return None;
+        }
+        state.score = state.evaluate();
+        Some(state)
+    }
+
+    /// 在所在表面上放下 `placement` 是否合法；没有表面位姿的摆放在任一兼容地面上合法即可
+    pub fn fits(&self, placement: &Placement) -> bool {
+        let mut surfaces = self.context.surfaces_for(placement.item_id);
+        match &placement.surface {
+            Some(pose) => surfaces
+                .find(|surface| surface.mesh_index == pose.surface)
+                .is_some_and(|surface| self.is_valid(placement, surface)),
+            None => surfaces
+                .filter(|surface| surface.kind() == SurfaceKind::Floor)
+                .any(|surface| self.is_valid(placement, surface)),
+        }
+    }
+
+    fn child(&self, placement: Placement) -> Self {
+        let mut inventory = self.inventory.clone();
+        if let Some(count) = inventory.get_mut(&placement.item_id) {
+            *count -= 1;
+            if *count == 0 {
+                inventory.remove(&placement.item_id);
+            }
+        }
+        let mut child = Self {
+            context: Arc::clone(&self.context),
+            placed: self.placed.clone(),
+            occupancy: self.occupancy.clone(),
+            inventory,
+            score: 0.0,
+        };
+        child.push_placement(placement);
+        child.score = child.evaluate();
+        child
+    }
+
+    /// 剩余的 item_id 是否都是可选的（下限已满足），可以不再放置
+    fn can_skip(&self, item_id: u32) -> bool {
+        let (Some(demand), Some(remaining)) =
+            (self.context.demand(item_id), self.inventory.get(&item_id))
+        else {
+            return false;
+        };
+        *remaining <= demand.max.saturating_sub(demand.min)
+    }
+
+    /// 放弃剩余的 item_id：摆放不变，只从待放清单中移除
+    fn skip(&self, item_id: u32) -> Self {
+        let mut child = self.clone();
+        child.inventory.remove(&item_id);
+        child.score = child.evaluate();
+        child
+    }
+
+    fn push_placement(&mut self, placement: Placement) {
+        if let (Some(forbidden), Some(restricted)) = (
+            self.context.footprint(placement.item_id),
+            self.context.clearance(placement.item_id),
+        ) {
+            self.occupancy.insert(
+                placement.item_id,
+                self.context.posed_shape(forbidden, &placement),
+                self.context.posed_shape(restricted, &placement),
+            );
+        }
+        self.placed.push(placement);
+    }
+
+    /// 把组合展开为各成员的摆放，普通家具原样保留
+    pub fn member_placements(&self) -> Vec<Placement> {
+        self.placed
+            .iter()
+            .flat_map(|p| match self.context.group(p.item_id) {
+                Some(group) => group.member_placements(&p.pose),
+                None => vec![p.clone()],
+            })
+            .collect()
+    }
+
+    /// 展开组合并对成员做随机精修（见 `ItemGroup::jitter_members`），同一种子结果相同
+    pub fn refine_members(&self, seed: u64) -> Vec<Placement> {
+        let context = &self.context;
+        let mut placements = Vec::with_capacity(self.placed.len());
+        for (index, p) in self.placed.iter().enumerate() {
+            let (Some(group), Some(hull)) = (context.group(p.item_id), context.footprint(p.item_id))
+            else {
+                placements.push(p.clone());
+                continue;
+            };
+            let mut rng = SeededRng::new(seed).fork(index as u64);
+            let hull = hull.posed(&p.pose);
+            let accepts = |member: &Placement| self.member_fits(index, member);
+            placements.extend(group.jitter_members(&p.pose, &hull, &context.footprints, &mut rng, accepts));
+        }
+        placements
+    }
+
+    /// 精修后的组合成员（组合在 `placed` 中的下标为 `group_index`）是否与组合外的占用冲突
+    ///
+    /// 与 `is_valid` 的规则相同：禁止区不压门窗留空区、不与其他家具的禁止区或限制区相交，
+    /// 限制区不压其他家具的禁止区；组合自身的占用不参与检查。
+    fn member_fits(&self, group_index: usize, member: &Placement) -> bool {
+        let context = &self.context;
+        let Some(forbidden) = context.footprint(member.item_id) else {
+            return true;
+        };
+        let own = |entry: &OccupiedEntry| entry.index == group_index;
+        let occupancy = &self.occupancy;
+        let forbidden = context.posed_shape(forbidden, member);
+        if context.blocks_keep_out(&forbidden)
+            || occupancy.forbidden().overlaps_except(&forbidden, own)
+            || occupancy.restricted().overlaps_except(&forbidden, own)
+        {
+            return false;
+        }
+        let clearance = context.clearance(member.item_id).filter(|c| !c.is_empty());
+        !clearance.is_some_and(|c| {
+            occupancy
+                .forbidden()
+                .overlaps_except(&context.posed_shape(c, member), own)
+        })
+    }
+
+    /// 可以被移动的摆放从哪个下标开始（之前的都是固定的）
+    pub fn movable_from(&self) -> usize {
+        self.context.pinned.len().min(self.placed.len())
+    }
+
+    /// 导出用的 (item_id, 世界矩阵)：固定的摆放使用原始矩阵，组合展开为成员并精修
+    pub fn transforms(&self, seed: u64) -> Vec<(u32, [[f32; 4]; 4])> {
+        let pinned = &self.context.pinned;
+        // 固定的摆放在最前且不是组合，展开后下标不变
+        self.refine_members(seed)
+            .iter()
+            .enumerate()
+            .map(|(index, p)| {
+                let matrix = pinned.get(index).and_then(|pin| pin.matrix);
+                (p.item_id, matrix.unwrap_or_else(|| self.placement_matrix(p)))
+            })
+            .collect()
+    }
+
+    /// 摆放的世界矩阵；叠放在其他家具上时抬高到支撑物禁止区的顶面
+    pub fn placement_matrix(&self, placement: &Placement) -> [[f32; 4]; 4] {
+        let mut matrix = self.context.placement_matrix(placement);
+        matrix[3][1] += self.stack_height(placement);
+        matrix
+    }
+
+    fn stack_height(&self, placement: &Placement) -> f32 {
+        let context = &self.context;
+        let (Some(regions_type), Some(footprint)) = (
+            context.regions_type(placement.item_id),
+            context.footprint(placement.item_id),
+        ) else {
+            return 0.0;
+        };
+        if footprint.is_empty() {
+            return 0.0;
+        }
+        let shape = footprint.posed(&placement.pose);
+        self.placed
+            .iter()
+            .filter(|other| {
+                context
+                    .regions_type(other.item_id)
+                    .is_some_and(|t| context.constraints.stacks(regions_type, t))
+            })
+            .filter_map(|other| {
+                let support = context.footprint(other.item_id)?;
+                contains_centroid(&support.posed(&other.pose), &shape)
+                    .then_some(support.max_y - footprint.min_y)
+            })
+            .fold(0.0, f32::max)
+    }
+
+    /// 评分：由上下文中的目标函数给出
+    fn evaluate(&self) -> f32 {
+        self.context.objective.evaluate(self)
+    }
+
+    /// 各评分项明细（按需重新计算，不随节点保存）
+    pub fn breakdown(&self) -> ScoreBreakdown {
+        self.context.objective.breakdown(self)
+    }
+
+    fn footprint_area(&self, item_id: u32) -> f32 {
+        self.context.footprint(item_id).map_or(0.0, |fp| fp.area())
+    }
+}
+
 impl SearchNode for LayoutState {
     fn expand(&self) -> BinaryHeap<Self> {
         let mut children = BinaryHeap::new();
 
-        // 假设最大扩展128个子节点
-        for _ in 0..128 {
-            let new_node = LayoutState {
-                placed: self.placed.clone(),       // 复制已放置的家具
-                occupancy: self.occupancy.clone(), // 复制几何缓存
-                inventory: self.inventory.clone(), // 复制剩余家具
-                score: self.score + 1.0,           // 假设新节点的分数加 1
+        let Some(item_id) = self.next_item() else {
+            return children;
+        };
+
+        // 每个节点的随机流只由种子和节点 key 决定，与扩展顺序无关
+        let mut rng = SeededRng::new(self.context.seed).fork(self.key());
+        let jitter = self.context.candidates.jitter_mm * 0.5;
+
+        let mut candidates = Vec::new();
+        for surface in self.context.surfaces_for(item_id) {
+            // 墙面上的家具保持竖直，不绕法向旋转
+            let thetas: &[f32] = match surface.kind() {
+                SurfaceKind::Wall => &[0.0],
+                _ => &self.context.candidates.thetas,
             };
-            children.push(new_node);
+            for sample in &surface.samples {
+                for &theta in thetas {
+                    let (du, dv) = if jitter > 0.0 {
+                        (rng.range_f32(-jitter, jitter), rng.range_f32(-jitter, jitter))
+                    } else {
+                        (0.0, 0.0)
+                    };
+                    let pose = SurfacePose::new(surface.mesh_index, sample[0] + du, sample[1] + dv, theta);
+                    let Some(placement) = self.context.place_on(item_id, pose) else {
+                        continue;
+                    };
+                    if self.is_valid(&placement, surface) && self.satisfies_constraints(&placement) {
+                        candidates.push(placement);
+                    }
+                }
+            }
+        }
+
+        // 候选过多时等间隔抽取，保持空间上的分散
+        let max_children = self.context.candidates.max_children.max(1);
+        let stride = candidates.len().div_ceil(max_children).max(1);
+        let offset = rng.below(stride);
+        // 通行检查代价较高，只对抽取后的子节点做；放入家具只会减少空地，剪枝不会误删
+        for placement in candidates.into_iter().skip(offset).step_by(stride) {
+            let child = self.child(placement);
+            if !child.blocks_circulation() {
+                children.push(child);
+            }
+        }
+        if self.can_skip(item_id) {
+            children.push(self.skip(item_id));
         }
 
         children
@@ -36,23 +542,80 @@ impl SearchNode for LayoutState {
     fn key(&self) -> u64 {
         self.hash_layout()
     }
+
+    fn depth(&self) -> usize {
+        self.placed.len()
+    }
+
+    fn is_goal(&self) -> bool {
+        self.is_complete()
+    }
 }
 
-// 计算布局状态的唯一key
-impl LayoutState {
-    // 计算布局状态的唯一key
-    fn hash_layout(&self) -> u64 {
-        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+/// LayoutState 的落盘形式：只保存摆放和剩余清单，分数与占用在恢复时重算
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LayoutSnapshot {
+    pub placed: Vec<Placement>,
+    /// item_id → 剩余数量，按 item_id 排序保证输出稳定
+    pub inventory: BTreeMap<u32, u32>,
+}
+
+impl Snapshot for LayoutState {
+    type Data = LayoutSnapshot;
+    type Context = Arc<LayoutContext>;
+
+    fn snapshot(&self) -> LayoutSnapshot {
+        LayoutSnapshot {
+            placed: self.placed.clone(),
+            inventory: self.inventory.iter().map(|(k, v)| (*k, *v)).collect(),
+        }
+    }
+
+    fn restore(data: LayoutSnapshot, context: &Arc<LayoutContext>) -> Result<Self, String> {
+        let root = Self::new(Arc::clone(context), data.inventory.into_iter().collect());
+        root.with_placements(data.placed)
+            .ok_or_else(|| "checkpoint layout is not valid in the current scene".to_string())
+    }
+}
+
+impl Traceable for LayoutState {
+    type Step = Placement;
+
+    fn last_step(&self) -> Option<Placement> {
+        self.placed.last().cloned()
+    }
+}
 
-        // 对 `placed`, `inventory` 进行哈希运算
-        self.placed.hash(&mut hasher);
-        for (key, value) in &self.inventory {
-            key.hash(&mut hasher); // 对键进行哈希
-            value.hash(&mut hasher); // 对值进行哈希
+impl Diverse for LayoutState {
+    /// 同类家具之间的最大位移（mm，含高度）：每件家具到另一布局中最近的同 item_id 家具的距离取最大；
+    /// 某个 item_id 的件数不同时为无穷大
+    fn distance(&self, other: &Self) -> f32 {
+        let len = self.context.items.len();
+        if placed_counts(&self.placed, len) != placed_counts(&other.placed, len) {
+            return f32::INFINITY;
         }
+        let farthest = |a: &[Placement], b: &[Placement]| {
+            a.iter()
+                .map(|p| {
+                    b.iter()
+                        .filter(|q| q.item_id == p.item_id)
+                        .map(|q| {
+                            let dy = self.context.lift(p) - self.context.lift(q);
+                            (p.pose.x - q.pose.x).hypot(p.pose.y - q.pose.y).hypot(dy)
+                        })
+                        .fold(f32::INFINITY, f32::min)
+                })
+                .fold(0.0, f32::max)
+        };
+        farthest(&self.placed, &other.placed).max(farthest(&other.placed, &self.placed))
+    }
+}
 
-        // 返回哈希值
-        hasher.finish()
+// 计算布局状态的唯一key
+impl LayoutState {
+    // 排序 + 量化后的稳定哈希：同一组摆放无论放置顺序如何都得到相同 key
+    fn hash_layout(&self) -> u64 {
+        layout_key(&self.placed, &self.inventory, &self.context.key)
     }
 }
 
@@ -73,6 +636,26 @@ impl PartialOrd for LayoutState {
 
 impl Ord for LayoutState {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+        self.score.total_cmp(&other.score)
     }
 }
+
+/// 墙面上家具正面在面内 (u, v) 的投影：宽取局部 X 范围，高取局部 Y 范围
+///
+/// 墙面家具不绕法向旋转，投影是轴对齐的矩形；没有该表面的位姿或有旋转时为 None。
+fn wall_outline(footprint: &Footprint, placement: &Placement, surface: &SupportSurface) -> Option<Polygon<f64>> {
+    let pose = placement
+        .surface
+        .as_ref()
+        .filter(|p| p.surface == surface.mesh_index && p.rotation == 0.0)?;
+    let (min_x, max_x) = footprint
+        .points
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), p| (lo.min(p[0]), hi.max(p[0])));
+    let (u0, u1) = ((pose.u + min_x) as f64, (pose.u + max_x) as f64);
+    let (v0, v1) = ((pose.v + footprint.min_y) as f64, (pose.v + footprint.max_y) as f64);
+    Some(Polygon::new(
+        LineString::from(vec![(u0, v0), (u1, v0), (u1, v1), (u0, v1)]),
+        Vec::new(),
+    ))
+}
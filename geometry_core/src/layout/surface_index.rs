@@ -0,0 +1,119 @@
+use crate::geometry_ops::boundary::flatten_boundary_polygon;
+use crate::layout::surface_pose::SurfaceFrame;
+use crate::models::space::Space;
+use geo::{Area, Contains};
+use geo_types::{Point, Polygon};
+use nalgebra::Vector3;
+use types::{RegionsType, RegionsTypeMask};
+
+/// `RegionsTypeMask` 可表达的类型数
+const MAX_REGIONS_TYPES: usize = 32;
+
+/// 一个可放置表面：拟合平面、面内轮廓与面积
+#[derive(Clone, Debug)]
+pub struct IndexedSurface {
+    /// `Space::meshes` 下标
+    pub mesh_index: usize,
+    pub mask: RegionsTypeMask,
+    pub frame: SurfaceFrame,
+    /// 轮廓（含洞，如柱子），坐标为 `frame` 的 (u, v)
+    pub outline: Polygon<f64>,
+    /// 轮廓面积（mm²，已扣除洞）
+    pub area: f32,
+}
+
+impl IndexedSurface {
+    /// 世界坐标投影到面内 (u, v)
+    pub fn to_uv(&self, p: [f32; 3]) -> [f32; 2] {
+        project_uv(&self.frame, p)
+    }
+
+    pub fn contains_uv(&self, u: f32, v: f32) -> bool {
+        self.outline.contains(&Point::new(u as f64, v as f64))
+    }
+}
+
+/// RegionsType → 兼容表面的预计算索引，表面按 mesh 顺序排列
+#[derive(Clone, Debug, Default)]
+pub struct SurfaceIndex {
+    surfaces: Vec<IndexedSurface>,
+    by_type: Vec<Vec<usize>>,
+}
+
+impl SurfaceIndex {
+    /// 只收录掩码非空、能拟合平面且轮廓有效的网格；`frames` 与 `space.meshes` 一一对应
+    pub fn build(space: &Space, frames: &[Option<SurfaceFrame>]) -> Self {
+        let mut surfaces = Vec::new();
+        let mut by_type = vec![Vec::new(); MAX_REGIONS_TYPES];
+
+        let metas = space.meshes.iter().zip(space.surface_metas.iter()).zip(frames);
+        for (mesh_index, ((mesh, meta), frame)) in metas.enumerate() {
+            let mask = meta.regions_type_mask;
+            let Some(frame) = frame else {
+                continue;
+            };
+            if mask.is_empty() {
+                continue;
+            }
+
+            let Some(boundary) = flatten_boundary_polygon(mesh) else {
+                continue;
+            };
+            let outline = boundary.reproject(|p| {
+                let [u, v] = project_uv(frame, p);
+                [u as f64, v as f64]
+            });
+            let area = outline.unsigned_area() as f32;
+            if area <= 0.0 {
+                continue;
+            }
+
+            let slot = surfaces.len();
+            for (id, list) in by_type.iter_mut().enumerate() {
+                if mask.contains_id(id as RegionsType) {
+                    list.push(slot);
+                }
+            }
+            surfaces.push(IndexedSurface {
+                mesh_index,
+                mask,
+                frame: frame.clone(),
+                outline,
+                area,
+            });
+        }
+
+        Self { surfaces, by_type }
+    }
+
+    pub fn surfaces(&self) -> &[IndexedSurface] {
+        &self.surfaces
+    }
+
+    pub fn len(&self) -> usize {
+        self.surfaces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.surfaces.is_empty()
+    }
+
+    /// 可以放置 `regions_type` 的表面
+    pub fn compatible(&self, regions_type: RegionsType) -> impl Iterator<Item = &IndexedSurface> {
+        self.by_type
+            .get(regions_type as usize)
+            .into_iter()
+            .flatten()
+            .map(|slot| &self.surfaces[*slot])
+    }
+
+    /// 可以放置 `regions_type` 的表面总面积（mm²）
+    pub fn compatible_area(&self, regions_type: RegionsType) -> f32 {
+        self.compatible(regions_type).map(|s| s.area).sum()
+    }
+}
+
+fn project_uv(frame: &SurfaceFrame, p: [f32; 3]) -> [f32; 2] {
+    let d = Vector3::from(p) - frame.origin;
+    [d.dot(&frame.u_axis), d.dot(&frame.v_axis)]
+}
@@ -0,0 +1,122 @@
+use crate::geometry_ops::plane::fit_plane_pca;
+use crate::models::mesh::Mesh;
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+/// 表面朝向分类定义在 geometry_ops，分割等几何算法也要用到
+pub use crate::geometry_ops::plane::SurfaceKind;
+
+/// 表面的局部坐标系：法向指向房间内部，(u, v) 为面内坐标轴
+///
+/// - 地面：u 沿世界 +X，v 沿世界 +Z，与 `Pose2D` 的 x/y 方向一致
+/// - 墙面：v 沿墙面向上，u = v × normal
+/// - 顶面：u 沿世界 +X，v = u × 世界 +Y
+#[derive(Clone, Debug)]
+pub struct SurfaceFrame {
+    pub kind: SurfaceKind,
+    pub origin: Vector3<f32>,
+    pub u_axis: Vector3<f32>,
+    pub v_axis: Vector3<f32>,
+    pub normal: Vector3<f32>,
+}
+
+impl SurfaceFrame {
+    /// 用 `fit_plane_pca` 拟合网格平面，法向翻转到朝向 `interior`（房间内的一点）
+    ///
+    /// `interior` 落在平面上时无法判断内外，此时取朝上的法向。
+    pub fn fit(mesh: &Mesh, interior: &Vector3<f32>) -> Option<Self> {
+        let (origin, _, _, normal) = fit_plane_pca(&mesh.positions)?;
+        let side = (interior - origin).dot(&normal);
+        let flip = if side.abs() > 1e-3 { side < 0.0 } else { normal.y < 0.0 };
+        let normal = if flip { -normal } else { normal };
+        Some(Self::from_plane(origin, normal))
+    }
+
+    pub fn from_plane(origin: Vector3<f32>, normal: Vector3<f32>) -> Self {
+        let normal = normal.normalize();
+        let kind = SurfaceKind::from_normal(&normal);
+        let (u_axis, v_axis) = match kind {
+            SurfaceKind::Floor | SurfaceKind::Ceiling => {
+                let u = project_onto_plane(&Vector3::x(), &normal);
+                let up = Vector3::y();
+                (u, u.cross(&up).normalize())
+            }
+            SurfaceKind::Wall => {
+                let v = project_onto_plane(&Vector3::y(), &normal);
+                (v.cross(&normal).normalize(), v)
+            }
+        };
+        Self {
+            kind,
+            origin,
+            u_axis,
+            v_axis,
+            normal,
+        }
+    }
+
+    /// 面内坐标 (u, v) 加法向偏移对应的世界坐标
+    pub fn point(&self, u: f32, v: f32, offset: f32) -> Vector3<f32> {
+        self.origin + self.u_axis * u + self.v_axis * v + self.normal * offset
+    }
+}
+
+fn project_onto_plane(axis: &Vector3<f32>, normal: &Vector3<f32>) -> Vector3<f32> {
+    let projected = axis - normal * axis.dot(normal);
+    if projected.norm() > 1e-4 {
+        return projected.normalize();
+    }
+    // axis 与法向平行：任取一条垂直方向
+    let fallback = if normal.x.abs() < 0.9 { Vector3::x() } else { Vector3::z() };
+    (fallback - normal * fallback.dot(normal)).normalize()
+}
+
+/// 相对表面的位姿：`surface` 为 `Space::meshes` 下标，(u, v) 为面内坐标（mm），
+/// `rotation` 为绕法向、从 u 转向 v 的角度（弧度），`offset` 为沿法向的距离（mm）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SurfacePose {
+    pub surface: usize,
+    pub u: f32,
+    pub v: f32,
+    pub rotation: f32,
+    pub offset: f32,
+}
+
+impl SurfacePose {
+    pub fn new(surface: usize, u: f32, v: f32, rotation: f32) -> Self {
+        Self {
+            surface,
+            u,
+            v,
+            rotation,
+            offset: 0.0,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// 世界变换矩阵（列主序，`m[3]` 为平移）
+    ///
+    /// 物体局部 +Y 始终朝上：地面上为法向，顶面上为法向的反方向（吊挂），
+    /// 墙面上为墙面向上方向，此时物体局部 +Z 沿法向朝向房间内部。
+    pub fn to_matrix(&self, frame: &SurfaceFrame) -> [[f32; 4]; 4] {
+        let (s, c) = self.rotation.sin_cos();
+        let u = frame.u_axis * c + frame.v_axis * s;
+        let v = frame.v_axis * c - frame.u_axis * s;
+        let (x, y, z) = match frame.kind {
+            SurfaceKind::Floor => (u, frame.normal, v),
+            SurfaceKind::Ceiling => (u, -frame.normal, v),
+            SurfaceKind::Wall => (u, v, frame.normal),
+        };
+        let t = frame.point(self.u, self.v, self.offset);
+        [
+            [x.x, x.y, x.z, 0.0],
+            [y.x, y.y, y.z, 0.0],
+            [z.x, z.y, z.z, 0.0],
+            [t.x, t.y, t.z, 1.0],
+        ]
+    }
+}
@@ -2,3 +2,4 @@ pub mod search;
 pub mod layout;
 pub mod models;
 pub mod geometry_ops;
+pub mod rng;
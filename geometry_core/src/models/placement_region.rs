@@ -25,7 +25,12 @@ pub struct Region {
 #[derive(Debug, Clone)]
 pub struct PlacementSemantics {
     pub regions_type: RegionsType,
+    /// 需要放置的数量（区间上限）
     pub count: i32,
+    /// 数量下限；None 表示必须恰好放 `count` 件，0 表示可选
+    pub min_count: Option<i32>,
+    /// 超出下限后每多放一件的奖励
+    pub reward: f32,
 }
 
 #[derive(Debug, Clone)]
@@ -13,3 +13,23 @@ pub struct Space {
 }
 
 pub type Mesh = crate::models::mesh::Mesh;
+
+impl Space {
+    /// 所有网格包围盒的中心，用于判断表面法向朝向房间内部
+    pub fn interior_point(&self) -> Option<[f32; 3]> {
+        let mut points = self.meshes.iter().flat_map(|m| m.positions.iter());
+        let first = *points.next()?;
+        let (min, max) = points.fold((first, first), |(mut min, mut max), p| {
+            for i in 0..3 {
+                min[i] = min[i].min(p[i]);
+                max[i] = max[i].max(p[i]);
+            }
+            (min, max)
+        });
+        Some([
+            (min[0] + max[0]) * 0.5,
+            (min[1] + max[1]) * 0.5,
+            (min[2] + max[2]) * 0.5,
+        ])
+    }
+}
@@ -0,0 +1,57 @@
+/// 采样与搜索共用的确定性随机数（SplitMix64）
+///
+/// 算法写死在这里而不依赖外部 crate，保证同一种子在不同构建、平台上序列一致
+#[derive(Clone, Debug)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// 当前内部状态；`SeededRng::new(state)` 从这里继续同一序列
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// 由当前状态和 `stream` 派生独立的序列，不推进 `self`
+    pub fn fork(&self, stream: u64) -> Self {
+        let mut mixer = Self::new(self.state ^ stream.rotate_left(32));
+        Self::new(mixer.next_u64() ^ stream)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// [0, 1) 均匀分布
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// [lo, hi) 均匀分布
+    pub fn range_f32(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.next_f32()
+    }
+
+    /// [0, n) 均匀分布；`n == 0` 时返回 0
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
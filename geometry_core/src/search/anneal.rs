@@ -0,0 +1,85 @@
+use crate::rng::SeededRng;
+use crate::search::node::SearchNode;
+
+/// 局部搜索的邻域：在当前解附近随机生成一个合法的新解
+pub trait Neighborhood: SearchNode + Clone {
+    /// 生成一个邻居；随机出的操作不合法时返回 None
+    fn neighbor(&self, rng: &mut SeededRng) -> Option<Self>;
+}
+
+/// 温度表：T_k = initial_temperature * cooling_rate^k，降到 min_temperature 为止
+#[derive(Clone, Debug)]
+pub struct AnnealSchedule {
+    pub initial_temperature: f32,
+    pub cooling_rate: f32,
+    pub min_temperature: f32,
+    pub max_iterations: usize,
+}
+
+impl Default for AnnealSchedule {
+    fn default() -> Self {
+        Self {
+            initial_temperature: 0.05,
+            cooling_rate: 0.995,
+            min_temperature: 1e-4,
+            max_iterations: 2000,
+        }
+    }
+}
+
+/// 退火统计
+#[derive(Clone, Debug, Default)]
+pub struct AnnealStats {
+    pub iterations: usize,
+    /// 接受的移动（含变差的移动）
+    pub accepted: usize,
+    /// 因 Metropolis 准则被拒绝的移动
+    pub rejected: usize,
+    /// 不合法（碰撞、越界）的移动
+    pub invalid: usize,
+    /// 刷新最优解的次数
+    pub improved: usize,
+    pub initial_score: f32,
+    pub final_score: f32,
+    pub final_temperature: f32,
+}
+
+/// 模拟退火：从 `start` 出发，返回见过的最优解
+pub fn anneal<T: Neighborhood>(start: T, schedule: &AnnealSchedule, seed: u64) -> (T, AnnealStats) {
+    let mut rng = SeededRng::new(seed);
+    let mut stats = AnnealStats {
+        initial_score: start.score(),
+        ..AnnealStats::default()
+    };
+    let mut best = start.clone();
+    let mut current = start;
+    let mut temperature = schedule.initial_temperature;
+
+    while stats.iterations < schedule.max_iterations && temperature > schedule.min_temperature {
+        stats.iterations += 1;
+        temperature *= schedule.cooling_rate;
+
+        let Some(candidate) = current.neighbor(&mut rng) else {
+            stats.invalid += 1;
+            continue;
+        };
+
+        let delta = candidate.score() - current.score();
+        let accept = delta >= 0.0 || rng.next_f32() < (delta / temperature).exp();
+        if !accept {
+            stats.rejected += 1;
+            continue;
+        }
+
+        stats.accepted += 1;
+        current = candidate;
+        if current.score() > best.score() {
+            best = current.clone();
+            stats.improved += 1;
+        }
+    }
+
+    stats.final_score = best.score();
+    stats.final_temperature = temperature;
+    (best, stats)
+}
@@ -0,0 +1,83 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::search::engine::SearchStats;
+use crate::search::node::SearchNode;
+
+/// 检查点文件格式版本，格式不兼容时递增
+pub const CHECKPOINT_VERSION: u32 = 2;
+
+/// 可写入检查点的节点：与共享上下文分离的稳定表示
+pub trait Snapshot: SearchNode {
+    /// 落盘数据，不含共享上下文
+    type Data: Serialize + DeserializeOwned;
+    /// 恢复时需要的共享上下文
+    type Context;
+
+    fn snapshot(&self) -> Self::Data;
+    fn restore(data: Self::Data, context: &Self::Context) -> Result<Self, String>;
+}
+
+/// 检查点参数：`every_steps` 为 0 时只在运行结束时写入
+#[derive(Clone, Debug)]
+pub struct CheckpointConfig {
+    pub path: PathBuf,
+    pub every_steps: usize,
+}
+
+/// 搜索引擎的完整可恢复状态
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SearchCheckpoint<D> {
+    pub version: u32,
+    pub step_count: usize,
+    pub stats: SearchStats,
+    /// 已访问 key，升序保存以保证文件内容稳定
+    pub visited: Vec<u64>,
+    pub frontier: Vec<FrontierEntry<D>>,
+    /// Frontier 平分 tie 随机数的状态，恢复后与不中断的运行保持一致
+    pub frontier_rng: Option<u64>,
+    /// Frontier 各深度已出队的节点数（束搜索的每层名额）
+    #[serde(default)]
+    pub frontier_popped: Vec<(usize, usize)>,
+    pub best: Option<D>,
+    pub goal: Option<D>,
+}
+
+/// Frontier 中的一个节点及其 tie 值
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FrontierEntry<D> {
+    pub node: D,
+    pub tie: u64,
+}
+
+impl<D: Serialize + DeserializeOwned> SearchCheckpoint<D> {
+    /// 先写临时文件再重命名，中断时不会留下半个检查点
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+        }
+        let text = serde_json::to_string(self)
+            .map_err(|err| format!("Failed to serialize checkpoint: {err}"))?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, text)
+            .map_err(|err| format!("Failed to write {}: {err}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .map_err(|err| format!("Failed to write {}: {err}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        let checkpoint: Self = serde_json::from_str(&text)
+            .map_err(|err| format!("Failed to parse checkpoint {}: {err}", path.display()))?;
+        if checkpoint.version != CHECKPOINT_VERSION {
+            return Err(format!(
+                "Unsupported checkpoint version {} (expected {CHECKPOINT_VERSION})",
+                checkpoint.version
+            ));
+        }
+        Ok(checkpoint)
+    }
+}
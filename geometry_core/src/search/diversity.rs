@@ -0,0 +1,79 @@
+use crate::search::node::SearchNode;
+
+/// 可以度量彼此差异的节点，用于收集多个不同的结果
+pub trait Diverse: SearchNode {
+    /// 两个节点的差异（越大越不同）；无法比较时返回无穷大
+    fn distance(&self, other: &Self) -> f32;
+}
+
+/// 保留得分最高的 K 个目标节点，且两两差异不小于 `min_distance`
+pub struct DiverseSet<T> {
+    capacity: usize,
+    min_distance: f32,
+    distance: fn(&T, &T) -> f32,
+    // 只在节点被保留时复制，引擎本身不要求 T: Clone
+    duplicate: fn(&T) -> T,
+    /// 按得分从高到低
+    kept: Vec<T>,
+}
+
+impl<T: Diverse + Clone> DiverseSet<T> {
+    pub fn new(capacity: usize, min_distance: f32) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            min_distance,
+            distance: T::distance,
+            duplicate: T::clone,
+            kept: Vec::new(),
+        }
+    }
+}
+
+impl<T: SearchNode> DiverseSet<T> {
+    /// 尝试加入一个目标节点，返回是否被保留
+    ///
+    /// 与已保留节点过于接近时，只有得分比所有相近节点都高才替换它们；
+    /// 超出容量时丢弃得分最低的。
+    pub fn offer(&mut self, node: &T) -> bool {
+        let score = node.score();
+        let close: Vec<usize> = (0..self.kept.len())
+            .filter(|i| (self.distance)(node, &self.kept[*i]) < self.min_distance)
+            .collect();
+        if close.iter().any(|i| self.kept[*i].score() >= score) {
+            return false;
+        }
+        if self.kept.len() - close.len() >= self.capacity
+            && self.kept.last().is_some_and(|worst| worst.score() >= score)
+        {
+            return false;
+        }
+        for i in close.into_iter().rev() {
+            self.kept.remove(i);
+        }
+        let at = self.kept.partition_point(|kept| kept.score() >= score);
+        self.kept.insert(at, (self.duplicate)(node));
+        self.kept.truncate(self.capacity);
+        true
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.kept.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.kept.is_empty()
+    }
+
+    /// 已保留的节点，按得分从高到低
+    pub fn nodes(&self) -> &[T] {
+        &self.kept
+    }
+
+    pub fn into_nodes(self) -> Vec<T> {
+        self.kept
+    }
+}
@@ -1,8 +1,118 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use crate::search::checkpoint::{
+    CheckpointConfig, FrontierEntry, SearchCheckpoint, Snapshot, CHECKPOINT_VERSION,
+};
+use crate::search::diversity::{Diverse, DiverseSet};
 use crate::search::frontier::{Frontier};
 use crate::search::node::SearchNode;
+use crate::search::trace::{describe_step, SearchTrace, TraceEvent, TraceRecord, Traceable, Tracer};
+
+/// 搜索预算：任一条件满足即停止，`None` 表示不限制
+#[derive(Clone, Debug)]
+pub struct SearchBudget {
+    pub max_steps: Option<usize>,
+    pub max_duration: Option<Duration>,
+    pub target_score: Option<f32>,
+    /// 找到第一个目标节点（多结果模式下为收集满）后是否停止
+    pub stop_on_goal: bool,
+    /// 外部停止标志（如可视化窗口关闭），置为 true 后在下一步前停止
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for SearchBudget {
+    fn default() -> Self {
+        Self {
+            max_steps: None,
+            max_duration: None,
+            target_score: None,
+            stop_on_goal: true,
+            cancel: None,
+        }
+    }
+}
+
+impl SearchBudget {
+    pub fn with_max_steps(mut self, steps: usize) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    pub fn with_max_duration(mut self, duration: Duration) -> Self {
+        self.max_duration = Some(duration);
+        self
+    }
+
+    pub fn with_target_score(mut self, score: f32) -> Self {
+        self.target_score = Some(score);
+        self
+    }
+
+    pub fn with_cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|c| c.load(Ordering::Relaxed))
+    }
+}
+
+/// 并行扩展参数：每批弹出 `batch_size` 个节点，由 `workers` 个线程扩展
+///
+/// 子节点按弹出顺序合并回 Frontier，因此结果只取决于 `batch_size`，
+/// 与线程数无关；同一种子下可复现。
+#[derive(Clone, Debug)]
+pub struct ParallelConfig {
+    pub workers: usize,
+    pub batch_size: usize,
+}
+
+impl Default for ParallelConfig {
+    fn default() -> Self {
+        let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+        Self {
+            workers,
+            batch_size: workers * 2,
+        }
+    }
+}
+
+/// 停止原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StopReason {
+    #[default]
+    Running,
+    GoalReached,
+    TargetScore,
+    StepLimit,
+    TimeLimit,
+    Exhausted,
+    Cancelled,
+}
+
+/// 搜索统计
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SearchStats {
+    /// 已扩展的节点数
+    pub expanded: usize,
+    /// 被 Frontier 丢弃的节点数
+    pub pruned: usize,
+    /// 因 key 已访问而跳过的节点数
+    pub duplicates: usize,
+    /// Frontier 的峰值大小
+    pub peak_frontier: usize,
+    pub elapsed: Duration,
+    pub stop_reason: StopReason,
+}
+
+type Progress<T> = Box<dyn FnMut(&T) + Send>;
+
 // 搜索引擎
 pub struct SearchEngine<T, F>
 where 
@@ -12,6 +122,16 @@ where
     frontier: F,
     visited: HashSet<u64>,
     step_count: usize,
+    stats: SearchStats,
+    best: Option<T>,
+    goal: Option<T>,
+    // 多结果模式下保留的互不相近的目标节点
+    alternatives: Option<DiverseSet<T>>,
+    // 从检查点恢复前已被 Frontier 丢弃的节点数
+    pruned_base: usize,
+    trace: Option<Tracer<T>>,
+    // 最高分节点提高时的回调
+    progress: Option<Progress<T>>,
     _marker: PhantomData<T>,
 }
 
@@ -22,39 +142,139 @@ where
 {
     pub fn with_root(mut frontier: F, root: T) -> Self {
         frontier.push(root);
+        let stats = SearchStats {
+            peak_frontier: frontier.len(),
+            ..SearchStats::default()
+        };
 
         Self {
             frontier,
             visited: HashSet::new(),
             step_count: 0,
+            stats,
+            best: None,
+            goal: None,
+            alternatives: None,
+            pruned_base: 0,
+            trace: None,
+            progress: None,
             _marker: PhantomData,
         }
     }
 
-    pub fn step(&mut self) {
+    /// 每当 `best()` 的得分提高时调用 `progress`，用于在搜索过程中发布中间结果
+    pub fn with_progress(mut self, progress: impl FnMut(&T) + Send + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// 弹出并扩展一个节点；Frontier 为空时返回 false
+    pub fn step(&mut self) -> bool {
+        match self.pop_next() {
+            Popped::Empty => false,
+            Popped::Handled => true,
+            Popped::Expand(node) => {
+                let expansion = expand_unvisited(&node, &self.visited);
+                self.merge(node, expansion);
+                true
+            }
+        }
+    }
+
+    /// 弹出下一个节点：重复节点和目标节点在此处理，其余交给调用方扩展
+    fn pop_next(&mut self) -> Popped<T> {
         let Some(node) = self.frontier.pop() else {
-            return;
+            return Popped::Empty;
         };
 
         let key = node.key();
         if self.visited.contains(&key) {
-            return;
+            self.stats.duplicates += 1;
+            self.trace_node(TraceEvent::Duplicate, &node, None);
+            return Popped::Handled;
         }
 
         self.visited.insert(key);
 
-        let children = node.expand();
+        if node.is_goal() {
+            // 目标节点不再扩展
+            self.trace_node(TraceEvent::Goal, &node, None);
+            self.record_goal(node);
+            self.step_count += 1;
+            return Popped::Handled;
+        }
 
-        for child in children {
-            let k = child.key();
-            if !self.visited.contains(&k) {
-                self.frontier.push(child);
-            }
+        Popped::Expand(node)
+    }
+
+    /// 把一个节点的扩展结果并入 Frontier
+    fn merge(&mut self, node: T, expansion: Expansion<T>) {
+        self.stats.expanded += 1;
+        self.stats.duplicates += expansion.duplicates.len();
+
+        let parent = node.key();
+        self.trace_node(TraceEvent::Expand, &node, None);
+        for (id, score) in expansion.duplicates {
+            self.trace_record(TraceRecord {
+                parent: Some(parent),
+                score: Some(score),
+                ..self.record(TraceEvent::Duplicate, id)
+            });
+        }
+        for child in expansion.children {
+            self.trace_node(TraceEvent::Push, &child, Some(parent));
+            self.frontier.push(child);
+        }
+        // 即使不记录轨迹也要取走，避免 Frontier 内的缓存增长
+        for id in self.frontier.take_pruned() {
+            self.trace_record(self.record(TraceEvent::Pruned, id));
         }
 
+        self.stats.peak_frontier = self.stats.peak_frontier.max(self.frontier.len());
+        self.stats.pruned = self.pruned_base + self.frontier.pruned();
+        self.record_best(node);
         self.step_count += 1;
     }
 
+    /// 在预算内运行搜索，返回统计信息
+    pub fn run(&mut self, budget: &SearchBudget) -> SearchStats {
+        self.run_with(budget, Self::step)
+    }
+
+    fn run_with(&mut self, budget: &SearchBudget, mut step: impl FnMut(&mut Self) -> bool) -> SearchStats {
+        let start = Instant::now();
+        let first_step = self.step_count;
+
+        let reason = loop {
+            if budget.stop_on_goal && self.goal_reached() {
+                break StopReason::GoalReached;
+            }
+            if budget
+                .target_score
+                .zip(self.best_score())
+                .is_some_and(|(target, best)| best >= target)
+            {
+                break StopReason::TargetScore;
+            }
+            if budget.max_steps.is_some_and(|max| self.step_count - first_step >= max) {
+                break StopReason::StepLimit;
+            }
+            if budget.max_duration.is_some_and(|max| start.elapsed() >= max) {
+                break StopReason::TimeLimit;
+            }
+            if budget.is_cancelled() {
+                break StopReason::Cancelled;
+            }
+            if !step(self) {
+                break StopReason::Exhausted;
+            }
+        };
+
+        self.stats.elapsed += start.elapsed();
+        self.stats.stop_reason = reason;
+        self.stats.clone()
+    }
+
     pub fn frontier_len(&self) -> usize {
         self.frontier.len()
     }
@@ -62,4 +282,376 @@ where
     pub fn steps(&self) -> usize {
         self.step_count
     }
+
+    pub fn stats(&self) -> &SearchStats {
+        &self.stats
+    }
+
+    /// 目前见过的最高分节点（含目标节点）
+    pub fn best(&self) -> Option<&T> {
+        best_of(&self.best, &self.goal)
+    }
+
+    /// 目前见过的最高分目标节点
+    pub fn goal(&self) -> Option<&T> {
+        self.goal.as_ref()
+    }
+
+    /// 取出结果：优先返回目标节点，否则返回最高分节点
+    pub fn into_result(self) -> Option<T> {
+        self.goal.or(self.best)
+    }
+
+    /// 多结果模式下保留的目标节点（按得分从高到低），未开启时为空
+    pub fn alternatives(&self) -> &[T] {
+        self.alternatives.as_ref().map_or(&[], |set| set.nodes())
+    }
+
+    /// 取出全部结果：多结果模式下为保留的目标节点，否则为 `into_result` 的单个结果
+    pub fn into_results(self) -> Vec<T> {
+        match self.alternatives {
+            Some(set) if !set.is_empty() => set.into_nodes(),
+            _ => self.goal.or(self.best).into_iter().collect(),
+        }
+    }
+
+    /// 当前的轨迹文件（未开启时为 `None`）
+    pub fn trace(&self) -> Option<&SearchTrace> {
+        self.trace.as_ref().map(|t| &t.sink)
+    }
+
+    /// 把缓冲的轨迹写入磁盘
+    pub fn flush_trace(&mut self) -> Result<(), String> {
+        match &mut self.trace {
+            Some(tracer) => tracer.sink.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn record(&self, event: TraceEvent, id: u64) -> TraceRecord {
+        TraceRecord {
+            step: self.step_count,
+            event,
+            id,
+            parent: None,
+            depth: None,
+            score: None,
+            placement: None,
+        }
+    }
+
+    fn trace_node(&mut self, event: TraceEvent, node: &T, parent: Option<u64>) {
+        let Some(tracer) = &self.trace else {
+            return;
+        };
+        let placement = if event == TraceEvent::Push {
+            (tracer.describe)(node)
+        } else {
+            None
+        };
+        self.trace_record(TraceRecord {
+            parent,
+            depth: Some(node.depth()),
+            score: Some(node.score()),
+            placement,
+            ..self.record(event, node.key())
+        });
+    }
+
+    fn trace_record(&mut self, record: TraceRecord) {
+        if let Some(tracer) = &mut self.trace {
+            tracer.sink.write(&record);
+        }
+    }
+
+    fn goal_reached(&self) -> bool {
+        match &self.alternatives {
+            Some(set) => set.len() >= set.capacity(),
+            None => self.goal.is_some(),
+        }
+    }
+
+    fn best_score(&self) -> Option<f32> {
+        self.best().map(|b| b.score())
+    }
+
+    fn record_goal(&mut self, node: T) {
+        if let Some(set) = &mut self.alternatives {
+            set.offer(&node);
+        }
+        if self.goal.as_ref().is_none_or(|g| node.score() > g.score()) {
+            let previous = self.best_score();
+            self.goal = Some(node);
+            self.report_progress(previous);
+        }
+    }
+
+    fn record_best(&mut self, node: T) {
+        if self.best.as_ref().is_none_or(|b| node.score() > b.score()) {
+            let previous = self.best_score();
+            self.best = Some(node);
+            self.report_progress(previous);
+        }
+    }
+
+    fn report_progress(&mut self, previous: Option<f32>) {
+        let Some(progress) = &mut self.progress else {
+            return;
+        };
+        let improved = best_of(&self.best, &self.goal)
+            .filter(|best| previous.is_none_or(|p| best.score() > p));
+        if let Some(best) = improved {
+            progress(best);
+        }
+    }
+}
+
+impl<T, F> SearchEngine<T, F>
+where
+    T: SearchNode + Send + Sync,
+    F: Frontier<T>,
+{
+    /// 弹出一批节点并在线程池中并行扩展；Frontier 为空时返回 false
+    ///
+    /// 扩展期间各线程只读共享 visited 集合，写入只发生在弹出时，
+    /// 因此合并顺序固定，结果与线程调度无关。
+    pub fn step_batch(&mut self, parallel: &ParallelConfig) -> bool {
+        let mut batch = Vec::new();
+        let mut progressed = false;
+        while batch.len() < parallel.batch_size.max(1) {
+            match self.pop_next() {
+                Popped::Empty => break,
+                Popped::Handled => progressed = true,
+                Popped::Expand(node) => batch.push(node),
+            }
+        }
+        if batch.is_empty() {
+            return progressed;
+        }
+
+        let visited = &self.visited;
+        let workers = parallel.workers.clamp(1, batch.len());
+        let expansions: Vec<Expansion<T>> = if workers == 1 {
+            batch.iter().map(|node| expand_unvisited(node, visited)).collect()
+        } else {
+            let chunk = batch.len().div_ceil(workers);
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .chunks(chunk)
+                    .map(|nodes| {
+                        scope.spawn(move || {
+                            nodes
+                                .iter()
+                                .map(|node| expand_unvisited(node, visited))
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .flat_map(|h| h.join().expect("expansion worker panicked"))
+                    .collect()
+            })
+        };
+
+        for (node, expansion) in batch.into_iter().zip(expansions) {
+            self.merge(node, expansion);
+        }
+        true
+    }
+
+    /// 并行版本的 `run`；步数按节点计，最后一批可能略超 `max_steps`
+    pub fn run_parallel(&mut self, budget: &SearchBudget, parallel: &ParallelConfig) -> SearchStats {
+        self.run_with(budget, |engine| engine.step_batch(parallel))
+    }
+}
+
+impl<T, F> SearchEngine<T, F>
+where
+    T: Diverse + Clone,
+    F: Frontier<T>,
+{
+    /// 收集最多 `count` 个目标节点，两两差异（`Diverse::distance`）不小于 `min_distance`
+    ///
+    /// 已记录的目标节点会先加入；检查点不保存这些结果，恢复后需重新开启。
+    pub fn with_alternatives(mut self, count: usize, min_distance: f32) -> Self {
+        let mut set = DiverseSet::new(count, min_distance);
+        if let Some(goal) = &self.goal {
+            set.offer(goal);
+        }
+        self.alternatives = Some(set);
+        self
+    }
+}
+
+impl<T, F> SearchEngine<T, F>
+where
+    T: Traceable,
+    F: Frontier<T>,
+{
+    /// 开启搜索轨迹；当前 Frontier 中的节点以无父节点的 `push` 记录写入
+    pub fn with_trace(mut self, trace: SearchTrace) -> Self {
+        self.trace = Some(Tracer {
+            sink: trace,
+            describe: describe_step::<T>,
+        });
+        for node in self.frontier.nodes() {
+            let record = TraceRecord {
+                depth: Some(node.depth()),
+                score: Some(node.score()),
+                placement: describe_step(node),
+                ..self.record(TraceEvent::Push, node.key())
+            };
+            if let Some(tracer) = &mut self.trace {
+                tracer.sink.write(&record);
+            }
+        }
+        self
+    }
+}
+
+impl<T, F> SearchEngine<T, F>
+where
+    T: Snapshot,
+    F: Frontier<T>,
+{
+    /// 当前状态的检查点（不修改引擎）
+    pub fn checkpoint(&self) -> SearchCheckpoint<T::Data> {
+        let mut visited: Vec<u64> = self.visited.iter().copied().collect();
+        visited.sort_unstable();
+        SearchCheckpoint {
+            version: CHECKPOINT_VERSION,
+            step_count: self.step_count,
+            stats: self.stats.clone(),
+            visited,
+            frontier: self
+                .frontier
+                .ranked_nodes()
+                .into_iter()
+                .map(|(node, tie)| FrontierEntry {
+                    node: node.snapshot(),
+                    tie,
+                })
+                .collect(),
+            frontier_rng: self.frontier.rng_state(),
+            frontier_popped: self.frontier.popped_per_depth(),
+            best: self.best.as_ref().map(T::snapshot),
+            goal: self.goal.as_ref().map(T::snapshot),
+        }
+    }
+
+    pub fn save_checkpoint(&self, path: &std::path::Path) -> Result<(), String> {
+        self.checkpoint().save(path)
+    }
+
+    /// 从检查点恢复；`frontier` 应与写检查点时使用的类型和参数一致
+    pub fn resume(
+        mut frontier: F,
+        checkpoint: SearchCheckpoint<T::Data>,
+        context: &T::Context,
+    ) -> Result<Self, String> {
+        frontier.restore_popped_per_depth(&checkpoint.frontier_popped);
+        for entry in checkpoint.frontier {
+            frontier.push_ranked(T::restore(entry.node, context)?, entry.tie);
+        }
+        if let Some(state) = checkpoint.frontier_rng {
+            frontier.restore_rng_state(state);
+        }
+        let best = checkpoint.best.map(|d| T::restore(d, context)).transpose()?;
+        let goal = checkpoint.goal.map(|d| T::restore(d, context)).transpose()?;
+        let pruned_base = checkpoint.stats.pruned.saturating_sub(frontier.pruned());
+
+        Ok(Self {
+            frontier,
+            visited: checkpoint.visited.into_iter().collect(),
+            step_count: checkpoint.step_count,
+            stats: checkpoint.stats,
+            best,
+            goal,
+            alternatives: None,
+            pruned_base,
+            trace: None,
+            progress: None,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T, F> SearchEngine<T, F>
+where
+    T: Snapshot + Send + Sync,
+    F: Frontier<T>,
+{
+    /// 分段运行并定期写检查点，结束时再写一次
+    ///
+    /// `parallel` 为 `None` 时串行扩展。预算按整个调用计算。
+    pub fn run_checkpointed(
+        &mut self,
+        budget: &SearchBudget,
+        parallel: Option<&ParallelConfig>,
+        checkpoint: &CheckpointConfig,
+    ) -> Result<SearchStats, String> {
+        let start = Instant::now();
+        let first_step = self.step_count;
+
+        loop {
+            let done = self.step_count - first_step;
+            let mut chunk = budget.clone();
+            let remaining = budget.max_steps.map(|max| max.saturating_sub(done));
+            chunk.max_steps = match (remaining, checkpoint.every_steps) {
+                (remaining, 0) => remaining,
+                (Some(remaining), every) => Some(remaining.min(every)),
+                (None, every) => Some(every),
+            };
+            chunk.max_duration = budget.max_duration.map(|max| max.saturating_sub(start.elapsed()));
+
+            let stats = match parallel {
+                Some(parallel) => self.run_parallel(&chunk, parallel),
+                None => self.run(&chunk),
+            };
+            self.save_checkpoint(&checkpoint.path)?;
+
+            let overall_limit = budget
+                .max_steps
+                .is_some_and(|max| self.step_count - first_step >= max);
+            if stats.stop_reason != StopReason::StepLimit || overall_limit {
+                return Ok(stats);
+            }
+        }
+    }
+}
+
+enum Popped<T> {
+    Empty,
+    Handled,
+    Expand(T),
+}
+
+/// 单个节点的扩展结果：未访问的子节点，以及被跳过的重复子节点
+struct Expansion<T> {
+    children: Vec<T>,
+    /// 被跳过的子节点 (key, score)
+    duplicates: Vec<(u64, f32)>,
+}
+
+fn best_of<'a, T: SearchNode>(best: &'a Option<T>, goal: &'a Option<T>) -> Option<&'a T> {
+    match (best, goal) {
+        (Some(b), Some(g)) if b.score() > g.score() => Some(b),
+        (_, Some(g)) => Some(g),
+        (b, None) => b.as_ref(),
+    }
+}
+
+fn expand_unvisited<T: SearchNode>(node: &T, visited: &HashSet<u64>) -> Expansion<T> {
+    let mut children = Vec::new();
+    let mut duplicates = Vec::new();
+    for child in node.expand() {
+        let key = child.key();
+        if visited.contains(&key) {
+            duplicates.push((key, child.score()));
+        } else {
+            children.push(child);
+        }
+    }
+    Expansion { children, duplicates }
 }
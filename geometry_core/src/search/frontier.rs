@@ -1,4 +1,6 @@
-use std::collections::BinaryHeap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap};
+use crate::rng::SeededRng;
 use crate::search::node::{ScoredNode, SearchNode};
 
 pub trait Frontier<T: SearchNode> {
@@ -6,19 +8,90 @@ pub trait Frontier<T: SearchNode> {
     fn pop(&mut self) -> Option<T>;
     fn len(&self) -> usize;
 
+    /// 当前所有节点（顺序不限），用于写检查点
+    fn nodes(&self) -> Vec<&T>;
+
+    /// 当前所有节点及其平分 tie 值，用于写检查点
+    fn ranked_nodes(&self) -> Vec<(&T, u64)> {
+        self.nodes().into_iter().map(|n| (n, 0)).collect()
+    }
+
+    /// 按保存的 tie 值放回节点，不消耗随机数
+    fn push_ranked(&mut self, node: T, _tie: u64) {
+        self.push(node);
+    }
+
+    /// tie 随机数的内部状态，无随机数时为 None
+    fn rng_state(&self) -> Option<u64> {
+        None
+    }
+
+    fn restore_rng_state(&mut self, _state: u64) {}
+
+    /// 各深度已出队的节点数（深度升序），用于写检查点；不按深度限额时为空
+    fn popped_per_depth(&self) -> Vec<(usize, usize)> {
+        Vec::new()
+    }
+
+    /// 恢复各深度已出队的节点数，须在放回节点之前调用
+    fn restore_popped_per_depth(&mut self, _counts: &[(usize, usize)]) {}
+
+    /// 累计被丢弃（未进入 Frontier 或被挤出）的节点数
+    fn pruned(&self) -> usize {
+        0
+    }
+
+    /// 取出上次调用以来被丢弃节点的 key，用于搜索轨迹
+    fn take_pruned(&mut self) -> Vec<u64> {
+        Vec::new()
+    }
+
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
 }
 
+/// 分数相同的节点按随机 tie 值排序，tie 值由种子决定
+struct Ranked<T: SearchNode> {
+    node: ScoredNode<T>,
+    tie: u64,
+}
+
+impl<T: SearchNode> PartialEq for Ranked<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: SearchNode> Eq for Ranked<T> {}
+
+impl<T: SearchNode> PartialOrd for Ranked<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: SearchNode> Ord for Ranked<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.node.cmp(&other.node).then(self.tie.cmp(&other.tie))
+    }
+}
+
 pub struct HeapFrontier<T: SearchNode> {
-    heap: BinaryHeap<ScoredNode<T>>,
+    heap: BinaryHeap<Ranked<T>>,
+    rng: SeededRng,
 }
 
 impl<T: SearchNode> HeapFrontier<T> {
     pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// 指定平分节点的出队顺序种子
+    pub fn with_seed(seed: u64) -> Self {
         Self {
             heap: BinaryHeap::new(),
+            rng: SeededRng::new(seed),
         }
     }
 }
@@ -32,14 +105,170 @@ impl<T: SearchNode> Default for HeapFrontier<T> {
 
 impl<T: SearchNode> Frontier<T> for HeapFrontier<T> {
     fn push(&mut self, node: T) {
-        self.heap.push(ScoredNode(node));
+        let tie = self.rng.next_u64();
+        self.push_ranked(node, tie);
+    }
+
+    fn push_ranked(&mut self, node: T, tie: u64) {
+        self.heap.push(Ranked {
+            node: ScoredNode(node),
+            tie,
+        });
     }
 
     fn pop(&mut self) -> Option<T> {
-        self.heap.pop().map(|n| n.0)
+        self.heap.pop().map(|n| n.node.0)
     }
 
     fn len(&self) -> usize {
         self.heap.len()
     }
+
+    fn nodes(&self) -> Vec<&T> {
+        self.heap.iter().map(|n| &n.node.0).collect()
+    }
+
+    fn ranked_nodes(&self) -> Vec<(&T, u64)> {
+        self.heap.iter().map(|n| (&n.node.0, n.tie)).collect()
+    }
+
+    fn rng_state(&self) -> Option<u64> {
+        Some(self.rng.state())
+    }
+
+    fn restore_rng_state(&mut self, state: u64) {
+        self.rng = SeededRng::new(state);
+    }
+}
+
+/// 束搜索 Frontier：每个深度最多出队（展开）`width` 个节点，其余丢弃
+///
+/// 同一深度已出队与排队中的节点合计不超过 `width`：出队不会腾出名额，
+/// 整次运行中每层展开的节点数有上限，运行时间可预估。
+pub struct BeamFrontier<T: SearchNode> {
+    width: usize,
+    // 每层按分数升序排列，末尾为最优
+    levels: BTreeMap<usize, Vec<Ranked<T>>>,
+    // 每层已出队的节点数
+    popped: BTreeMap<usize, usize>,
+    len: usize,
+    pruned: usize,
+    // 尚未被取走的丢弃节点 key
+    recent_pruned: Vec<u64>,
+    rng: SeededRng,
+}
+
+impl<T: SearchNode> BeamFrontier<T> {
+    pub fn new(width: usize) -> Self {
+        Self::with_seed(width, 0)
+    }
+
+    /// 指定平分节点取舍顺序的种子
+    pub fn with_seed(width: usize, seed: u64) -> Self {
+        Self {
+            width: width.max(1),
+            levels: BTreeMap::new(),
+            popped: BTreeMap::new(),
+            len: 0,
+            pruned: 0,
+            recent_pruned: Vec::new(),
+            rng: SeededRng::new(seed),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+}
+
+impl<T: SearchNode> Frontier<T> for BeamFrontier<T> {
+    fn push(&mut self, node: T) {
+        let tie = self.rng.next_u64();
+        self.push_ranked(node, tie);
+    }
+
+    fn push_ranked(&mut self, node: T, tie: u64) {
+        let depth = node.depth();
+        let capacity = self.width.saturating_sub(self.popped.get(&depth).copied().unwrap_or(0));
+        if capacity == 0 {
+            // 这一层的名额已经用完
+            self.pruned += 1;
+            self.recent_pruned.push(node.key());
+            return;
+        }
+        let level = self.levels.entry(depth).or_default();
+        let node = Ranked {
+            node: ScoredNode(node),
+            tie,
+        };
+        if level.len() >= capacity {
+            // 已满：不比最差节点好则直接丢弃，否则挤掉最差节点
+            if level.first().is_some_and(|worst| node <= *worst) {
+                self.pruned += 1;
+                self.recent_pruned.push(node.node.0.key());
+                return;
+            }
+            let worst = level.remove(0);
+            self.pruned += 1;
+            self.recent_pruned.push(worst.node.0.key());
+            self.len -= 1;
+        }
+        let idx = level.partition_point(|n| *n <= node);
+        level.insert(idx, node);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        // 各层中分数最高的节点；分数相同时取更深的一层
+        let depth = self
+            .levels
+            .iter()
+            .filter_map(|(depth, level)| level.last().map(|n| (*depth, n)))
+            .max_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(&b.0)))
+            .map(|(depth, _)| depth)?;
+        let level = self.levels.get_mut(&depth)?;
+        let node = level.pop()?;
+        if level.is_empty() {
+            self.levels.remove(&depth);
+        }
+        *self.popped.entry(depth).or_default() += 1;
+        self.len -= 1;
+        Some(node.node.0)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn nodes(&self) -> Vec<&T> {
+        self.levels.values().flatten().map(|n| &n.node.0).collect()
+    }
+
+    fn ranked_nodes(&self) -> Vec<(&T, u64)> {
+        self.levels.values().flatten().map(|n| (&n.node.0, n.tie)).collect()
+    }
+
+    fn rng_state(&self) -> Option<u64> {
+        Some(self.rng.state())
+    }
+
+    fn restore_rng_state(&mut self, state: u64) {
+        self.rng = SeededRng::new(state);
+    }
+
+    fn popped_per_depth(&self) -> Vec<(usize, usize)> {
+        self.popped.iter().map(|(depth, count)| (*depth, *count)).collect()
+    }
+
+    fn restore_popped_per_depth(&mut self, counts: &[(usize, usize)]) {
+        self.popped = counts.iter().copied().collect();
+    }
+
+    fn pruned(&self) -> usize {
+        self.pruned
+    }
+
+    fn take_pruned(&mut self) -> Vec<u64> {
+        std::mem::take(&mut self.recent_pruned)
+    }
 }
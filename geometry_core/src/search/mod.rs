@@ -1,7 +1,15 @@
+pub mod anneal;
+pub mod checkpoint;
+pub mod diversity;
 pub mod engine;
 pub mod frontier;
 pub mod node;
+pub mod trace;
 
-pub use engine::SearchEngine;
-pub use frontier::{HeapFrontier, Frontier};
+pub use anneal::{anneal, AnnealSchedule, AnnealStats, Neighborhood};
+pub use checkpoint::{CheckpointConfig, FrontierEntry, SearchCheckpoint, Snapshot};
+pub use diversity::{Diverse, DiverseSet};
+pub use engine::{ParallelConfig, SearchBudget, SearchEngine, SearchStats, StopReason};
+pub use frontier::{BeamFrontier, HeapFrontier, Frontier};
 pub use node::SearchNode;
+pub use trace::{SearchTrace, TraceEvent, TraceRecord, Traceable};
@@ -11,6 +11,16 @@ pub trait SearchNode: Sized {
 
     /// 可选：唯一标识（用于 visited 去重）
     fn key(&self) -> u64;
+
+    /// 节点深度（BeamFrontier 按深度分层）
+    fn depth(&self) -> usize {
+        0
+    }
+
+    /// 是否为目标节点（目标节点不再扩展）
+    fn is_goal(&self) -> bool {
+        false
+    }
 }
 
 /// 包装节点，让 Frontier 的 BinaryHeap 可以排序
@@ -33,6 +43,6 @@ impl<T: SearchNode> PartialOrd for ScoredNode<T> {
 
 impl<T: SearchNode> Ord for ScoredNode<T> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.0.score().total_cmp(&other.0.score())
     }
 }
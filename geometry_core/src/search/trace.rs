@@ -0,0 +1,123 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::search::node::SearchNode;
+
+/// 可写入搜索轨迹的节点：描述它相对父节点新增的一步
+pub trait Traceable: SearchNode {
+    type Step: Serialize;
+
+    fn last_step(&self) -> Option<Self::Step>;
+}
+
+/// 轨迹事件类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceEvent {
+    /// 子节点进入 Frontier（含根节点）
+    Push,
+    /// 节点被弹出并扩展
+    Expand,
+    /// 弹出的是目标节点
+    Goal,
+    /// key 已访问，节点被跳过
+    Duplicate,
+    /// 被 Frontier 丢弃
+    Pruned,
+}
+
+/// 一行轨迹；`id`/`parent` 为节点 key，相同 key 即同一布局
+#[derive(Clone, Debug, Serialize)]
+pub struct TraceRecord {
+    pub step: usize,
+    pub event: TraceEvent,
+    pub id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    /// 本节点新增的一步，只在 `push` 事件中给出
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placement: Option<Value>,
+}
+
+/// JSON Lines 轨迹文件；写满 `max_bytes` 后不再写入
+pub struct SearchTrace {
+    writer: BufWriter<File>,
+    max_bytes: u64,
+    bytes: u64,
+    records: usize,
+    truncated: bool,
+}
+
+impl SearchTrace {
+    pub fn create(path: &Path, max_bytes: u64) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+        }
+        let file = File::create(path)
+            .map_err(|err| format!("Failed to create {}: {err}", path.display()))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            max_bytes,
+            bytes: 0,
+            records: 0,
+            truncated: false,
+        })
+    }
+
+    /// 写入一行；超出上限或写入失败后返回 false，之后的记录全部丢弃
+    pub fn write(&mut self, record: &TraceRecord) -> bool {
+        if self.truncated {
+            return false;
+        }
+        let Ok(mut line) = serde_json::to_string(record) else {
+            return false;
+        };
+        line.push('\n');
+        if self.bytes + line.len() as u64 > self.max_bytes
+            || self.writer.write_all(line.as_bytes()).is_err()
+        {
+            self.truncated = true;
+            return false;
+        }
+        self.bytes += line.len() as u64;
+        self.records += 1;
+        true
+    }
+
+    pub fn flush(&mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|err| format!("Failed to flush search trace: {err}"))
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes
+    }
+
+    pub fn records_written(&self) -> usize {
+        self.records
+    }
+
+    /// 是否因达到大小上限而截断
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+/// 引擎内部使用：轨迹文件 + 节点描述函数（在 `with_trace` 时按 `T: Traceable` 取得）
+pub(crate) struct Tracer<T> {
+    pub(crate) sink: SearchTrace,
+    pub(crate) describe: fn(&T) -> Option<Value>,
+}
+
+pub(crate) fn describe_step<T: Traceable>(node: &T) -> Option<Value> {
+    node.last_step().and_then(|step| serde_json::to_value(step).ok())
+}
@@ -0,0 +1,1686 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use geometry_core::layout::{
+    check_pair, layout_key, region_contact, CandidateConfig, CirculationConfig, Constraint, ConstraintSet, Demand, KeepOutConfig,
+    GroupMember, ItemGroup, KeepOutKind, Objective, ObjectiveWeights, PinnedPlacement, Relation, RoomShell, Strength, Footprint, GeometryCache, KeyConfig, LayoutContext, LayoutState,
+    Placement, Pose2D, SurfaceFrame, SurfaceIndex, SurfaceKind, SurfacePose,
+};
+use geometry_core::geometry_ops::{
+    flatten_boundary_polygon, flatten_outer_boundary, segment_space, SegmentConfig,
+};
+use geometry_core::layout::collision::CONTACT_TOLERANCE_MM;
+use geometry_core::layout::geometry::{polygon_inside, polygons_overlap};
+use geometry_core::models::mesh::Mesh;
+use geometry_core::models::placement_region::{
+    HeightRange, PlacementRegion, PlacementSemantics, Region, Regions, SdfGrid, Visual,
+};
+use geometry_core::models::space::{Space, SurfaceMeta};
+use geometry_core::search::{
+    anneal, AnnealSchedule, BeamFrontier, CheckpointConfig, HeapFrontier, ParallelConfig,
+    SearchBudget, SearchCheckpoint, SearchEngine, SearchNode, SearchTrace, StopReason,
+};
+use types::RegionsTypeMask;
+use vdb_core::VdbGrid;
+
+fn floor(size: f32) -> Mesh {
+    Mesh {
+        positions: vec![[0.0, 0.0, 0.0], [size, 0.0, 0.0], [size, 0.0, size], [0.0, 0.0, size]],
+        indices: vec![0, 1, 2, 0, 2, 3],
+    }
+}
+
+fn box_mesh(sx: f32, sy: f32, sz: f32) -> Mesh {
+    let (hx, hz) = (sx * 0.5, sz * 0.5);
+    Mesh {
+        positions: vec![
+            [-hx, 0.0, -hz],
+            [hx, 0.0, -hz],
+            [hx, 0.0, hz],
+            [-hx, 0.0, hz],
+            [-hx, sy, -hz],
+            [hx, sy, -hz],
+            [hx, sy, hz],
+            [-hx, sy, hz],
+        ],
+        indices: vec![
+            0, 2, 1, 0, 3, 2, 4, 5, 6, 4, 6, 7, 0, 1, 5, 0, 5, 4, 1, 2, 6, 1, 6, 5, 2, 3, 7, 2, 7,
+            6, 3, 0, 4, 3, 4, 7,
+        ],
+    }
+}
+
+fn item(regions_type: u32, size: f32) -> PlacementRegion {
+    PlacementRegion {
+        regions: Regions {
+            forbidden_region: Region {
+                mesh: box_mesh(size, size, size),
+                sdf: None,
+            },
+            restricted_region: Region {
+                mesh: box_mesh(size + 200.0, size, size + 200.0),
+                sdf: None,
+            },
+        },
+        semantics: PlacementSemantics {
+            regions_type,
+            count: 1,
+            min_count: None,
+            reward: 0.0,
+        },
+        visual: Visual {
+            footprint_2d: Mesh::default(),
+            height_range: HeightRange::default(),
+        },
+    }
+}
+
+fn context(items: Vec<PlacementRegion>) -> Arc<LayoutContext> {
+    let candidates = CandidateConfig {
+        sample_step_mm: 250.0,
+        ..CandidateConfig::default()
+    };
+    context_with(items, candidates)
+}
+
+fn context_with(items: Vec<PlacementRegion>, candidates: CandidateConfig) -> Arc<LayoutContext> {
+    Arc::new(build_context(items, candidates))
+}
+
+fn build_context(items: Vec<PlacementRegion>, candidates: CandidateConfig) -> LayoutContext {
+    let mut mask = RegionsTypeMask::NONE;
+    mask.insert_id(7);
+    let space = Space {
+        meshes: vec![floor(2000.0)],
+        surface_metas: vec![SurfaceMeta {
+            regions_type_mask: mask,
+        }],
+    };
+    LayoutContext::new(space, items, candidates)
+}
+
+#[test]
+fn expand_places_next_item_without_overlap() {
+    let ctx = context(vec![item(7, 800.0), item(7, 800.0)]);
+    let root = LayoutState::new(ctx, HashMap::from([(0, 1), (1, 1)]));
+
+    let children = root.expand();
+    assert!(!children.is_empty(), "expected candidates on the floor");
+    assert!(children.len() <= CandidateConfig::default().max_children);
+
+    let child = children.into_sorted_vec().pop().unwrap();
+    assert_eq!(child.placed().len(), 1);
+    assert!(child.score() > root.score());
+
+    for grandchild in child.expand() {
+        let a = &grandchild.placed()[0].pose;
+        let b = &grandchild.placed()[1].pose;
+        let gap = (a.x - b.x).abs().max((a.y - b.y).abs());
+        assert!(gap > 800.0, "footprints overlap: {a:?} {b:?}");
+        assert!(grandchild.is_complete());
+    }
+}
+
+#[test]
+fn expand_skips_incompatible_surfaces() {
+    let ctx = context(vec![item(3, 500.0)]);
+    let root = LayoutState::new(ctx, HashMap::from([(0, 1)]));
+    assert!(root.expand().is_empty());
+}
+
+#[test]
+fn geometry_cache_children_share_parent() {
+    let footprint = Footprint::from_points_xz(&box_mesh(1000.0, 0.0, 1000.0).positions[..4]);
+    let mut parent = GeometryCache::new();
+    let shape = footprint.posed_shape(&Pose2D::new(0.0, 0.0, 0.0));
+    parent.insert(0, shape.clone(), shape);
+
+    let mut child = parent.clone();
+    let far = footprint.posed_shape(&Pose2D::new(3000.0, 0.0, 0.0));
+    child.insert(1, far.clone(), far);
+
+    assert_eq!(parent.len(), 1);
+    assert_eq!(child.len(), 2);
+
+    let probe = footprint.posed_shape(&Pose2D::new(3200.0, 0.0, 0.3));
+    assert!(child.overlaps_forbidden(&probe));
+    assert!(!parent.overlaps_forbidden(&probe));
+    assert!(!child.overlaps_forbidden(&footprint.posed_shape(&Pose2D::new(1500.0, 0.0, 0.0))));
+}
+
+#[test]
+fn occupancy_grid_matches_linear_scan() {
+    let mut rng = geometry_core::rng::SeededRng::new(5);
+    let shape = |size: f32, rng: &mut geometry_core::rng::SeededRng| {
+        let footprint = Footprint::from_points_xz(&box_mesh(size, 0.0, size).positions[..4]);
+        let pose = Pose2D::new(rng.range_f32(-500.0, 6000.0), rng.range_f32(-500.0, 6000.0), rng.range_f32(0.0, 3.0));
+        footprint.posed_shape(&pose)
+    };
+    let mut cache = GeometryCache::new();
+    let mut snapshots = Vec::new();
+    for i in 0..40 {
+        // 大小不一，有的跨好几个格子
+        let placed = shape(if i % 5 == 0 { 2500.0 } else { 400.0 }, &mut rng);
+        cache.insert(i, placed.clone(), placed);
+        snapshots.push(cache.clone());
+    }
+
+    let layer = cache.forbidden();
+    for _ in 0..200 {
+        let probe = shape(600.0, &mut rng);
+        let scanned: Vec<u32> = layer.iter().filter(|e| e.shape.overlaps(&probe)).map(|e| e.item_id).collect();
+        let mut queried: Vec<u32> = layer
+            .query(&probe.bounds)
+            .filter(|e| e.shape.overlaps(&probe))
+            .map(|e| e.item_id)
+            .collect();
+        queried.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(queried, scanned);
+        assert_eq!(cache.overlaps_forbidden(&probe), !scanned.is_empty());
+        // 只返回附近的条目
+        assert!(layer.query(&probe.bounds).count() < layer.len());
+    }
+
+    // 父状态的索引不受子状态插入的影响
+    let first = &snapshots[0];
+    assert_eq!(first.len(), 1);
+    let only = first.forbidden().iter().next().unwrap().shape.clone();
+    assert_eq!(first.forbidden().query(&only.bounds).count(), 1);
+}
+
+#[test]
+fn objective_breakdown_matches_score() {
+    let ctx = context(vec![item(7, 600.0)]);
+    let root = LayoutState::new(ctx, HashMap::from([(0, 1)]));
+    let best = root.expand().into_sorted_vec().pop().unwrap();
+
+    let breakdown = best.breakdown();
+    assert!((breakdown.total - best.score()).abs() < 1e-5);
+    let count = breakdown.terms.iter().find(|t| t.name == "count").unwrap();
+    assert_eq!(count.value, 1.0);
+    assert!(breakdown.terms.iter().all(|t| (0.0..=1.0).contains(&t.value)));
+}
+
+#[test]
+fn layout_key_ignores_placement_order_and_jitter() {
+    let candidates = CandidateConfig {
+        sample_step_mm: 500.0,
+        max_children: usize::MAX,
+        ..CandidateConfig::default()
+    };
+    let ctx = context_with(vec![item(7, 500.0)], candidates);
+    let root = LayoutState::new(ctx, HashMap::from([(0, 2)]));
+    let children = root.expand().into_vec();
+    let a = &children[0];
+    let far = |c: &&LayoutState| {
+        let (p, q) = (&a.placed()[0].pose, &c.placed()[0].pose);
+        (p.x - q.x).abs().max((p.y - q.y).abs()) > 600.0
+    };
+    let b = children.iter().find(far).unwrap();
+    let same_pose = |x: &Pose2D, y: &Pose2D| x.x == y.x && x.y == y.y && x.theta == y.theta;
+    let with_second = |parent: &LayoutState, pose: &Pose2D| {
+        parent
+            .expand()
+            .into_iter()
+            .find(|g| same_pose(&g.placed()[1].pose, pose))
+    };
+
+    let ab = with_second(a, &b.placed()[0].pose).expect("a then b");
+    let ba = with_second(b, &a.placed()[0].pose).expect("b then a");
+    assert_eq!(ab.key(), ba.key());
+
+    let config = KeyConfig::default();
+    let p = Placement::new(0, Pose2D::new(100.0, 200.0, 0.0));
+    let q = Placement::new(0, Pose2D::new(100.001, 199.999, std::f32::consts::TAU - 1e-4));
+    assert_eq!(config.quantize(&p), config.quantize(&q));
+    assert_eq!(
+        layout_key(&[p.clone(), q.clone()], &HashMap::new(), &config),
+        layout_key(&[q, p], &HashMap::new(), &config)
+    );
+}
+
+#[test]
+fn seeded_expansion_is_reproducible() {
+    let poses = |seed: u64| {
+        let candidates = CandidateConfig {
+            sample_step_mm: 250.0,
+            max_children: 16,
+            jitter_mm: 100.0,
+            ..CandidateConfig::default()
+        };
+        let items = vec![item(7, 500.0)];
+        let ctx = Arc::new(build_context(items, candidates).with_seed(seed));
+        let root = LayoutState::new(ctx, HashMap::from([(0, 1)]));
+        let mut out: Vec<_> = root
+            .expand()
+            .into_iter()
+            .map(|c| {
+                let p = &c.placed()[0].pose;
+                (p.x.to_bits(), p.y.to_bits(), p.theta.to_bits())
+            })
+            .collect();
+        out.sort();
+        out
+    };
+    assert_eq!(poses(7), poses(7));
+    assert_ne!(poses(7), poses(8));
+}
+
+#[test]
+fn anneal_refines_complete_layout_without_overlap() {
+    let ctx = context(vec![item(7, 800.0), item(7, 600.0)]);
+    let empty = LayoutState::new(ctx, HashMap::new());
+    let at = |item_id: u32, x: f32, y: f32, theta: f32| Placement::new(item_id, Pose2D::new(x, y, theta));
+
+    let overlapping = vec![at(0, 1000.0, 1000.0, 0.0), at(1, 1200.0, 1000.0, 0.0)];
+    assert!(empty.with_placements(overlapping).is_none());
+
+    // 远离墙面、且未对齐：得分偏低
+    let start = empty
+        .with_placements(vec![at(0, 600.0, 600.0, 0.4), at(1, 1600.0, 1600.0, 0.0)])
+        .expect("start layout is valid");
+    assert!(start.is_complete());
+
+    let schedule = AnnealSchedule {
+        max_iterations: 500,
+        ..AnnealSchedule::default()
+    };
+    let (refined, stats) = anneal(start.clone(), &schedule, 3);
+    assert_eq!(stats.initial_score, start.score());
+    assert!(refined.score() > start.score());
+    assert_eq!(stats.final_score, refined.score());
+    assert!(stats.accepted > 0 && stats.improved > 0);
+    assert_eq!(refined.placed().len(), 2);
+
+    // 重建一次即重新做全部碰撞/越界检查
+    let rebuilt = empty.with_placements(refined.placed().to_vec());
+    assert!(rebuilt.is_some_and(|s| s.score() == refined.score()));
+
+    let (again, _) = anneal(start, &schedule, 3);
+    assert_eq!(again.key(), refined.key());
+}
+
+#[test]
+fn parallel_search_is_independent_of_worker_count() {
+    let candidates = CandidateConfig {
+        sample_step_mm: 250.0,
+        jitter_mm: 40.0,
+        max_children: 16,
+        ..CandidateConfig::default()
+    };
+    let ctx = Arc::new(build_context(vec![item(7, 600.0), item(7, 400.0)], candidates).with_seed(9));
+    let budget = SearchBudget {
+        stop_on_goal: false,
+        ..SearchBudget::default()
+    }
+    .with_max_steps(18);
+
+    let run = |workers: usize| {
+        let root = LayoutState::new(Arc::clone(&ctx), HashMap::from([(0, 1), (1, 2)]));
+        let mut engine = SearchEngine::with_root(HeapFrontier::with_seed(9), root);
+        let parallel = ParallelConfig {
+            workers,
+            batch_size: 6,
+        };
+        let stats = engine.run_parallel(&budget, &parallel);
+        let best = engine.into_result().expect("a layout");
+        (best.key(), best.score(), stats.expanded, stats.duplicates)
+    };
+
+    let single = run(1);
+    assert_eq!(run(3), single);
+    assert_eq!(run(8), single);
+}
+
+#[test]
+fn checkpoint_round_trips_and_resumes() {
+    let ctx = context(vec![item(7, 800.0), item(7, 500.0)]);
+    let root = LayoutState::new(Arc::clone(&ctx), HashMap::from([(0, 1), (1, 2)]));
+    let path = std::env::temp_dir().join("spaceforge_checkpoint_test.json");
+    let checkpoint = CheckpointConfig {
+        path: path.clone(),
+        every_steps: 2,
+    };
+
+    let mut engine = SearchEngine::with_root(HeapFrontier::new(), root);
+    let budget = SearchBudget::default().with_max_steps(5);
+    let stats = engine.run_checkpointed(&budget, None, &checkpoint).unwrap();
+    assert_eq!(stats.stop_reason, StopReason::StepLimit);
+    assert_eq!(engine.steps(), 5);
+
+    let saved = SearchCheckpoint::load(&path).unwrap();
+    assert_eq!(saved.step_count, 5);
+    assert_eq!(saved.frontier.len(), engine.frontier_len());
+    let mut resumed = SearchEngine::resume(HeapFrontier::new(), saved, &ctx).unwrap();
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(resumed.steps(), engine.steps());
+    assert_eq!(resumed.frontier_len(), engine.frontier_len());
+    assert_eq!(resumed.stats().expanded, engine.stats().expanded);
+    let key = |e: &SearchEngine<LayoutState, HeapFrontier<LayoutState>>| e.best().map(|b| b.key());
+    assert_eq!(key(&resumed), key(&engine));
+    let score = resumed.best().map(|b| b.score());
+    assert_eq!(score, engine.best().map(|b| b.score()));
+
+    let stats = resumed.run(&SearchBudget::default());
+    assert_eq!(stats.stop_reason, StopReason::GoalReached);
+    assert!(resumed.steps() > 5);
+}
+
+#[test]
+fn resumed_search_matches_uninterrupted_run() {
+    let ctx = context(vec![item(7, 800.0), item(7, 500.0), item(7, 400.0)]);
+    let inventory = HashMap::from([(0, 1), (1, 2), (2, 1)]);
+    let budget = SearchBudget::default();
+
+    let root = LayoutState::new(Arc::clone(&ctx), inventory.clone());
+    let mut straight = SearchEngine::with_root(BeamFrontier::with_seed(6, 11), root);
+    straight.run(&budget);
+
+    let path = std::env::temp_dir().join("spaceforge_resume_match_test.json");
+    let root = LayoutState::new(Arc::clone(&ctx), inventory);
+    let mut first = SearchEngine::with_root(BeamFrontier::with_seed(6, 11), root);
+    let checkpoint = CheckpointConfig {
+        path: path.clone(),
+        every_steps: 0,
+    };
+    first
+        .run_checkpointed(&budget.clone().with_max_steps(3), None, &checkpoint)
+        .unwrap();
+    let saved = SearchCheckpoint::load(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    // 恢复时的种子不应影响结果
+    let mut resumed: SearchEngine<LayoutState, _> =
+        SearchEngine::resume(BeamFrontier::with_seed(6, 999), saved, &ctx).unwrap();
+    resumed.run(&budget);
+
+    assert_eq!(resumed.steps(), straight.steps());
+    assert_eq!(resumed.stats().pruned, straight.stats().pruned);
+    let straight_goal = straight.into_result().unwrap();
+    let resumed_goal = resumed.into_result().unwrap();
+    assert_eq!(resumed_goal.key(), straight_goal.key());
+    assert_eq!(resumed_goal.transforms(0), straight_goal.transforms(0));
+}
+
+#[test]
+fn trace_records_tree_and_respects_size_cap() {
+    let ctx = context(vec![item(7, 800.0), item(7, 500.0)]);
+    let inventory = HashMap::from([(0, 1), (1, 1)]);
+    let path = std::env::temp_dir().join("spaceforge_trace_test.jsonl");
+
+    let root = LayoutState::new(Arc::clone(&ctx), inventory.clone());
+    let trace = SearchTrace::create(&path, u64::MAX).unwrap();
+    let mut engine = SearchEngine::with_root(BeamFrontier::new(4), root).with_trace(trace);
+    engine.run(&SearchBudget::default());
+    engine.flush_trace().unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let records: Vec<serde_json::Value> =
+        text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(records.len(), engine.trace().unwrap().records_written());
+    let count = |event: &str| records.iter().filter(|r| r["event"] == event).count();
+    assert_eq!(count("expand"), engine.stats().expanded);
+    assert_eq!(count("pruned"), engine.stats().pruned);
+    assert_eq!(count("goal"), 1);
+    assert!(count("pruned") > 0);
+
+    // 根节点没有父节点，其余 push 都指向一个被扩展过的节点
+    let expanded: Vec<&serde_json::Value> =
+        records.iter().filter(|r| r["event"] == "expand").map(|r| &r["id"]).collect();
+    let pushes: Vec<&serde_json::Value> = records.iter().filter(|r| r["event"] == "push").collect();
+    assert!(pushes[0].get("parent").is_none());
+    for push in &pushes[1..] {
+        assert!(expanded.contains(&&push["parent"]));
+        assert!(push["placement"]["pose"]["theta"].is_number());
+    }
+
+    let root = LayoutState::new(ctx, inventory);
+    let trace = SearchTrace::create(&path, 2048).unwrap();
+    let mut engine = SearchEngine::with_root(BeamFrontier::new(4), root).with_trace(trace);
+    engine.run(&SearchBudget::default());
+    engine.flush_trace().unwrap();
+    let trace = engine.trace().unwrap();
+    assert!(trace.is_truncated());
+    assert!(std::fs::metadata(&path).unwrap().len() <= 2048);
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn surface_poses_keep_items_upright_on_every_surface() {
+    let quad = |positions: [[f32; 3]; 4]| Mesh {
+        positions: positions.to_vec(),
+        indices: vec![0, 1, 2, 0, 2, 3],
+    };
+    let (s, h) = (2000.0, 2500.0);
+    let mut mask = RegionsTypeMask::NONE;
+    mask.insert_id(7);
+    let space = Space {
+        meshes: vec![
+            floor(s),
+            quad([[0.0, h, 0.0], [s, h, 0.0], [s, h, s], [0.0, h, s]]),
+            quad([[0.0, 0.0, 0.0], [s, 0.0, 0.0], [s, h, 0.0], [0.0, h, 0.0]]),
+        ],
+        surface_metas: vec![SurfaceMeta { regions_type_mask: mask }; 3],
+    };
+    let ctx = LayoutContext::new(space, vec![item(7, 500.0)], CandidateConfig::default());
+    let kinds: Vec<SurfaceKind> = ctx.surfaces.iter().map(|s| s.kind()).collect();
+    assert_eq!(kinds, [SurfaceKind::Floor, SurfaceKind::Ceiling, SurfaceKind::Wall]);
+    let close = |a: [f32; 4], b: [f32; 4]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-3);
+
+    // 地面：与 Pose2D 的矩阵一致
+    let placement = Placement::new(0, Pose2D::new(400.0, 700.0, 0.5));
+    let expected = placement.pose.to_matrix(0.0);
+    let actual = ctx.placement_matrix(&placement);
+    assert!((0..4).all(|i| close(actual[i], expected[i])), "{actual:?}");
+
+    let ceiling = ctx.surface_frame(1).unwrap();
+    assert_eq!(ceiling.kind, SurfaceKind::Ceiling);
+    assert!(ceiling.normal.y < -0.99);
+    let hanging = ctx.surface_matrix(&SurfacePose::new(1, 0.0, 0.0, 0.0).with_offset(300.0)).unwrap();
+    assert!(close(hanging[1], [0.0, 1.0, 0.0, 0.0]));
+    assert!((hanging[3][1] - (h - 300.0)).abs() < 1e-3);
+
+    // 墙面 z = 0：法向指向房间内部 (+Z)，v 沿墙向上
+    let wall = ctx.surface_frame(2).unwrap();
+    assert_eq!(wall.kind, SurfaceKind::Wall);
+    assert!(wall.normal.z > 0.99 && wall.v_axis.y > 0.99);
+    let art = ctx.surface_matrix(&SurfacePose::new(2, 100.0, 300.0, 0.0).with_offset(20.0)).unwrap();
+    assert!(close(art[1], [0.0, 1.0, 0.0, 0.0]));
+    assert!(close(art[2], [0.0, 0.0, 1.0, 0.0]));
+    let origin = wall.point(100.0, 300.0, 20.0);
+    assert!(close(art[3], [origin.x, origin.y, origin.z, 1.0]));
+    assert!((art[3][2] - 20.0).abs() < 1e-3);
+
+    // 绕法向旋转 90°：局部 +X 转到墙面向上方向
+    let turned = ctx.surface_matrix(&SurfacePose::new(2, 0.0, 0.0, std::f32::consts::FRAC_PI_2)).unwrap();
+    assert!(close(turned[0], [0.0, 1.0, 0.0, 0.0]));
+}
+
+#[test]
+fn wall_items_are_placed_on_walls_and_exported_from_the_wall_frame() {
+    let (s, h) = (2000.0, 2500.0);
+    let mask_of = |id: u32| {
+        let mut mask = RegionsTypeMask::NONE;
+        mask.insert_id(id);
+        SurfaceMeta {
+            regions_type_mask: mask,
+        }
+    };
+    // 地面放 7 类，z = 0 的墙面放 9 类（如电视）
+    let space = Space {
+        meshes: vec![
+            floor(s),
+            Mesh {
+                positions: vec![[0.0, 0.0, 0.0], [s, 0.0, 0.0], [s, h, 0.0], [0.0, h, 0.0]],
+                indices: vec![0, 1, 2, 0, 2, 3],
+            },
+        ],
+        surface_metas: vec![mask_of(7), mask_of(9)],
+    };
+    let candidates = CandidateConfig {
+        sample_step_mm: 250.0,
+        ..CandidateConfig::default()
+    };
+    let ctx = Arc::new(LayoutContext::new(space, vec![item(9, 500.0)], candidates));
+    let usable: Vec<usize> = ctx.surfaces_for(0).map(|s| s.mesh_index).collect();
+    assert_eq!(usable, [1], "only the wall accepts type 9");
+    let root = LayoutState::new(Arc::clone(&ctx), HashMap::from([(0, 1)]));
+
+    let children = root.expand().into_sorted_vec();
+    assert!(!children.is_empty(), "expected candidates on the wall");
+    let close = |a: [f32; 4], b: [f32; 4]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-2);
+    let wall = ctx.surface_frame(1).unwrap();
+    for child in &children {
+        let placed = &child.placed()[0];
+        let pose = placed.surface.as_ref().expect("wall placements keep their surface pose");
+        assert_eq!(pose.surface, 1);
+        assert_eq!(pose.rotation, 0.0);
+        // 禁止区背面贴墙：500mm 的箱子原点离墙 250mm
+        assert!((pose.offset - 250.0).abs() < 1e-3);
+
+        let [(item_id, matrix)] = child.transforms(0)[..] else {
+            panic!("expected one transform");
+        };
+        assert_eq!(item_id, 0);
+        let origin = wall.point(pose.u, pose.v, pose.offset);
+        assert!(close(matrix[0], [1.0, 0.0, 0.0, 0.0]), "{matrix:?}");
+        assert!(close(matrix[1], [0.0, 1.0, 0.0, 0.0]), "{matrix:?}");
+        assert!(close(matrix[2], [0.0, 0.0, 1.0, 0.0]), "{matrix:?}");
+        assert!(close(matrix[3], [origin.x, origin.y, origin.z, 1.0]), "{matrix:?}");
+        assert!((matrix[3][2] - 250.0).abs() < 1e-2);
+        // 整件家具都在墙面范围内
+        assert!(matrix[3][1] >= -1e-2 && matrix[3][1] + 500.0 <= h + 1e-2);
+        assert!(matrix[3][0] >= 250.0 - 1e-2 && matrix[3][0] <= s - 250.0 + 1e-2);
+
+        // 俯视位姿与表面位姿一致，占用区间随高度抬起
+        assert!(placed.pose.theta.abs() < 1e-4);
+        assert!((placed.pose.y - 250.0).abs() < 1e-2);
+        let entry = child.occupancy().forbidden().iter().next().unwrap();
+        assert!((entry.shape.min_y - matrix[3][1]).abs() < 1e-2);
+    }
+
+    // 同一俯视位置、不同高度的墙面摆放是不同的布局
+    let heights: std::collections::HashSet<i64> = children
+        .iter()
+        .map(|c| c.transforms(0)[0].1[3][1].round() as i64)
+        .collect();
+    assert!(heights.len() > 1);
+    let keys: std::collections::HashSet<u64> = children.iter().map(|c| c.key()).collect();
+    assert_eq!(keys.len(), children.len());
+}
+
+#[test]
+fn surface_index_maps_regions_types_to_surfaces() {
+    let mask_of = |ids: &[u32]| {
+        let mut mask = RegionsTypeMask::NONE;
+        for id in ids {
+            mask.insert_id(*id);
+        }
+        SurfaceMeta {
+            regions_type_mask: mask,
+        }
+    };
+    let (s, h) = (2000.0, 2500.0);
+    let space = Space {
+        meshes: vec![
+            floor(s),
+            Mesh {
+                positions: vec![[0.0, 0.0, 0.0], [s, 0.0, 0.0], [s, h, 0.0], [0.0, h, 0.0]],
+                indices: vec![0, 1, 2, 0, 2, 3],
+            },
+            Mesh {
+                positions: vec![[0.0, h, 0.0], [s, h, 0.0], [s, h, s], [0.0, h, s]],
+                indices: vec![0, 1, 2, 0, 2, 3],
+            },
+        ],
+        surface_metas: vec![mask_of(&[6, 7]), mask_of(&[2, 3]), mask_of(&[])],
+    };
+    let items = vec![item(2, 300.0), item(8, 900.0)];
+    let ctx = LayoutContext::new(space, items, CandidateConfig::default());
+    let index: &SurfaceIndex = &ctx.surface_index;
+
+    // 掩码为空的顶面不进入索引
+    assert_eq!(index.len(), 2);
+    let chairs: Vec<_> = index.compatible(7).collect();
+    assert_eq!(chairs.len(), 1);
+    assert_eq!(chairs[0].frame.kind, SurfaceKind::Floor);
+    assert!((chairs[0].area - s * s).abs() < 1.0);
+    assert!((index.compatible_area(6) - s * s).abs() < 1.0);
+
+    let walls: Vec<_> = ctx.compatible_surfaces(0).collect();
+    assert_eq!(walls.len(), 1);
+    assert_eq!(walls[0].mesh_index, 1);
+    assert_eq!(walls[0].frame.kind, SurfaceKind::Wall);
+    assert!((walls[0].area - s * h).abs() < 1.0);
+    let [u, v] = walls[0].to_uv([500.0, 2000.0, 0.0]);
+    assert!(walls[0].contains_uv(u, v));
+    assert!(!walls[0].contains_uv(u, v + h));
+
+    assert_eq!(ctx.compatible_surfaces(1).count(), 0);
+    assert_eq!(index.compatible(31).count(), 0);
+    assert_eq!(index.compatible(40).count(), 0);
+}
+
+#[test]
+fn pair_collision_allows_only_clearance_overlap() {
+    // 禁止区 500 见方，限制区每侧外扩 100
+    let (a, b) = (item(7, 500.0), item(7, 500.0));
+    let at = |x: f32, theta: f32| Pose2D::new(x, 0.0, theta).to_matrix(0.0);
+    let origin = at(0.0, 0.0);
+
+    let far = check_pair(&a, &origin, &b, &at(2000.0, 0.0));
+    assert!(far.is_legal());
+    assert!(!far.clearances.overlaps);
+
+    // 间隙 150：只有限制区互相压住
+    let near = check_pair(&a, &origin, &b, &at(650.0, 0.0));
+    assert!(near.is_legal());
+    assert!(near.clearances.overlaps);
+    assert!((near.clearances.penetration - 50.0).abs() < 1e-2);
+    assert_eq!(near.violation_depth(), 0.0);
+
+    // 间隙 50：a 的限制区压到 b 的禁止区
+    let tight = check_pair(&a, &origin, &b, &at(550.0, 0.0));
+    assert!(!tight.is_legal());
+    assert!(!tight.forbidden.overlaps);
+    assert!(tight.a_clearance.overlaps && tight.b_clearance.overlaps);
+    assert!((tight.violation_depth() - 50.0).abs() < 1e-2);
+
+    let hit = check_pair(&a, &origin, &b, &at(400.0, 0.0));
+    assert!(hit.forbidden.overlaps);
+    assert!((hit.forbidden.penetration - 100.0).abs() < 1e-2);
+
+    // 旋转 45° 后对角线 ≈ 354，间隙 560 时禁止区已相交
+    let turned = check_pair(&a, &origin, &b, &at(560.0, std::f32::consts::FRAC_PI_4));
+    assert!(turned.forbidden.overlaps);
+    let clear = check_pair(&a, &origin, &b, &at(720.0, std::f32::consts::FRAC_PI_4));
+    assert!(!clear.forbidden.overlaps);
+}
+
+/// 体素 10mm 的 SDF，窄带 ±30mm
+fn sdf_region(mesh: Mesh) -> Region {
+    VdbGrid::init();
+    let grid = VdbGrid::from_mesh(&mesh.positions, &mesh.indices, 10.0, 1.0).unwrap();
+    Region {
+        mesh,
+        sdf: Some(SdfGrid {
+            grid: Arc::new(grid),
+            voxel_size: 10.0,
+        }),
+    }
+}
+
+fn shifted(mut mesh: Mesh, dx: f32, dz: f32) -> Mesh {
+    for p in &mut mesh.positions {
+        p[0] += dx;
+        p[2] += dz;
+    }
+    mesh
+}
+
+fn merged(a: Mesh, b: Mesh) -> Mesh {
+    let base = a.positions.len() as u32;
+    Mesh {
+        positions: a.positions.into_iter().chain(b.positions).collect(),
+        indices: a
+            .indices
+            .into_iter()
+            .chain(b.indices.into_iter().map(|i| i + base))
+            .collect(),
+    }
+}
+
+/// L 形，高 500：x ∈ [0, 500]、z ∈ [0, 500] 的一角是空的，但在包围盒内
+fn l_mesh() -> Mesh {
+    merged(
+        shifted(box_mesh(1000.0, 500.0, 500.0), 0.0, -250.0),
+        shifted(box_mesh(500.0, 500.0, 500.0), -250.0, 250.0),
+    )
+}
+
+#[test]
+fn sdf_contact_refines_box_overlap() {
+    let l = sdf_region(l_mesh());
+    let cube = sdf_region(box_mesh(300.0, 300.0, 300.0));
+    let origin = Pose2D::new(0.0, 0.0, 0.0).to_matrix(0.0);
+    let at = |x: f32, z: f32| Pose2D::new(x, z, 0.0).to_matrix(0.0);
+    let band = 30.0;
+
+    // 空角里离两臂各 100：包围盒相交，SDF 判定分离
+    let separated = region_contact(&l, &origin, &cube, &at(250.0, 250.0));
+    assert!(!separated.overlaps);
+    assert_eq!(separated.penetration, 0.0);
+
+    // 贴着两臂的内侧面
+    let touching = region_contact(&l, &origin, &cube, &at(150.0, 150.0));
+    assert!(!touching.overlaps, "{touching:?}");
+    assert!(touching.penetration <= CONTACT_TOLERANCE_MM);
+
+    // 压进一条臂 10mm：窄带内取 SDF 深度，而不是包围盒的深度
+    let shallow = region_contact(&l, &origin, &cube, &at(140.0, 250.0));
+    assert!(shallow.overlaps);
+    assert!((shallow.penetration - 10.0).abs() < 1.5, "{shallow:?}");
+
+    // 整个埋进一条臂：SDF 到达窄带边缘，深度退回包围盒的结果
+    let buried = region_contact(&l, &origin, &cube, &at(-250.0, 250.0));
+    assert!(buried.overlaps);
+    assert!(buried.penetration > band, "{buried:?}");
+
+    // 十字交叉：双方的顶点与三角形重心都不在对方内部，只有密集采样能发现
+    let bar = sdf_region(box_mesh(1000.0, 100.0, 100.0));
+    let cross = sdf_region(box_mesh(100.0, 200.0, 1000.0));
+    let crossing = region_contact(&bar, &origin, &cross, &origin);
+    assert!(crossing.overlaps);
+    assert!(crossing.penetration >= band, "{crossing:?}");
+    assert!(!region_contact(&bar, &origin, &cross, &at(0.0, 600.0)).overlaps);
+}
+
+#[test]
+fn search_accepts_hull_overlaps_that_clear_in_3d() {
+    let sdf_item = |mesh: Mesh| {
+        let mut region = item(7, 0.0);
+        region.regions.forbidden_region = sdf_region(mesh.clone());
+        region.regions.restricted_region = sdf_region(mesh);
+        region
+    };
+    let with_sdf = vec![sdf_item(l_mesh()), sdf_item(box_mesh(200.0, 200.0, 200.0))];
+    let mut without_sdf = with_sdf.clone();
+    for item in &mut without_sdf {
+        item.regions.forbidden_region.sdf = None;
+        item.regions.restricted_region.sdf = None;
+    }
+
+    let corner = Placement::new(1, Pose2D::new(1300.0, 1300.0, 0.0));
+    let into_arm = Placement::new(1, Pose2D::new(1050.0, 1300.0, 0.0));
+    let check = |items: Vec<PlacementRegion>| {
+        let ctx = context(items);
+        let root = LayoutState::new(ctx, HashMap::from([(0, 1), (1, 1)]));
+        let state = root
+            .with_placements(vec![Placement::new(0, Pose2D::new(1000.0, 1000.0, 0.0))])
+            .unwrap();
+        (state.fits(&corner), state.fits(&into_arm))
+    };
+
+    // 小箱子在 L 的空角里：俯视凸包相交，3D 不相交
+    assert_eq!(check(without_sdf), (false, false));
+    assert_eq!(check(with_sdf), (true, false));
+}
+
+#[test]
+fn clearance_may_not_cover_placed_forbidden_zone() {
+    let ctx = context_with(
+        vec![item(7, 500.0), item(7, 500.0)],
+        CandidateConfig {
+            sample_step_mm: 100.0,
+            max_children: usize::MAX,
+            ..CandidateConfig::default()
+        },
+    );
+    let root = LayoutState::new(ctx, HashMap::from([(0, 1), (1, 1)]));
+    let first = root
+        .with_placements(vec![Placement::new(0, Pose2D::new(1000.0, 1000.0, 0.0))])
+        .unwrap();
+    let surface = &first.context().surfaces[0];
+    let beside = |dx: f32| Pose2D::new(1000.0 + dx, 1000.0, 0.0);
+
+    assert!(first.is_valid(&Placement::new(1, beside(650.0)), surface));
+    assert!(!first.is_valid(&Placement::new(1, beside(550.0)), surface));
+    assert!(!first.is_valid(&Placement::new(1, beside(300.0)), surface));
+}
+
+#[test]
+fn room_shell_reports_containment_and_wall_distances() {
+    let quad = |positions: [[f32; 3]; 4]| Mesh {
+        positions: positions.to_vec(),
+        indices: vec![0, 1, 2, 0, 2, 3],
+    };
+    let (s, h) = (2000.0, 2500.0);
+    let space = Space {
+        meshes: vec![
+            floor(s),
+            quad([[0.0, h, 0.0], [s, h, 0.0], [s, h, s], [0.0, h, s]]),
+            quad([[0.0, 0.0, 0.0], [s, 0.0, 0.0], [s, h, 0.0], [0.0, h, 0.0]]),
+            quad([[0.0, 0.0, s], [s, 0.0, s], [s, h, s], [0.0, h, s]]),
+            quad([[0.0, 0.0, 0.0], [0.0, 0.0, s], [0.0, h, s], [0.0, h, 0.0]]),
+            quad([[s, 0.0, 0.0], [s, 0.0, s], [s, h, s], [s, h, 0.0]]),
+        ],
+        surface_metas: vec![SurfaceMeta { regions_type_mask: RegionsTypeMask::NONE }; 6],
+    };
+    let shell = RoomShell::from_space(&space);
+    assert_eq!(shell.floors().len(), 1);
+    let sofa = item(7, 500.0);
+    let at = |x: f32, z: f32, y: f32| Pose2D::new(x, z, 0.0).to_matrix(y);
+
+    let centre = shell.contain(&sofa, &at(1000.0, 1000.0, 0.0));
+    assert!(centre.is_contained());
+    assert_eq!(centre.walls.len(), 4);
+    assert!(centre.walls.iter().all(|w| (w.distance - 750.0).abs() < 1e-2));
+
+    // 贴近 z = 0 的墙但不穿透
+    let near = shell.contain(&sofa, &at(1000.0, 300.0, 0.0));
+    assert!(near.is_contained());
+    assert!((near.min_wall_distance().unwrap() - 50.0).abs() < 1e-2);
+    assert_eq!(near.walls.iter().find(|w| w.mesh_index == 2).unwrap().distance, 50.0);
+
+    let through = shell.contain(&sofa, &at(1000.0, 100.0, 0.0));
+    assert!(!through.inside && through.intersects);
+    assert!((through.penetration - 150.0).abs() < 1e-2);
+    assert!(through.min_wall_distance().unwrap() < 0.0);
+
+    let ceiling = shell.contain(&sofa, &at(1000.0, 1000.0, 2200.0));
+    assert!(ceiling.intersects);
+    assert!((ceiling.penetration - 200.0).abs() < 1e-2);
+
+    let outside = shell.contain(&sofa, &at(3000.0, 1000.0, 0.0));
+    assert!(!outside.inside && !outside.intersects);
+    assert!(!outside.is_contained());
+
+    // 上下文用摆放所在表面的矩阵做同样的检查
+    let ctx = LayoutContext::new(space, vec![sofa], CandidateConfig::default());
+    let placement = Placement::new(0, Pose2D::new(1000.0, 1000.0, 0.3));
+    assert!(ctx.containment(&placement).unwrap().is_contained());
+}
+
+fn furnished_room(items: Vec<PlacementRegion>, constraints: Vec<Constraint>) -> Arc<LayoutContext> {
+    let mut mask = RegionsTypeMask::NONE;
+    for item in &items {
+        mask.insert_id(item.semantics.regions_type);
+    }
+    let space = Space {
+        meshes: vec![floor(2000.0)],
+        surface_metas: vec![SurfaceMeta { regions_type_mask: mask }],
+    };
+    let candidates = CandidateConfig {
+        sample_step_mm: 250.0,
+        ..CandidateConfig::default()
+    };
+    Arc::new(
+        LayoutContext::new(space, items, candidates)
+            .with_constraints(ConstraintSet::new(constraints)),
+    )
+}
+
+fn relation(subject: u32, relation: Relation, target: u32, strength: Strength) -> Constraint {
+    Constraint {
+        label: format!("{subject} {relation:?} {target}"),
+        subject,
+        target,
+        relation,
+        strength,
+    }
+}
+
+#[test]
+fn relational_constraints_score_and_validate_layouts() {
+    let faces = Relation::Faces {
+        max_distance_mm: 600.0,
+        max_angle_deg: 30.0,
+    };
+    let soft = furnished_room(
+        vec![item(6, 800.0), item(7, 400.0)],
+        vec![relation(7, faces, 6, Strength::Soft { weight: 1.0 })],
+    );
+    let at = |item_id: u32, x: f32, z: f32, theta: f32| Placement::new(item_id, Pose2D::new(x, z, theta));
+    let table = at(0, 1000.0, 1000.0, 0.0);
+    // 椅子正前方为局部 +Z，theta = 0 时朝向桌子
+    let facing = vec![table.clone(), at(1, 1000.0, 250.0, 0.0)];
+    let away = vec![table.clone(), at(1, 1000.0, 250.0, std::f32::consts::PI)];
+    let relations = |state: &LayoutState| {
+        state.breakdown().terms.iter().find(|t| t.name == "relations").unwrap().value
+    };
+
+    let root = LayoutState::new(soft, HashMap::new());
+    let good = root.with_placements(facing.clone()).unwrap();
+    assert_eq!(good.constraint_report()[0].satisfied, 1);
+    assert_eq!(relations(&good), 1.0);
+    let bad = root.with_placements(away.clone()).unwrap();
+    assert_eq!(bad.constraint_report()[0].violated, 1);
+    assert_eq!(relations(&bad), 0.0);
+    assert!(good.score() > bad.score());
+
+    let hard = furnished_room(
+        vec![item(6, 800.0), item(7, 400.0)],
+        vec![relation(7, faces, 6, Strength::Hard)],
+    );
+    let root = LayoutState::new(Arc::clone(&hard), HashMap::new());
+    assert!(root.with_placements(facing).is_some());
+    assert!(root.with_placements(away.clone()).is_none());
+
+    // 桌子还没放时只算待定，不剪枝
+    let waiting = LayoutState::new(hard, HashMap::from([(0, 1)]));
+    let chair_only = waiting.with_placements(away[1..].to_vec()).unwrap();
+    let status = chair_only.constraint_report()[0];
+    assert_eq!((status.pending, status.violated), (1, 0));
+}
+
+#[test]
+fn hard_constraints_prune_expansion() {
+    let faces = Relation::Faces {
+        max_distance_mm: 600.0,
+        max_angle_deg: 30.0,
+    };
+    let ctx = furnished_room(
+        vec![item(6, 800.0), item(7, 400.0)],
+        vec![relation(7, faces, 6, Strength::Hard)],
+    );
+    let root = LayoutState::new(ctx, HashMap::from([(0, 1), (1, 1)]));
+    let tables = root.expand();
+    assert!(tables.iter().all(|t| t.placed()[0].item_id == 0));
+
+    let mut complete = 0;
+    for table in tables {
+        for layout in table.expand() {
+            let status = layout.constraint_report()[0];
+            assert_eq!((status.satisfied, status.violated), (1, 0));
+            complete += 1;
+        }
+    }
+    assert!(complete > 0, "expected chairs facing some table placement");
+}
+
+#[test]
+fn on_relation_stacks_items_without_collision() {
+    let lamp_on_table = relation(9, Relation::On, 6, Strength::Hard);
+    let items = || vec![item(6, 800.0), item(9, 200.0)];
+    let layout = vec![
+        Placement::new(0, Pose2D::new(1000.0, 1000.0, 0.0)),
+        Placement::new(1, Pose2D::new(1100.0, 950.0, 0.5)),
+    ];
+
+    let stacked = LayoutState::new(furnished_room(items(), vec![lamp_on_table]), HashMap::new())
+        .with_placements(layout.clone())
+        .unwrap();
+    assert_eq!(stacked.constraint_report()[0].satisfied, 1);
+    let lamp = stacked.placement_matrix(&layout[1]);
+    assert!((lamp[3][1] - 800.0).abs() < 1e-3);
+    assert_eq!(stacked.placement_matrix(&layout[0])[3][1], 0.0);
+
+    let plain = LayoutState::new(furnished_room(items(), Vec::new()), HashMap::new());
+    assert!(plain.with_placements(layout).is_none());
+}
+
+#[test]
+fn inventory_honours_ranges_and_optional_items() {
+    let with_counts = |mut region: PlacementRegion, count: i32, min: Option<i32>, reward: f32| {
+        region.semantics.count = count;
+        region.semantics.min_count = min;
+        region.semantics.reward = reward;
+        region
+    };
+    let items = vec![
+        with_counts(item(7, 500.0), 3, Some(1), 0.5),
+        with_counts(item(7, 300.0), 1, Some(0), 1.0),
+        with_counts(item(7, 400.0), 0, None, 0.0),
+    ];
+    let ctx = context(items);
+    assert_eq!(ctx.demands[0], Demand::range(1, 3).with_reward(0.5));
+    assert_eq!(ctx.demands[1], Demand::optional(1, 1.0));
+    assert!(ctx.demands[1].is_optional());
+    assert_eq!(ctx.initial_inventory(), HashMap::from([(0, 3), (1, 1)]));
+
+    let root = LayoutState::new(Arc::clone(&ctx), ctx.initial_inventory());
+    // 下限未满足时不能放弃
+    let firsts = root.expand();
+    assert!(firsts.iter().all(|c| c.placed().len() == 1));
+
+    let one = firsts.into_sorted_vec().pop().unwrap();
+    let report = one.count_report();
+    assert!(report.iter().all(|r| r.is_met()));
+    assert_eq!(report[0].placed, 1);
+    assert_eq!(report[2].placed, 0);
+
+    // 剩余 2 件都在下限之外，可以放弃
+    let children = one.expand().into_vec();
+    let skipped = children.iter().find(|c| c.placed().len() == 1).expect("skip child");
+    assert!(!skipped.inventory().contains_key(&0));
+    assert!(children.iter().any(|c| c.placed().len() == 2 && c.score() > skipped.score()));
+
+    // 放弃所有可选家具后即完成搜索
+    let done = skipped.expand().into_vec().into_iter().find(|c| c.placed().len() == 1).unwrap();
+    assert!(done.is_complete() && done.counts_met());
+
+    let count = |state: &LayoutState| {
+        state.breakdown().terms.iter().find(|t| t.name == "count").unwrap().value
+    };
+    // 下限 1 件 + 奖励 0.5 × 2 + 1.0 × 1 = 3
+    assert!((count(&done) - 1.0 / 3.0).abs() < 1e-6);
+    assert_eq!(count(&root), 0.0);
+}
+
+#[test]
+fn demand_from_semantics_clamps_unchecked_count_min() {
+    let mut region = item(7, 500.0);
+    region.semantics.count = 2;
+    region.semantics.min_count = Some(5);
+    assert_eq!(Demand::from_semantics(&region.semantics), Demand::exact(2));
+    region.semantics.min_count = Some(-1);
+    assert_eq!(Demand::from_semantics(&region.semantics), Demand::range(0, 2));
+    region.semantics.count = -3;
+    assert_eq!(Demand::from_semantics(&region.semantics), Demand::exact(0));
+}
+
+#[test]
+fn door_and_window_keep_outs_block_items() {
+    let quad = |positions: [[f32; 3]; 4]| Mesh {
+        positions: positions.to_vec(),
+        indices: vec![0, 1, 2, 0, 2, 3],
+    };
+    let mask_of = |id: Option<u32>| {
+        let mut mask = RegionsTypeMask::NONE;
+        if let Some(id) = id {
+            mask.insert_id(id);
+        }
+        SurfaceMeta {
+            regions_type_mask: mask,
+        }
+    };
+    let (door, window, s) = (0, 1, 2000.0);
+    let space = Space {
+        meshes: vec![
+            floor(s),
+            // 门在 z = 0 的墙上，宽 900、高 2100
+            quad([[800.0, 0.0, 0.0], [1700.0, 0.0, 0.0], [1700.0, 2100.0, 0.0], [800.0, 2100.0, 0.0]]),
+            // 窗在 z = 2000 的墙上，窗台高 900
+            quad([[300.0, 900.0, s], [1100.0, 900.0, s], [1100.0, 1500.0, s], [300.0, 1500.0, s]]),
+        ],
+        surface_metas: vec![mask_of(Some(7)), mask_of(Some(door)), mask_of(Some(window))],
+    };
+    let mut tall = item(7, 300.0);
+    tall.regions.forbidden_region.mesh = box_mesh(300.0, 1200.0, 300.0);
+    let items = vec![item(7, 500.0), item(7, 300.0), tall];
+    let mut config = KeepOutConfig::default();
+    config.doors.insert_id(door);
+    config.windows.insert_id(window);
+
+    let build = |config: &KeepOutConfig| {
+        let ctx = LayoutContext::new(space.clone(), items.clone(), CandidateConfig::default());
+        LayoutState::new(Arc::new(ctx.with_keep_outs(config)), HashMap::new())
+    };
+    let state = build(&config);
+    let kinds: Vec<_> = state.context().keep_outs.iter().map(|z| z.kind).collect();
+    assert_eq!(kinds, [KeepOutKind::DoorSwing, KeepOutKind::DoorApproach, KeepOutKind::WindowBand]);
+    let band = &state.context().keep_outs[2].shape;
+    assert!((band.min_y - 900.0).abs() < 1e-3 && (band.max_y - 1500.0).abs() < 1e-3);
+    let fits = |state: &LayoutState, item_id: u32, x: f32, z: f32| {
+        state.fits(&Placement::new(item_id, Pose2D::new(x, z, 0.0)))
+    };
+
+    // 门前通行区与门扇扫过的范围
+    assert!(!fits(&state, 0, 1250.0, 300.0));
+    assert!(!fits(&state, 0, 1100.0, 950.0));
+    assert!(fits(&state, 0, 1250.0, 1300.0));
+    assert!(fits(&state, 0, 400.0, 1000.0));
+
+    // 窗前：低于窗台的家具可以放，高的不行
+    assert!(fits(&state, 1, 700.0, 1800.0));
+    assert!(!fits(&state, 2, 700.0, 1800.0));
+    config.allow_low_under_windows = false;
+    assert!(!fits(&build(&config), 1, 700.0, 1800.0));
+}
+
+#[test]
+fn circulation_requires_wide_path_from_door() {
+    let s = 3000.0;
+    let mut door_mask = RegionsTypeMask::NONE;
+    door_mask.insert_id(0);
+    let mut floor_mask = RegionsTypeMask::NONE;
+    floor_mask.insert_id(7);
+    let space = Space {
+        meshes: vec![
+            floor(s),
+            // 门在 z = 0 的墙上，x 从 1000 到 1900
+            Mesh {
+                positions: vec![
+                    [1000.0, 0.0, 0.0],
+                    [1900.0, 0.0, 0.0],
+                    [1900.0, 2100.0, 0.0],
+                    [1000.0, 2100.0, 0.0],
+                ],
+                indices: vec![0, 1, 2, 0, 2, 3],
+            },
+        ],
+        surface_metas: vec![
+            SurfaceMeta {
+                regions_type_mask: floor_mask,
+            },
+            SurfaceMeta {
+                regions_type_mask: door_mask,
+            },
+        ],
+    };
+    // 隔断：长条形家具，横在房间中部
+    let divider = |width: f32| {
+        let mut divider = item(7, 300.0);
+        divider.regions.forbidden_region.mesh = box_mesh(width, 900.0, 300.0);
+        divider.regions.restricted_region.mesh = box_mesh(width + 200.0, 900.0, 500.0);
+        divider
+    };
+    let keep_out = KeepOutConfig {
+        doors: door_mask,
+        ..KeepOutConfig::default()
+    };
+    let weights = ObjectiveWeights {
+        circulation: 1.0,
+        ..ObjectiveWeights::default()
+    };
+    let build = |divider_width: f32, hard: bool| {
+        let items = vec![divider(divider_width), item(7, 400.0)];
+        let ctx = LayoutContext::new(space.clone(), items, CandidateConfig::default())
+            .with_objective(Objective::from_weights(&weights, RegionsTypeMask::NONE))
+            .with_keep_outs(&keep_out)
+            .with_circulation(CirculationConfig {
+                hard,
+                ..CirculationConfig::default()
+            });
+        LayoutState::new(Arc::new(ctx), HashMap::new())
+    };
+    let layout = |divider_x: f32| {
+        vec![
+            Placement::new(0, Pose2D::new(divider_x, 1500.0, 0.0)),
+            Placement::new(1, Pose2D::new(2500.0, 2500.0, 0.0)),
+        ]
+    };
+
+    // 隔断只占左半边，右侧留出 1500 宽的通道
+    let open = build(1200.0, true).with_placements(layout(900.0)).unwrap();
+    let report = open.circulation_report();
+    assert_eq!(report.doors, 1);
+    assert!(report.doors_connected && report.is_walkable());
+    let far = &report.paths[1];
+    let length = far.length_mm.unwrap();
+    assert!(length > 1500.0 && length < 3000.0, "length {length}");
+    assert!(far.min_width_mm.unwrap() >= 800.0);
+    let polyline = &far.polyline;
+    assert!(polyline[0][1] < 600.0 && polyline[0][0] > 1000.0 && polyline[0][0] < 1900.0);
+    let end = polyline.last().unwrap();
+    assert!((end[0] - 2500.0).abs() <= 300.0 && (end[1] - 2500.0).abs() <= 300.0);
+    assert_eq!(report.polylines().count(), 2);
+
+    // 隔断两侧只剩 500 宽的缝：后面的家具走不到
+    let blocked = build(2000.0, false).with_placements(layout(1500.0)).unwrap();
+    let report = blocked.circulation_report();
+    assert!(report.paths[0].reachable && !report.paths[1].reachable);
+    assert!(!blocked.is_walkable());
+    let term = blocked.breakdown().terms.into_iter().find(|t| t.name == "circulation");
+    assert!((term.unwrap().value - 0.5).abs() < 1e-6);
+    assert!(build(2000.0, true).with_placements(layout(1500.0)).is_none());
+
+    // 没有门时无从判断，视为可达
+    let ctx = build_context(vec![item(7, 400.0)], CandidateConfig::default());
+    let state = LayoutState::new(Arc::new(ctx), HashMap::new())
+        .with_placements(vec![Placement::new(0, Pose2D::new(1000.0, 1000.0, 0.0))])
+        .unwrap();
+    let report = state.circulation_report();
+    assert_eq!(report.doors, 0);
+    assert!(report.is_walkable() && report.paths[0].length_mm.is_none());
+}
+
+#[test]
+fn item_groups_search_as_one_unit_and_expand_to_members() {
+    let mut chair = item(7, 300.0);
+    chair.semantics.count = 2;
+    let items = vec![item(7, 600.0), chair];
+    let group = |chairs: usize, jitter_mm: f32| {
+        let mut members = vec![GroupMember {
+            item_id: 0,
+            offset: Pose2D::new(0.0, 0.0, 0.0),
+        }];
+        for i in 0..chairs {
+            // 椅子在桌子前后，面朝桌子
+            let (z, theta) = if i % 2 == 0 { (-500.0, 0.0) } else { (500.0, std::f32::consts::PI) };
+            members.push(GroupMember {
+                item_id: 1,
+                offset: Pose2D::new(0.0, z, theta),
+            });
+        }
+        ItemGroup {
+            name: "dining_set".to_string(),
+            members,
+            count: 1,
+            jitter_mm,
+        }
+    };
+    let grouped = |chairs: usize, jitter_mm: f32| {
+        build_context(items.clone(), CandidateConfig::default()).with_groups(vec![group(chairs, jitter_mm)])
+    };
+
+    // 3 把椅子超过需求上限
+    assert!(grouped(3, 0.0).is_err());
+
+    let ctx = Arc::new(grouped(2, 40.0).unwrap());
+    assert_eq!(ctx.items.len(), 3);
+    assert_eq!(ctx.demands[..2], [Demand::exact(0), Demand::exact(0)]);
+    assert_eq!(ctx.initial_inventory(), HashMap::from([(2, 1)]));
+    let hull = ctx.footprint(2).unwrap();
+    // 凸包：桌面 600×600 加前后两个梯形（600→300，高 350）
+    assert!((hull.area() - (600.0 * 600.0 + 2.0 * 450.0 * 350.0)).abs() < 1.0);
+
+    // 组合作为一件家具搜索，展开后得到桌子和两把椅子
+    let root = LayoutState::new(Arc::clone(&ctx), ctx.initial_inventory());
+    let state = root.expand().into_sorted_vec().pop().unwrap();
+    assert!(state.is_complete() && state.counts_met());
+    let group_pose = state.placed()[0].pose.clone();
+    let members = state.member_placements();
+    let ids: Vec<u32> = members.iter().map(|p| p.item_id).collect();
+    assert_eq!(ids, [0, 1, 1]);
+    let expected = group_pose.transform_point([0.0, 500.0]);
+    assert!((members[2].pose.x - expected[0]).abs() < 1e-3 && (members[2].pose.y - expected[1]).abs() < 1e-3);
+    assert!((members[2].pose.theta - group_pose.theta - std::f32::consts::PI).abs() < 1e-5);
+
+    // 精修：成员有偏移但仍在组合轮廓内、互不相交，且同一种子结果相同
+    let refined = state.refine_members(7);
+    assert_eq!(refined.len(), 3);
+    assert!(refined.iter().zip(&members).any(|(a, b)| a.pose.x != b.pose.x || a.pose.y != b.pose.y));
+    let outline = hull.posed(&group_pose);
+    let shapes: Vec<_> = refined.iter().map(|p| ctx.footprint(p.item_id).unwrap().posed(&p.pose)).collect();
+    for (i, shape) in shapes.iter().enumerate() {
+        assert!(polygon_inside(&outline, shape));
+        assert!(shapes[i + 1..].iter().all(|other| !polygons_overlap(shape, other)));
+    }
+    let again = state.refine_members(7);
+    assert!(refined.iter().zip(&again).all(|(a, b)| a.pose.x == b.pose.x && a.pose.y == b.pose.y));
+}
+
+#[test]
+fn refined_members_keep_their_clearance_off_neighbours() {
+    // 桌子前面的小柜：禁止区 100，限制区横向 500；精修时小柜的限制区会移出组合的限制区
+    let mut desk = item(7, 600.0);
+    desk.regions.restricted_region.mesh = box_mesh(600.0, 600.0, 600.0);
+    let mut cabinet = item(7, 100.0);
+    cabinet.regions.restricted_region.mesh = box_mesh(500.0, 100.0, 100.0);
+    let mut lamp = item(7, 100.0);
+    lamp.regions.restricted_region.mesh = box_mesh(100.0, 100.0, 100.0);
+    let group = ItemGroup {
+        name: "desk_set".to_string(),
+        members: vec![
+            GroupMember {
+                item_id: 0,
+                offset: Pose2D::new(0.0, 0.0, 0.0),
+            },
+            GroupMember {
+                item_id: 1,
+                offset: Pose2D::new(0.0, -400.0, 0.0),
+            },
+        ],
+        count: 1,
+        jitter_mm: 100.0,
+    };
+    let ctx = Arc::new(
+        build_context(vec![desk, cabinet, lamp], CandidateConfig::default())
+            .with_groups(vec![group])
+            .unwrap(),
+    );
+
+    // 台灯紧贴组合限制区的右下角，组合的凸包与台灯互不相交
+    let root = LayoutState::new(Arc::clone(&ctx), ctx.initial_inventory());
+    let state = root
+        .with_placements(vec![
+            Placement::new(3, Pose2D::new(1000.0, 1000.0, 0.0)),
+            Placement::new(2, Pose2D::new(1335.0, 590.0, 0.0)),
+        ])
+        .unwrap();
+    let lamp = ctx.footprint(2).unwrap().posed(&state.placed()[1].pose);
+
+    let mut moved = 0;
+    for seed in 0..64 {
+        let refined = state.refine_members(seed);
+        let cabinet = &refined[1];
+        moved += usize::from(cabinet.pose.x != 1000.0);
+        let clearance = ctx.clearance(1).unwrap().posed(&cabinet.pose);
+        assert!(!polygons_overlap(&clearance, &lamp), "seed {seed}: {cabinet:?}");
+    }
+    assert!(moved > 0);
+}
+
+#[test]
+fn pinned_placements_stay_fixed_and_keep_their_matrix() {
+    let mut chair = item(7, 300.0);
+    chair.semantics.count = 2;
+    let items = vec![item(7, 500.0), chair];
+    // 旧的 transforms.json 中的矩阵：带高度，导出时应原样保留
+    let matrix = Pose2D::new(1000.0, 1000.0, std::f32::consts::FRAC_PI_2).to_matrix(12.0);
+    let bed = PinnedPlacement::from_matrix(0, matrix);
+    assert!((bed.placement.pose.theta - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+    assert!((bed.placement.pose.x - 1000.0).abs() < 1e-3 && (bed.placement.pose.y - 1000.0).abs() < 1e-3);
+
+    let pinned = |pins: Vec<PinnedPlacement>| {
+        let candidates = CandidateConfig {
+            sample_step_mm: 250.0,
+            ..CandidateConfig::default()
+        };
+        let ctx = build_context(items.clone(), candidates);
+        Arc::new(ctx.with_pinned(pins).unwrap())
+    };
+    assert!(build_context(items.clone(), CandidateConfig::default())
+        .with_pinned(vec![PinnedPlacement::new(5, Pose2D::new(0.0, 0.0, 0.0))])
+        .is_err());
+
+    // 只重新摆椅子：床固定，不在待放清单里，且挡住候选位置
+    let ctx = pinned(vec![bed.clone()]);
+    assert_eq!(ctx.initial_inventory(), HashMap::from([(1, 2)]));
+    let root = LayoutState::new(Arc::clone(&ctx), ctx.initial_inventory());
+    assert_eq!(root.placed().len(), 1);
+    assert!(!root.fits(&Placement::new(1, Pose2D::new(1000.0, 1000.0, 0.0))));
+
+    let mut state = root;
+    while !state.is_complete() {
+        state = state.expand().into_sorted_vec().pop().unwrap();
+    }
+    assert!(state.counts_met());
+    let schedule = AnnealSchedule {
+        max_iterations: 300,
+        ..AnnealSchedule::default()
+    };
+    let (refined, stats) = anneal(state, &schedule, 5);
+    assert!(stats.accepted > 0);
+    assert!(ctx.is_pinned(0, &refined.placed()[0]));
+    let transforms = refined.transforms(0);
+    assert_eq!(transforms.len(), 3);
+    assert_eq!(transforms[0], (0, matrix));
+
+    // 重建时固定的摆放必须在开头
+    let movable = refined.placed()[1..].to_vec();
+    assert!(refined.with_placements(movable).is_none());
+
+    // 已固定一把椅子时，只剩一把待放
+    let ctx = pinned(vec![bed, PinnedPlacement::new(1, Pose2D::new(300.0, 300.0, 0.0))]);
+    assert_eq!(ctx.initial_inventory(), HashMap::from([(1, 1)]));
+}
+
+#[test]
+fn pinned_wall_items_keep_their_wall() {
+    let (s, h) = (2000.0, 2500.0);
+    let mask_of = |id: u32| {
+        let mut mask = RegionsTypeMask::NONE;
+        mask.insert_id(id);
+        SurfaceMeta {
+            regions_type_mask: mask,
+        }
+    };
+    let space = Space {
+        meshes: vec![
+            floor(s),
+            Mesh {
+                positions: vec![[0.0, 0.0, 0.0], [s, 0.0, 0.0], [s, h, 0.0], [0.0, h, 0.0]],
+                indices: vec![0, 1, 2, 0, 2, 3],
+            },
+        ],
+        surface_metas: vec![mask_of(7), mask_of(9)],
+    };
+    let candidates = CandidateConfig {
+        sample_step_mm: 250.0,
+        ..CandidateConfig::default()
+    };
+    let ctx = Arc::new(LayoutContext::new(space.clone(), vec![item(9, 500.0)], candidates.clone()));
+    let root = LayoutState::new(Arc::clone(&ctx), HashMap::from([(0, 1)]));
+    // 挂得比地面摆放的高度区间（0..500）更高的一件，才能区分是否恢复了墙面
+    let hung = root
+        .expand()
+        .into_vec()
+        .into_iter()
+        .find(|c| c.transforms(0)[0].1[3][1] > 1000.0)
+        .expect("a placement high on the wall");
+    let original = hung.placed()[0].clone();
+    let [(_, matrix)] = hung.transforms(0)[..] else {
+        panic!("expected one transform");
+    };
+
+    // 用导出的矩阵重新固定：恢复墙面与 (u, v) 位姿
+    let ctx = Arc::new(
+        LayoutContext::new(space, vec![item(9, 500.0)], candidates)
+            .with_pinned(vec![PinnedPlacement::from_matrix(0, matrix)])
+            .unwrap(),
+    );
+    let pinned = &ctx.pinned[0].placement;
+    let (pose, expected) = (pinned.surface.as_ref().unwrap(), original.surface.as_ref().unwrap());
+    assert_eq!(pose.surface, expected.surface);
+    assert!((pose.u - expected.u).abs() < 1e-2 && (pose.v - expected.v).abs() < 1e-2);
+    assert!((pose.offset - expected.offset).abs() < 1e-2 && pose.rotation.abs() < 1e-4);
+
+    let root = LayoutState::new(Arc::clone(&ctx), HashMap::new());
+    assert!(ctx.is_pinned(0, &root.placed()[0]));
+    // 俯视位姿相同但不在墙上的摆放不是这件固定家具
+    assert!(!ctx.is_pinned(0, &Placement::new(0, pinned.pose.clone())));
+    let entry = root.occupancy().forbidden().iter().next().unwrap();
+    assert!((entry.shape.min_y - matrix[3][1]).abs() < 1e-2);
+    // 固定的挂件挡住墙上同一位置，导出时矩阵原样保留
+    assert!(!root.fits(&original));
+    assert_eq!(root.transforms(0), vec![(0, matrix)]);
+}
+
+/// `n`×`n` 个边长 `cell` 的格子组成的地面，跳过 `skip` 中的格子；奇数格反向绕序
+fn grid_floor(n: usize, cell: f32, skip: &[(usize, usize)]) -> Mesh {
+    let mut positions = Vec::new();
+    for z in 0..=n {
+        for x in 0..=n {
+            positions.push([x as f32 * cell, 0.0, z as f32 * cell]);
+        }
+    }
+    let id = |x: usize, z: usize| (z * (n + 1) + x) as u32;
+    let mut indices = Vec::new();
+    for z in 0..n {
+        for x in 0..n {
+            if skip.contains(&(x, z)) {
+                continue;
+            }
+            let (a, b, c, d) = (id(x, z), id(x + 1, z), id(x + 1, z + 1), id(x, z + 1));
+            if (x + z) % 2 == 0 {
+                indices.extend([a, b, c, a, c, d]);
+            } else {
+                indices.extend([a, c, b, a, d, c]);
+            }
+        }
+    }
+    Mesh { positions, indices }
+}
+
+#[test]
+fn floor_boundary_keeps_holes_and_splits_pinch_points() {
+    use geo::{Area, Contains, Winding};
+
+    // 中间一格挖空：柱子
+    let holed = grid_floor(3, 1000.0, &[(1, 1)]);
+    let boundary = flatten_boundary_polygon(&holed).unwrap();
+    assert_eq!(boundary.polygon.interiors().len(), 1);
+    assert!(boundary.polygon.exterior().is_ccw());
+    assert!(boundary.polygon.interiors()[0].is_cw());
+    assert!((boundary.polygon.unsigned_area() - 8.0e6).abs() < 1.0);
+    let rings = boundary.rings_world();
+    assert_eq!(rings.len(), 2);
+    assert_eq!(rings[1].len(), 4);
+
+    let xz = boundary.reproject(|p| [p[0] as f64, p[2] as f64]);
+    assert!(xz.exterior().is_ccw());
+    assert!(xz.contains(&geo::Point::new(500.0, 500.0)));
+    assert!(!xz.contains(&geo::Point::new(1500.0, 1500.0)));
+
+    let mut mask = RegionsTypeMask::NONE;
+    mask.insert_id(7);
+    let space = Space {
+        meshes: vec![holed],
+        surface_metas: vec![SurfaceMeta {
+            regions_type_mask: mask,
+        }],
+    };
+    let ctx = LayoutContext::new(space, vec![item(7, 500.0)], CandidateConfig::default());
+    let surface = &ctx.surface_index.surfaces()[0];
+    let [u, v] = surface.to_uv([1500.0, 0.0, 1500.0]);
+    assert!(!surface.contains_uv(u, v));
+    assert!((surface.area - 8.0e6).abs() < 1.0);
+
+    // 两格只在一个顶点相接：各自成环，取面积相同时下标小的
+    let pinched = grid_floor(2, 1000.0, &[(1, 0), (0, 1)]);
+    let outer = flatten_outer_boundary(&pinched);
+    assert_eq!(outer.len(), 4);
+    let boundary = flatten_boundary_polygon(&pinched).unwrap();
+    assert!(boundary.polygon.interiors().is_empty());
+    assert!((boundary.polygon.unsigned_area() - 1.0e6).abs() < 1.0);
+    assert_eq!(flatten_outer_boundary(&pinched), outer);
+}
+
+#[test]
+fn flatten_outer_boundary_keeps_the_longest_loop() {
+    use geo::Area;
+
+    // 1m × 1m 的方块与 4m × 0.1m 的细条：方块面积大，细条周长长
+    let rect = |x0: f32, x1: f32, z1: f32, base: u32| {
+        let positions = vec![[x0, 0.0, 0.0], [x1, 0.0, 0.0], [x1, 0.0, z1], [x0, 0.0, z1]];
+        (positions, [0, 1, 2, 0, 2, 3].map(|i| base + i))
+    };
+    let (square, square_indices) = rect(0.0, 1000.0, 1000.0, 0);
+    let (strip, strip_indices) = rect(2000.0, 6000.0, 100.0, 4);
+    let mesh = Mesh {
+        positions: [square, strip].concat(),
+        indices: [square_indices, strip_indices].concat(),
+    };
+
+    // 旧约定：周长最长的环，按遍历顺序
+    let outer = flatten_outer_boundary(&mesh);
+    assert_eq!(outer.len(), 4);
+    assert!(outer.iter().all(|p| p[0] >= 2000.0 - 1e-2), "{outer:?}");
+    // 多边形接口取面积最大的外环
+    let boundary = flatten_boundary_polygon(&mesh).unwrap();
+    assert!((boundary.polygon.unsigned_area() - 1.0e6).abs() < 1.0);
+
+    // 有洞时外环周长更长，洞不出现在结果里
+    let holed = grid_floor(3, 1000.0, &[(1, 1)]);
+    let outer = flatten_outer_boundary(&holed);
+    assert_eq!(outer.len(), 12);
+    assert!(outer.iter().any(|p| p[0].abs() < 1e-2 && p[2].abs() < 1e-2));
+}
+
+#[test]
+fn support_surfaces_keep_items_off_column_holes() {
+    use geo::Intersects;
+
+    // 3m × 3m 地面，中间 1m × 1m 是柱子
+    let mut mask = RegionsTypeMask::NONE;
+    mask.insert_id(7);
+    let space = Space {
+        meshes: vec![grid_floor(3, 1000.0, &[(1, 1)])],
+        surface_metas: vec![SurfaceMeta {
+            regions_type_mask: mask,
+        }],
+    };
+    let candidates = CandidateConfig {
+        sample_step_mm: 250.0,
+        ..CandidateConfig::default()
+    };
+    let ctx = Arc::new(LayoutContext::new(space, vec![item(7, 500.0)], candidates));
+    let surface = &ctx.surfaces[0];
+    assert_eq!(surface.outline.interiors().len(), 1);
+
+    let root = LayoutState::new(Arc::clone(&ctx), HashMap::from([(0, 1)]));
+    assert!(root.is_valid(&Placement::new(0, Pose2D::new(500.0, 500.0, 0.0)), surface));
+    // 整个压在柱子上、跨过柱子边、贴着柱子边都不行
+    assert!(!root.is_valid(&Placement::new(0, Pose2D::new(1500.0, 1500.0, 0.0)), surface));
+    assert!(!root.is_valid(&Placement::new(0, Pose2D::new(1000.0, 1500.0, 0.0)), surface));
+    assert!(!root.is_valid(&Placement::new(0, Pose2D::new(750.0, 1500.0, 0.0)), surface));
+    assert!(root.is_valid(&Placement::new(0, Pose2D::new(700.0, 1500.0, 0.0)), surface));
+
+    let column = geo_types::Polygon::new(surface.outline.interiors()[0].clone(), Vec::new());
+    let children = root.expand();
+    assert!(!children.is_empty());
+    for child in children {
+        let placed = &child.placed()[0];
+        let shape = ctx.footprint(0).unwrap().posed(&placed.pose);
+        assert!(!shape.intersects(&column), "{:?} overlaps the column", placed.pose);
+    }
+}
+
+/// 未标注的整体房间网格：每个面单独的顶点（未焊接）、绕序不一致，地上放一个小箱子
+fn raw_room(sx: f32, sy: f32, sz: f32) -> Mesh {
+    let quads = [
+        // 地面、顶面
+        [[0.0, 0.0, 0.0], [sx, 0.0, 0.0], [sx, 0.0, sz], [0.0, 0.0, sz]],
+        [[0.0, sy, 0.0], [0.0, sy, sz], [sx, sy, sz], [sx, sy, 0.0]],
+        // 四面墙
+        [[0.0, 0.0, 0.0], [0.0, sy, 0.0], [sx, sy, 0.0], [sx, 0.0, 0.0]],
+        [[0.0, 0.0, sz], [sx, 0.0, sz], [sx, sy, sz], [0.0, sy, sz]],
+        [[0.0, 0.0, 0.0], [0.0, 0.0, sz], [0.0, sy, sz], [0.0, sy, 0.0]],
+        [[sx, 0.0, 0.0], [sx, sy, 0.0], [sx, sy, sz], [sx, 0.0, sz]],
+    ];
+    let mut mesh = Mesh::default();
+    for (i, quad) in quads.iter().enumerate() {
+        let base = mesh.positions.len() as u32;
+        mesh.positions.extend_from_slice(quad);
+        if i % 2 == 0 {
+            mesh.indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+        } else {
+            mesh.indices.extend([base, base + 2, base + 1, base, base + 3, base + 2]);
+        }
+    }
+    let mut clutter = box_mesh(200.0, 200.0, 200.0);
+    let base = mesh.positions.len() as u32;
+    mesh.positions
+        .extend(clutter.positions.drain(..).map(|p| [p[0] + 500.0, p[1], p[2] + 500.0]));
+    mesh.indices.extend(clutter.indices.iter().map(|i| i + base));
+    mesh
+}
+
+#[test]
+fn segmentation_splits_raw_room_into_masked_planes() {
+    let mut floor_mask = RegionsTypeMask::NONE;
+    floor_mask.insert_id(7);
+    let mut wall_mask = RegionsTypeMask::NONE;
+    wall_mask.insert_id(0);
+    let config = SegmentConfig {
+        floor_mask,
+        wall_mask,
+        ..SegmentConfig::default()
+    };
+    let raw = Space {
+        meshes: vec![raw_room(4000.0, 2800.0, 3000.0)],
+        surface_metas: vec![SurfaceMeta {
+            regions_type_mask: RegionsTypeMask::NONE,
+        }],
+    };
+    let space = segment_space(&raw, &config);
+
+    // 小箱子的面小于 min_area 被丢弃
+    assert_eq!(space.meshes.len(), 6);
+    let interior = nalgebra::Vector3::from(space.interior_point().unwrap());
+    let frames: Vec<_> = space
+        .meshes
+        .iter()
+        .map(|m| SurfaceFrame::fit(m, &interior).unwrap().kind)
+        .collect();
+    assert_eq!(frames.iter().filter(|k| **k == SurfaceKind::Floor).count(), 1);
+    assert_eq!(frames.iter().filter(|k| **k == SurfaceKind::Ceiling).count(), 1);
+    assert_eq!(frames.iter().filter(|k| **k == SurfaceKind::Wall).count(), 4);
+    for (kind, meta) in frames.iter().zip(&space.surface_metas) {
+        assert_eq!(meta.regions_type_mask, config.mask_for(*kind));
+    }
+    let floor = frames.iter().position(|k| *k == SurfaceKind::Floor).unwrap();
+    assert_eq!(flatten_outer_boundary(&space.meshes[floor]).len(), 4);
+
+    let ctx = Arc::new(LayoutContext::new(space, vec![item(7, 800.0)], CandidateConfig::default()));
+    assert_eq!(ctx.surface_index.surfaces().len(), 5);
+    let mut engine = SearchEngine::with_root(HeapFrontier::new(), LayoutState::new(Arc::clone(&ctx), HashMap::from([(0, 1)])));
+    engine.run(&SearchBudget::default().with_max_steps(50));
+    assert!(engine.into_result().unwrap().is_complete());
+}
+
+#[test]
+fn room_shell_sdf_mesh_is_closed_and_search_stays_inside() {
+    // 3m × 3m、高 2.5m，中间是柱子；z = 0 的墙上留了 1m 宽的门洞，墙与地面只在角上相接
+    let quad = |positions: [[f32; 3]; 4]| Mesh {
+        positions: positions.to_vec(),
+        indices: vec![0, 1, 2, 0, 2, 3],
+    };
+    let (s, h) = (3000.0, 2500.0);
+    let mut mask = RegionsTypeMask::NONE;
+    mask.insert_id(7);
+    let meshes = vec![
+        grid_floor(3, 1000.0, &[(1, 1)]),
+        quad([[0.0, h, 0.0], [s, h, 0.0], [s, h, s], [0.0, h, s]]),
+        quad([[0.0, 0.0, 0.0], [1000.0, 0.0, 0.0], [1000.0, h, 0.0], [0.0, h, 0.0]]),
+        quad([[2000.0, 0.0, 0.0], [s, 0.0, 0.0], [s, h, 0.0], [2000.0, h, 0.0]]),
+        quad([[0.0, 0.0, s], [s, 0.0, s], [s, h, s], [0.0, h, s]]),
+        quad([[0.0, 0.0, 0.0], [0.0, 0.0, s], [0.0, h, s], [0.0, h, 0.0]]),
+        quad([[s, 0.0, 0.0], [s, 0.0, s], [s, h, s], [s, h, 0.0]]),
+    ];
+    let metas = meshes
+        .iter()
+        .enumerate()
+        .map(|(i, _)| SurfaceMeta {
+            regions_type_mask: if i == 0 { mask } else { RegionsTypeMask::NONE },
+        })
+        .collect();
+    let space = Space {
+        meshes,
+        surface_metas: metas,
+    };
+
+    let closed = RoomShell::from_space(&space).closed_mesh().unwrap();
+    assert!(closed.positions.iter().all(|p| p[1] == 0.0 || p[1] == h));
+    // 每条棱恰好被两个三角形共用
+    let mut edges: HashMap<(u32, u32), u32> = HashMap::new();
+    for tri in closed.indices.chunks_exact(3) {
+        for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+            *edges.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+        }
+    }
+    assert!(edges.values().all(|&n| n == 2), "{edges:?}");
+    // 体积 = 去掉柱子的地面面积 × 层高
+    let volume: f32 = closed
+        .indices
+        .chunks_exact(3)
+        .map(|tri| {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| nalgebra::Vector3::from(closed.positions[i as usize]));
+            a.dot(&b.cross(&c)) / 6.0
+        })
+        .sum();
+    assert!((volume.abs() - 8.0e6 * h).abs() < 1.0e-3 * 8.0e6 * h, "{volume}");
+
+    // 高过顶面的家具俯视轮廓放得下，但穿过顶面，不能摆放
+    let mut tall = item(7, 500.0);
+    tall.regions.forbidden_region.mesh = box_mesh(500.0, 3000.0, 500.0);
+    let ctx = Arc::new(LayoutContext::new(space, vec![item(7, 500.0), tall], CandidateConfig::default()));
+    let surface = &ctx.surfaces[0];
+    let root = LayoutState::new(Arc::clone(&ctx), HashMap::from([(0, 1), (1, 1)]));
+    assert!(root.is_valid(&Placement::new(0, Pose2D::new(500.0, 500.0, 0.0)), surface));
+    assert!(!root.is_valid(&Placement::new(1, Pose2D::new(500.0, 500.0, 0.0)), surface));
+    assert!(!ctx.is_contained(&Placement::new(1, Pose2D::new(500.0, 500.0, 0.0))));
+    assert!(root.expand().iter().all(|child| child.placed()[0].item_id == 0));
+}
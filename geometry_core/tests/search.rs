@@ -0,0 +1,261 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+use geometry_core::search::{
+    BeamFrontier, Diverse, DiverseSet, Frontier, HeapFrontier, ParallelConfig, SearchBudget,
+    SearchEngine, SearchNode, StopReason,
+};
+
+/// 二叉树节点：每层选 0 或 1，深度达到 `depth` 即为目标
+#[derive(Clone, Debug)]
+struct Bits {
+    bits: Vec<u8>,
+    depth: usize,
+}
+
+impl SearchNode for Bits {
+    fn expand(&self) -> BinaryHeap<Self> {
+        let mut children = BinaryHeap::new();
+        for b in [0, 1] {
+            let mut bits = self.bits.clone();
+            bits.push(b);
+            children.push(Bits {
+                bits,
+                depth: self.depth,
+            });
+        }
+        children
+    }
+
+    fn score(&self) -> f32 {
+        self.bits.iter().map(|b| *b as f32).sum()
+    }
+
+    fn key(&self) -> u64 {
+        self.bits.iter().fold(1u64, |acc, b| acc * 2 + *b as u64)
+    }
+
+    fn depth(&self) -> usize {
+        self.bits.len()
+    }
+
+    fn is_goal(&self) -> bool {
+        self.bits.len() >= self.depth
+    }
+}
+
+impl PartialEq for Bits {
+    fn eq(&self, other: &Self) -> bool {
+        self.score() == other.score()
+    }
+}
+
+impl Eq for Bits {}
+
+impl PartialOrd for Bits {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Bits {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score().total_cmp(&other.score())
+    }
+}
+
+impl Diverse for Bits {
+    /// 不同位的个数
+    fn distance(&self, other: &Self) -> f32 {
+        if self.bits.len() != other.bits.len() {
+            return f32::INFINITY;
+        }
+        self.bits.iter().zip(&other.bits).filter(|(a, b)| a != b).count() as f32
+    }
+}
+
+fn root(depth: usize) -> Bits {
+    Bits {
+        bits: Vec::new(),
+        depth,
+    }
+}
+
+#[test]
+fn run_stops_at_goal_with_best_result() {
+    let mut engine = SearchEngine::with_root(HeapFrontier::new(), root(4));
+    let stats = engine.run(&SearchBudget::default());
+
+    assert_eq!(stats.stop_reason, StopReason::GoalReached);
+    assert_eq!(stats.expanded, 4);
+    assert!(stats.peak_frontier >= 4);
+    let goal = engine.into_result().unwrap();
+    assert_eq!(goal.bits, vec![1, 1, 1, 1]);
+}
+
+#[test]
+fn run_respects_step_and_score_limits() {
+    let mut engine = SearchEngine::with_root(HeapFrontier::new(), root(64));
+    let stats = engine.run(&SearchBudget::default().with_max_steps(3));
+    assert_eq!(stats.stop_reason, StopReason::StepLimit);
+    assert_eq!(engine.steps(), 3);
+    assert_eq!(engine.best().unwrap().score(), 2.0);
+
+    let stats = engine.run(&SearchBudget::default().with_target_score(5.0));
+    assert_eq!(stats.stop_reason, StopReason::TargetScore);
+    assert!(engine.best().unwrap().score() >= 5.0);
+}
+
+#[test]
+fn run_reports_exhaustion_and_duplicates() {
+    let mut engine = SearchEngine::with_root(HeapFrontier::new(), root(2));
+    let budget = SearchBudget {
+        stop_on_goal: false,
+        ..SearchBudget::default()
+    };
+    let stats = engine.run(&budget);
+    assert_eq!(stats.stop_reason, StopReason::Exhausted);
+    assert_eq!(stats.expanded, 3);
+    assert_eq!(engine.goal().unwrap().score(), 2.0);
+}
+
+#[test]
+fn beam_frontier_keeps_top_k_per_depth() {
+    let mut beam = BeamFrontier::new(2);
+    for bits in [vec![0, 0], vec![1, 1], vec![0, 1], vec![1, 0, 1]] {
+        beam.push(Bits { bits, depth: 8 });
+    }
+    assert_eq!(beam.len(), 3);
+    assert_eq!(beam.pruned(), 1);
+
+    let first = beam.pop().unwrap();
+    assert_eq!(first.score(), 2.0);
+    assert_eq!(first.bits.len(), 3);
+    assert_eq!(beam.pop().unwrap().bits, vec![1, 1]);
+    assert_eq!(beam.pop().unwrap().score(), 1.0);
+    assert!(beam.pop().is_none());
+}
+
+#[test]
+fn beam_frontier_does_not_refill_a_depth_after_pops() {
+    let mut beam = BeamFrontier::new(2);
+    let node = |bits: Vec<u8>| Bits { bits, depth: 8 };
+    beam.push(node(vec![0, 0]));
+    beam.push(node(vec![0, 1]));
+    assert_eq!(beam.pop().unwrap().bits, vec![0, 1]);
+
+    // 深度 2 只剩一个名额：更好的节点挤掉排队中的节点
+    beam.push(node(vec![1, 1]));
+    assert_eq!(beam.len(), 1);
+    assert_eq!(beam.pruned(), 1);
+    assert_eq!(beam.pop().unwrap().bits, vec![1, 1]);
+
+    // 名额用完后同一深度的节点都被丢弃，其他深度不受影响
+    beam.push(node(vec![1, 0]));
+    beam.push(node(vec![1, 1, 1]));
+    assert_eq!(beam.len(), 1);
+    assert_eq!(beam.pruned(), 2);
+    assert_eq!(beam.pop().unwrap().bits, vec![1, 1, 1]);
+    assert!(beam.pop().is_none());
+}
+
+#[test]
+fn beam_search_expands_at_most_width_nodes_per_depth() {
+    let mut engine = SearchEngine::with_root(BeamFrontier::new(3), root(10));
+    let budget = SearchBudget {
+        stop_on_goal: false,
+        ..SearchBudget::default()
+    };
+    let stats = engine.run(&budget);
+    // 根节点一层只有 1 个，其余 9 层每层最多 3 个
+    assert!(stats.expanded <= 1 + 3 * 9, "{stats:?}");
+}
+
+#[test]
+fn beam_search_stays_bounded() {
+    let mut engine = SearchEngine::with_root(BeamFrontier::new(3), root(10));
+    let stats = engine.run(&SearchBudget::default());
+    assert_eq!(stats.stop_reason, StopReason::GoalReached);
+    assert!(stats.peak_frontier <= 3 * 10);
+}
+
+#[test]
+fn parallel_run_explores_same_tree_as_serial() {
+    let budget = SearchBudget {
+        stop_on_goal: false,
+        ..SearchBudget::default()
+    };
+    let mut serial = SearchEngine::with_root(HeapFrontier::new(), root(6));
+    let serial_stats = serial.run(&budget);
+
+    let parallel = ParallelConfig {
+        workers: 4,
+        batch_size: 8,
+    };
+    let mut engine = SearchEngine::with_root(HeapFrontier::new(), root(6));
+    let stats = engine.run_parallel(&budget, &parallel);
+
+    assert_eq!(stats.stop_reason, StopReason::Exhausted);
+    assert_eq!(stats.expanded, serial_stats.expanded);
+    assert_eq!(engine.steps(), serial.steps());
+    assert_eq!(engine.goal().map(|g| g.key()), serial.goal().map(|g| g.key()));
+}
+
+#[test]
+fn diverse_set_keeps_best_distinct_goals() {
+    let mut set = DiverseSet::new(2, 2.0);
+    let bits = |bits: Vec<u8>| Bits { bits, depth: 4 };
+    assert!(set.offer(&bits(vec![1, 1, 0, 0])));
+    // 与已保留的只差一位且得分更低：拒绝
+    assert!(!set.offer(&bits(vec![1, 0, 0, 0])));
+    // 只差一位但得分更高：替换
+    assert!(set.offer(&bits(vec![1, 1, 1, 0])));
+    assert_eq!(set.len(), 1);
+    assert!(set.offer(&bits(vec![0, 0, 0, 1])));
+    // 已满且不比最差的好：拒绝
+    assert!(!set.offer(&bits(vec![0, 1, 0, 0])));
+    assert_eq!(set.nodes()[0].bits, vec![1, 1, 1, 0]);
+    assert_eq!(set.nodes()[1].bits, vec![0, 0, 0, 1]);
+}
+
+#[test]
+fn run_collects_top_k_diverse_goals() {
+    let budget = SearchBudget {
+        stop_on_goal: false,
+        ..SearchBudget::default()
+    };
+    let mut engine =
+        SearchEngine::with_root(HeapFrontier::new(), root(5)).with_alternatives(3, 2.0);
+    let stats = engine.run(&budget);
+    assert_eq!(stats.stop_reason, StopReason::Exhausted);
+
+    let results = engine.into_results();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].bits, vec![1, 1, 1, 1, 1]);
+    for pair in results.windows(2) {
+        assert!(pair[0].score() >= pair[1].score());
+    }
+    for (i, a) in results.iter().enumerate() {
+        for b in &results[i + 1..] {
+            assert!(a.distance(b) >= 2.0);
+        }
+    }
+}
+
+#[test]
+fn progress_reports_each_improvement_and_cancel_stops() {
+    let scores = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&scores);
+    let mut engine = SearchEngine::with_root(HeapFrontier::new(), root(6))
+        .with_progress(move |node: &Bits| seen.lock().unwrap().push(node.score()));
+    engine.run(&SearchBudget::default());
+    assert_eq!(*scores.lock().unwrap(), vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+    let cancel = Arc::new(AtomicBool::new(true));
+    let mut engine = SearchEngine::with_root(HeapFrontier::new(), root(6));
+    let stats = engine.run(&SearchBudget::default().with_cancel(cancel));
+    assert_eq!(stats.stop_reason, StopReason::Cancelled);
+    assert_eq!(engine.steps(), 0);
+}
@@ -1,9 +1,651 @@
+use geometry_core::layout::{
+    CandidateConfig, GroupMember, ItemGroup, KeyConfig, Objective, ObjectiveWeights,
+    PinnedPlacement, Pose2D,
+};
+use geometry_core::models::placement_region::{PlacementRegion, PlacementSemantics};
+use geometry_core::search::{AnnealSchedule, CheckpointConfig, ParallelConfig, SearchBudget};
+use std::collections::HashMap;
+use types::{RegionsType, RegionsTypeMask};
+
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct SceneConfig {
     pub space_usda_path: String,
     pub placement_region_usda_dir: String,
     pub regions_type_path: String,
     pub usda_scale: f32,
+    /// Relational constraints between region types; none when unset.
+    #[serde(default)]
+    pub constraints_path: Option<String>,
+    #[serde(default)]
+    pub objective: ObjectiveConfig,
+    #[serde(default)]
+    pub search: SearchConfig,
+    #[serde(default)]
+    pub anneal: AnnealConfig,
+    #[serde(default)]
+    pub keep_out: KeepOutConfig,
+    #[serde(default)]
+    pub circulation: CirculationConfig,
+    #[serde(default)]
+    pub segmentation: SegmentationConfig,
+    /// Per region type count overrides, keyed by registry name.
+    #[serde(default)]
+    pub inventory: HashMap<String, InventoryConfig>,
+    /// Item groups placed as one unit; members are taken out of the inventory.
+    #[serde(default)]
+    pub groups: Vec<GroupConfig>,
+    #[serde(default)]
+    pub pinned: PinnedConfig,
+}
+
+/// Count override for every PlacementRegion of one region type.
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InventoryConfig {
+    /// Lower bound, 0 makes the item optional; defaults to `max` (exact count).
+    pub min: Option<i32>,
+    /// Upper bound; defaults to the count from the USDA.
+    pub max: Option<i32>,
+    /// Score reward per item placed above `min`.
+    pub reward: Option<f32>,
+}
+
+impl InventoryConfig {
+    pub fn apply(&self, semantics: &mut PlacementSemantics) {
+        if let Some(max) = self.max {
+            semantics.count = max;
+        }
+        if let Some(min) = self.min {
+            semantics.min_count = Some(min);
+        }
+        if let Some(reward) = self.reward {
+            semantics.reward = reward;
+        }
+    }
+}
+
+/// A set of items (e.g. table + chairs) searched as one placeable unit.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupConfig {
+    pub name: String,
+    /// Number of groups to place.
+    #[serde(default = "default_group_count")]
+    pub count: u32,
+    /// Random offset of each member when refining the final layout, 0 = off.
+    #[serde(default)]
+    pub jitter_mm: f32,
+    /// The first member is the anchor: the group takes its region type.
+    pub members: Vec<GroupMemberConfig>,
+}
+
+/// Group member pose relative to the group origin.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupMemberConfig {
+    /// Region type name; the first PlacementRegion of this type is used.
+    #[serde(rename = "type")]
+    pub regions_type: String,
+    #[serde(default)]
+    pub x: f32,
+    #[serde(default)]
+    pub z: f32,
+    #[serde(default)]
+    pub theta_deg: f32,
+}
+
+fn default_group_count() -> u32 {
+    1
+}
+
+impl GroupConfig {
+    pub fn build(
+        &self,
+        regions_type_ids: &HashMap<String, RegionsType>,
+        items: &[PlacementRegion],
+    ) -> Result<ItemGroup, String> {
+        if self.members.is_empty() {
+            return Err(format!("group '{}' has no members", self.name));
+        }
+        let members = self
+            .members
+            .iter()
+            .map(|member| {
+                let name = &member.regions_type;
+                let id = regions_type_ids
+                    .get(name)
+                    .ok_or_else(|| format!("group '{}': type '{name}' not found in registry", self.name))?;
+                let item_id = items
+                    .iter()
+                    .position(|item| item.semantics.regions_type == *id)
+                    .ok_or_else(|| format!("group '{}': no PlacementRegion of type '{name}'", self.name))?;
+                Ok(GroupMember {
+                    item_id: item_id as u32,
+                    offset: Pose2D::new(member.x, member.z, member.theta_deg.to_radians()),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(ItemGroup {
+            name: self.name.clone(),
+            members,
+            count: self.count,
+            jitter_mm: self.jitter_mm.max(0.0),
+        })
+    }
+}
+
+/// Placements kept fixed while the rest of the room is solved.
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PinnedConfig {
+    /// transforms.json of an earlier run; its matrices are exported unchanged.
+    pub transforms_path: Option<String>,
+    /// Only pin these region types from `transforms_path` (empty = all).
+    pub types: Vec<String>,
+    /// Region types to solve again; every other entry of `transforms_path` is pinned.
+    pub resolve_types: Vec<String>,
+    /// Explicit placements by PlacementRegion index.
+    pub placements: Vec<PinnedPoseConfig>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PinnedPoseConfig {
+    pub index: usize,
+    pub x: f32,
+    pub z: f32,
+    #[serde(default)]
+    pub theta_deg: f32,
+}
+
+#[derive(serde::Deserialize)]
+struct TransformsFile {
+    placements: Vec<TransformEntry>,
+}
+
+#[derive(serde::Deserialize)]
+struct TransformEntry {
+    index: usize,
+    matrix: [[f32; 4]; 4],
+}
+
+impl PinnedConfig {
+    pub fn load(
+        &self,
+        regions_type_ids: &HashMap<String, RegionsType>,
+        items: &[PlacementRegion],
+    ) -> Result<Vec<PinnedPlacement>, String> {
+        let keep = type_mask(&self.types, regions_type_ids, "pinned")?;
+        let resolve = type_mask(&self.resolve_types, regions_type_ids, "pinned resolve")?;
+        let item = |index: usize| {
+            items
+                .get(index)
+                .ok_or_else(|| format!("pinned PlacementRegion index {index} out of range"))
+        };
+
+        let mut pinned = Vec::new();
+        if let Some(path) = &self.transforms_path {
+            let data = std::fs::read_to_string(path)
+                .map_err(|err| format!("Failed to read pinned transforms at {path}: {err}"))?;
+            let file: TransformsFile = serde_json::from_str(&data)
+                .map_err(|err| format!("Failed to parse pinned transforms {path}: {err}"))?;
+            for entry in file.placements {
+                let regions_type = item(entry.index)?.semantics.regions_type;
+                let kept = self.types.is_empty() || keep.contains_id(regions_type);
+                if kept && !resolve.contains_id(regions_type) {
+                    // Wall/ceiling surface poses are recovered from the matrix in `with_pinned`.
+                    pinned.push(PinnedPlacement::from_matrix(entry.index as u32, entry.matrix));
+                }
+            }
+        }
+        for pose in &self.placements {
+            item(pose.index)?;
+            pinned.push(PinnedPlacement::new(
+                pose.index as u32,
+                Pose2D::new(pose.x, pose.z, pose.theta_deg.to_radians()),
+            ));
+        }
+        Ok(pinned)
+    }
+}
+
+/// Search budget and candidate generation settings.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Maximum expanded nodes, 0 = unlimited.
+    pub max_steps: usize,
+    /// Wall-clock limit in milliseconds, 0 = unlimited (the default). A limit makes
+    /// the number of expanded nodes depend on machine speed, so runs are no longer
+    /// reproducible; `max_steps` bounds the search deterministically.
+    pub time_limit_ms: u64,
+    /// Stop once the best score reaches this value.
+    pub target_score: Option<f32>,
+    pub sample_step_mm: f32,
+    pub thetas_deg: Vec<f32>,
+    pub max_children: usize,
+    pub frontier: FrontierKind,
+    /// Nodes expanded per depth level when `frontier = "beam"`.
+    pub beam_width: usize,
+    /// Position grid used to detect duplicate layouts.
+    pub dedup_grid_mm: f32,
+    /// Angle step used to detect duplicate layouts.
+    pub dedup_angle_deg: f32,
+    /// Random offset applied to each sample position.
+    pub jitter_mm: f32,
+    /// Seed for candidate sampling and tie-breaking; unless `time_limit_ms` is set,
+    /// the same seed reproduces the same transforms.json.
+    pub seed: u64,
+    /// Expansion threads, 0 = one per core, 1 = serial search.
+    pub threads: usize,
+    /// Nodes popped and expanded together when `threads != 1`; results depend
+    /// on this value but not on the number of threads.
+    pub batch_size: usize,
+    /// Write a search checkpoint every N steps, 0 = never.
+    pub checkpoint_every: usize,
+    pub checkpoint_path: String,
+    /// Continue from `checkpoint_path` when the file exists.
+    pub resume: bool,
+    /// Number of alternative complete layouts to keep (transforms_<k>.json).
+    pub results: usize,
+    /// Alternatives must move some item by at least this distance from each other.
+    pub diversity_mm: f32,
+    /// Record every push/expand/duplicate/prune to /tmp/spaceforge/search_trace.jsonl.
+    pub trace: bool,
+    /// Size cap of the trace file; later records are dropped.
+    pub trace_max_mb: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrontierKind {
+    /// Unbounded best-first heap.
+    Heap,
+    /// At most K expanded nodes per depth level.
+    Beam,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        let candidates = CandidateConfig::default();
+        let key = KeyConfig::default();
+        Self {
+            max_steps: 10_000,
+            time_limit_ms: 0,
+            target_score: None,
+            sample_step_mm: candidates.sample_step_mm,
+            thetas_deg: candidates.thetas.iter().map(|t| t.to_degrees()).collect(),
+            max_children: candidates.max_children,
+            frontier: FrontierKind::Heap,
+            beam_width: 64,
+            dedup_grid_mm: key.grid_mm,
+            dedup_angle_deg: key.angle_step.to_degrees(),
+            jitter_mm: candidates.jitter_mm,
+            seed: 0,
+            threads: 1,
+            batch_size: 16,
+            checkpoint_every: 0,
+            checkpoint_path: "/tmp/spaceforge/search_checkpoint.json".to_string(),
+            resume: false,
+            results: 1,
+            diversity_mm: 500.0,
+            trace: false,
+            trace_max_mb: 64,
+        }
+    }
+}
+
+impl SearchConfig {
+    pub fn budget(&self) -> SearchBudget {
+        let mut budget = SearchBudget::default();
+        if self.max_steps > 0 {
+            budget = budget.with_max_steps(self.max_steps);
+        }
+        if self.time_limit_ms > 0 {
+            budget = budget.with_max_duration(std::time::Duration::from_millis(self.time_limit_ms));
+        }
+        if let Some(target) = self.target_score {
+            budget = budget.with_target_score(target);
+        }
+        budget
+    }
+
+    /// `None` when the search should run serially.
+    pub fn parallel(&self) -> Option<ParallelConfig> {
+        if self.threads == 1 {
+            return None;
+        }
+        let workers = if self.threads == 0 {
+            ParallelConfig::default().workers
+        } else {
+            self.threads
+        };
+        Some(ParallelConfig {
+            workers,
+            batch_size: self.batch_size.max(1),
+        })
+    }
+
+    /// `None` when periodic checkpoints are disabled.
+    pub fn checkpoint(&self) -> Option<CheckpointConfig> {
+        (self.checkpoint_every > 0).then(|| CheckpointConfig {
+            path: self.checkpoint_path.clone().into(),
+            every_steps: self.checkpoint_every,
+        })
+    }
+
+    pub fn key_config(&self) -> KeyConfig {
+        KeyConfig {
+            grid_mm: self.dedup_grid_mm,
+            angle_step: self.dedup_angle_deg.to_radians(),
+        }
+    }
+
+    pub fn candidates(&self) -> CandidateConfig {
+        CandidateConfig {
+            sample_step_mm: self.sample_step_mm,
+            thetas: self.thetas_deg.iter().map(|t| t.to_radians()).collect(),
+            max_children: self.max_children,
+            jitter_mm: self.jitter_mm,
+        }
+    }
+}
+
+/// Simulated annealing applied to the complete layout found by the search.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct AnnealConfig {
+    pub enabled: bool,
+    pub max_iterations: usize,
+    pub initial_temperature: f32,
+    /// Temperature multiplier applied every iteration.
+    pub cooling_rate: f32,
+    pub min_temperature: f32,
+}
+
+impl Default for AnnealConfig {
+    fn default() -> Self {
+        let schedule = AnnealSchedule::default();
+        Self {
+            enabled: true,
+            max_iterations: schedule.max_iterations,
+            initial_temperature: schedule.initial_temperature,
+            cooling_rate: schedule.cooling_rate,
+            min_temperature: schedule.min_temperature,
+        }
+    }
+}
+
+impl AnnealConfig {
+    pub fn schedule(&self) -> AnnealSchedule {
+        AnnealSchedule {
+            initial_temperature: self.initial_temperature,
+            cooling_rate: self.cooling_rate,
+            min_temperature: self.min_temperature,
+            max_iterations: self.max_iterations,
+        }
+    }
+}
+
+/// Weights of the layout objective terms; missing keys fall back to the defaults.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ObjectiveConfig {
+    pub count: f32,
+    pub wall_proximity: f32,
+    pub clearance: f32,
+    pub alignment: f32,
+    pub openings: f32,
+    /// Weighted satisfaction of the soft relational constraints.
+    pub relations: f32,
+    /// Fraction of placed items reachable from the doors.
+    pub circulation: f32,
+    /// Region type names treated as doors/windows by the `openings` term.
+    pub opening_types: Vec<String>,
+}
+
+impl Default for ObjectiveConfig {
+    fn default() -> Self {
+        let weights = ObjectiveWeights::default();
+        Self {
+            count: weights.count,
+            wall_proximity: weights.wall_proximity,
+            clearance: weights.clearance,
+            alignment: weights.alignment,
+            openings: weights.openings,
+            relations: weights.relations,
+            circulation: weights.circulation,
+            opening_types: vec!["door".to_string(), "window".to_string()],
+        }
+    }
+}
+
+impl ObjectiveConfig {
+    pub fn weights(&self) -> ObjectiveWeights {
+        ObjectiveWeights {
+            count: self.count,
+            wall_proximity: self.wall_proximity,
+            clearance: self.clearance,
+            alignment: self.alignment,
+            openings: self.openings,
+            relations: self.relations,
+            circulation: self.circulation,
+        }
+    }
+
+    pub fn build(&self, regions_type_ids: &HashMap<String, RegionsType>) -> Result<Objective, String> {
+        let openings = type_mask(&self.opening_types, regions_type_ids, "objective opening")?;
+        Ok(Objective::from_weights(&self.weights(), openings))
+    }
+}
+
+/// Keep-out zones in front of doors and windows, treated as fixed obstacles.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct KeepOutConfig {
+    pub door_types: Vec<String>,
+    pub window_types: Vec<String>,
+    /// Depth of the walkway kept clear in front of doors.
+    pub door_approach_mm: f32,
+    /// Depth of the band kept clear in front of windows.
+    pub window_depth_mm: f32,
+    /// Items lower than the window sill may stand in front of windows.
+    pub allow_low_under_windows: bool,
+}
+
+impl Default for KeepOutConfig {
+    fn default() -> Self {
+        let zones = geometry_core::layout::KeepOutConfig::default();
+        Self {
+            door_types: vec!["door".to_string()],
+            window_types: vec!["window".to_string()],
+            door_approach_mm: zones.door_approach_mm,
+            window_depth_mm: zones.window_depth_mm,
+            allow_low_under_windows: zones.allow_low_under_windows,
+        }
+    }
+}
+
+impl KeepOutConfig {
+    pub fn build(
+        &self,
+        regions_type_ids: &HashMap<String, RegionsType>,
+    ) -> Result<geometry_core::layout::KeepOutConfig, String> {
+        Ok(geometry_core::layout::KeepOutConfig {
+            doors: type_mask(&self.door_types, regions_type_ids, "keep-out door")?,
+            windows: type_mask(&self.window_types, regions_type_ids, "keep-out window")?,
+            door_approach_mm: self.door_approach_mm,
+            window_depth_mm: self.window_depth_mm,
+            allow_low_under_windows: self.allow_low_under_windows,
+            ..geometry_core::layout::KeepOutConfig::default()
+        })
+    }
+}
+
+/// Walkability check on a floor grid: every item must be reachable from the
+/// doors (the keep-out door approaches) through corridors at least
+/// `min_corridor_mm` wide.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct CirculationConfig {
+    pub cell_mm: f32,
+    pub min_corridor_mm: f32,
+    /// Items whose bottom is above this height do not block walking.
+    pub headroom_mm: f32,
+    /// Prune layouts that cut off an item or a door instead of only scoring them.
+    pub hard: bool,
+}
+
+impl Default for CirculationConfig {
+    fn default() -> Self {
+        let circulation = geometry_core::layout::CirculationConfig::default();
+        Self {
+            cell_mm: circulation.cell_mm,
+            min_corridor_mm: circulation.min_corridor_mm,
+            headroom_mm: circulation.headroom_mm,
+            hard: circulation.hard,
+        }
+    }
+}
+
+impl CirculationConfig {
+    pub fn build(&self) -> Result<geometry_core::layout::CirculationConfig, String> {
+        if self.cell_mm <= 0.0 {
+            return Err(format!("circulation cell_mm must be positive, got {}", self.cell_mm));
+        }
+        Ok(geometry_core::layout::CirculationConfig {
+            cell_mm: self.cell_mm,
+            min_corridor_mm: self.min_corridor_mm,
+            headroom_mm: self.headroom_mm,
+            hard: self.hard,
+        })
+    }
+}
+
+/// Splits unlabelled Space meshes (no `regionsTypeMask`) into floor, wall and
+/// ceiling patches, each masked with the region types listed for its class.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SegmentationConfig {
+    pub enabled: bool,
+    pub angle_tolerance_deg: f32,
+    pub distance_tolerance_mm: f32,
+    pub min_area_mm2: f32,
+    pub weld_mm: f32,
+    pub floor_types: Vec<String>,
+    pub wall_types: Vec<String>,
+    pub ceiling_types: Vec<String>,
+}
+
+impl Default for SegmentationConfig {
+    fn default() -> Self {
+        let segment = geometry_core::geometry_ops::SegmentConfig::default();
+        Self {
+            enabled: false,
+            angle_tolerance_deg: segment.angle_tolerance_deg,
+            distance_tolerance_mm: segment.distance_tolerance_mm,
+            min_area_mm2: segment.min_area_mm2,
+            weld_mm: segment.weld_mm,
+            floor_types: Vec::new(),
+            wall_types: Vec::new(),
+            ceiling_types: Vec::new(),
+        }
+    }
+}
+
+impl SegmentationConfig {
+    /// `None` when segmentation is disabled.
+    ///
+    /// `openings` are the door/window types of the keep-out config: every surface
+    /// carrying one gets keep-out zones, so no patch class may list them.
+    pub fn build(
+        &self,
+        regions_type_ids: &HashMap<String, RegionsType>,
+        openings: RegionsTypeMask,
+    ) -> Result<Option<geometry_core::geometry_ops::SegmentConfig>, String> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        if self.distance_tolerance_mm <= 0.0 {
+            return Err(format!(
+                "segmentation distance_tolerance_mm must be positive, got {}",
+                self.distance_tolerance_mm
+            ));
+        }
+        let classes = [
+            ("floor", &self.floor_types),
+            ("wall", &self.wall_types),
+            ("ceiling", &self.ceiling_types),
+        ];
+        let mut masks = [RegionsTypeMask::NONE; 3];
+        for ((class, names), mask) in classes.iter().zip(&mut masks) {
+            *mask = type_mask(names, regions_type_ids, &format!("segmentation {class}"))?;
+            if mask.intersects(openings) {
+                return Err(format!(
+                    "segmentation {class}_types must not include door/window types; \
+                     openings are not detected by segmentation"
+                ));
+            }
+        }
+        let [floor_mask, wall_mask, ceiling_mask] = masks;
+        Ok(Some(geometry_core::geometry_ops::SegmentConfig {
+            angle_tolerance_deg: self.angle_tolerance_deg,
+            distance_tolerance_mm: self.distance_tolerance_mm,
+            min_area_mm2: self.min_area_mm2,
+            weld_mm: self.weld_mm,
+            floor_mask,
+            wall_mask,
+            ceiling_mask,
+        }))
+    }
+}
+
+fn type_mask(
+    names: &[String],
+    regions_type_ids: &HashMap<String, RegionsType>,
+    what: &str,
+) -> Result<RegionsTypeMask, String> {
+    let mut mask = RegionsTypeMask::NONE;
+    for name in names {
+        let id = regions_type_ids
+            .get(name)
+            .ok_or_else(|| format!("{what} type '{name}' not found in registry"))?;
+        if !mask.insert_id(*id) {
+            return Err(format!("regions type id out of range [0,31]: {id}"));
+        }
+    }
+    Ok(mask)
+}
+
+/// Applies `[inventory]` overrides to the loaded PlacementRegions.
+///
+/// Fails when an item ends up with a lower bound above its upper bound, whether the
+/// bounds come from the config or from the USDA.
+pub fn apply_inventory(
+    overrides: &HashMap<String, InventoryConfig>,
+    regions_type_ids: &HashMap<String, RegionsType>,
+    items: &mut [PlacementRegion],
+) -> Result<(), String> {
+    for (name, config) in overrides {
+        let id = regions_type_ids
+            .get(name)
+            .ok_or_else(|| format!("inventory type '{name}' not found in registry"))?;
+        for item in items.iter_mut().filter(|item| item.semantics.regions_type == *id) {
+            config.apply(&mut item.semantics);
+        }
+    }
+    for item in items.iter() {
+        let semantics = &item.semantics;
+        let max = semantics.count.max(0);
+        if let Some(min) = semantics.min_count.filter(|min| *min > max) {
+            let name = regions_type_ids
+                .iter()
+                .find(|(_, id)| **id == semantics.regions_type)
+                .map_or("<unknown>", |(name, _)| name.as_str());
+            return Err(format!("inventory of '{name}': min {min} is greater than max {max}"));
+        }
+    }
+    Ok(())
 }
 
 pub fn load_scene_config(path: &str) -> Result<SceneConfig, String> {
@@ -21,6 +663,13 @@ pub fn load_scene_config(path: &str) -> Result<SceneConfig, String> {
     config.space_usda_path = canonicalize_if_possible(&config.space_usda_path);
     config.placement_region_usda_dir = canonicalize_if_possible(&config.placement_region_usda_dir);
     config.regions_type_path = canonicalize_if_possible(&config.regions_type_path);
+    config.constraints_path = config
+        .constraints_path
+        .map(|path| canonicalize_if_possible(&resolve_path(base, &path)));
+    config.pinned.transforms_path = config
+        .pinned
+        .transforms_path
+        .map(|path| canonicalize_if_possible(&resolve_path(base, &path)));
     Ok(config)
 }
 
@@ -1,4 +1,5 @@
 use crate::config::SceneConfig;
+use crate::solve::Alternative;
 
 pub fn export_scene_json(config: &SceneConfig) -> Result<(), String> {
     let out_dir = std::path::Path::new("/tmp/spaceforge");
@@ -12,7 +13,63 @@ pub fn export_scene_json(config: &SceneConfig) -> Result<(), String> {
     Ok(())
 }
 
-pub fn export_transforms_json() -> Result<(), String> {
+pub fn export_transforms_json(placements: &[(usize, [[f32; 4]; 4])]) -> Result<(), String> {
+    write_transforms_json("transforms.json", placements)
+}
+
+/// Writes each alternative as transforms_<k>.json and their scores to results_summary.json.
+pub fn export_alternatives_json(alternatives: &[Alternative]) -> Result<(), String> {
+    let mut summary = Vec::with_capacity(alternatives.len());
+    for (k, alternative) in alternatives.iter().enumerate() {
+        let file_name = format!("transforms_{k}.json");
+        write_transforms_json(&file_name, &alternative.transforms)?;
+        let terms = alternative
+            .breakdown
+            .terms
+            .iter()
+            .map(|t| serde_json::json!({ "name": t.name, "weight": t.weight, "value": t.value }))
+            .collect::<Vec<_>>();
+        summary.push(serde_json::json!({
+            "rank": k,
+            "file": file_name,
+            "score": alternative.breakdown.total,
+            "placed": alternative.placed,
+            "complete": alternative.complete,
+            "terms": terms
+        }));
+    }
+    let out_path = std::path::Path::new("/tmp/spaceforge").join("results_summary.json");
+    let payload = serde_json::json!({ "version": 1, "results": summary });
+    let text = serde_json::to_string_pretty(&payload)
+        .map_err(|err| format!("Failed to serialize results summary json: {err}"))?;
+    std::fs::write(&out_path, text)
+        .map_err(|err| format!("Failed to write {}: {err}", out_path.display()))?;
+    Ok(())
+}
+
+fn write_transforms_json(file_name: &str, placements: &[(usize, [[f32; 4]; 4])]) -> Result<(), String> {
+    let out_dir = std::path::Path::new("/tmp/spaceforge");
+    std::fs::create_dir_all(out_dir)
+        .map_err(|err| format!("Failed to create {}: {err}", out_dir.display()))?;
+    let out_path = out_dir.join(file_name);
+    let entries = placements
+        .iter()
+        .map(|(index, matrix)| serde_json::json!({ "index": index, "matrix": matrix }))
+        .collect::<Vec<_>>();
+    let payload = serde_json::json!({
+        "version": 1,
+        "space_meshes": [],
+        "placements": entries
+    });
+    let text = serde_json::to_string_pretty(&payload)
+        .map_err(|err| format!("Failed to serialize transforms json: {err}"))?;
+    std::fs::write(&out_path, text)
+        .map_err(|err| format!("Failed to write {}: {err}", out_path.display()))?;
+    Ok(())
+}
+
+/// Writes an empty transforms.json unless one already exists.
+pub fn ensure_transforms_json() -> Result<(), String> {
     let out_dir = std::path::Path::new("/tmp/spaceforge");
     std::fs::create_dir_all(out_dir)
         .map_err(|err| format!("Failed to create {}: {err}", out_dir.display()))?;
@@ -64,3 +121,20 @@ pub fn export_debug_boundary_json(points: &[[f32; 3]]) -> Result<(), String> {
         .map_err(|err| format!("Failed to write {}: {err}", out_path.display()))?;
     Ok(())
 }
+
+/// Door-to-item walking paths, one polyline per reachable item.
+pub fn export_debug_circulation_json(paths: &[Vec<[f32; 3]>]) -> Result<(), String> {
+    let out_dir = std::path::Path::new("/tmp/spaceforge");
+    std::fs::create_dir_all(out_dir)
+        .map_err(|err| format!("Failed to create {}: {err}", out_dir.display()))?;
+    let out_path = out_dir.join("debug_circulation.json");
+    let payload = serde_json::json!({
+        "polylines": paths,
+        "color": [0.2, 0.7, 0.3]
+    });
+    let text = serde_json::to_string_pretty(&payload)
+        .map_err(|err| format!("Failed to serialize debug circulation json: {err}"))?;
+    std::fs::write(&out_path, text)
+        .map_err(|err| format!("Failed to write {}: {err}", out_path.display()))?;
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use crate::config::SceneConfig;
+use crate::solve::{LayoutObserver, SolveProgress};
+use crossbeam_channel::Sender;
+use geometry_core::layout::LayoutState;
+use geometry_core::search::SearchNode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use viewer::{LayoutFrame, ScenePayload, SceneUpdate, ViewerConfig};
+
+/// Search improvements arrive far faster than the viewer can rebuild the scene.
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Solves on a background thread while the viewer shows each improved layout.
+///
+/// Closing the viewer window cancels the search; the best layout found so far
+/// is exported as in backend-only mode, without annealing.
+pub fn run_with_viewer() {
+    let config = match crate::load_backend_config() {
+        Ok(config) => config,
+        Err(err) => {
+            log::error!("Failed to load backend config: {err}");
+            return;
+        }
+    };
+
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let progress = SolveProgress {
+        cancel: Arc::clone(&cancel),
+        observer: Some(Arc::new(ViewerPublisher::new(
+            tx,
+            Arc::clone(&cancel),
+            config.search.seed,
+        ))),
+    };
+    let solver = {
+        let config = config.clone();
+        std::thread::spawn(move || crate::solve_and_export(&config, &progress))
+    };
+
+    viewer::run_with_config_and_rx(viewer_config(&config), Some(rx));
+
+    cancel.store(true, Ordering::Relaxed);
+    if solver.join().is_err() {
+        log::error!("Solver thread panicked");
+    }
+}
+
+fn viewer_config(config: &SceneConfig) -> ViewerConfig {
+    ViewerConfig {
+        space_usda_path: config.space_usda_path.clone(),
+        placement_region_usda_dir: config.placement_region_usda_dir.clone(),
+        regions_type_path: config.regions_type_path.clone(),
+        usda_scale: config.usda_scale,
+    }
+}
+
+/// Sends improved layouts to the viewer, at most one per `PUBLISH_INTERVAL`.
+///
+/// The room and the item catalog go out once, before the first layout; each
+/// layout after that only carries item ids and matrices.
+struct ViewerPublisher {
+    tx: Sender<SceneUpdate>,
+    cancel: Arc<AtomicBool>,
+    seed: u64,
+    scene_sent: AtomicBool,
+    last_sent: Mutex<Option<Instant>>,
+}
+
+impl ViewerPublisher {
+    fn new(tx: Sender<SceneUpdate>, cancel: Arc<AtomicBool>, seed: u64) -> Self {
+        Self {
+            tx,
+            cancel,
+            seed,
+            scene_sent: AtomicBool::new(false),
+            last_sent: Mutex::new(None),
+        }
+    }
+
+    fn send(&self, state: &LayoutState) {
+        let scene = (!self.scene_sent.swap(true, Ordering::Relaxed)).then(|| scene(state));
+        let updates = scene.into_iter().chain([SceneUpdate::Layout(frame(state, self.seed))]);
+        for update in updates {
+            // The receiver is gone once the viewer window is closed.
+            if self.tx.send(update).is_err() {
+                self.cancel.store(true, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+}
+
+impl LayoutObserver for ViewerPublisher {
+    fn improved(&self, state: &LayoutState) {
+        let mut last_sent = self.last_sent.lock().unwrap_or_else(|e| e.into_inner());
+        if last_sent.is_some_and(|at| at.elapsed() < PUBLISH_INTERVAL) {
+            return;
+        }
+        *last_sent = Some(Instant::now());
+        self.send(state);
+    }
+
+    fn finished(&self, state: &LayoutState) {
+        self.send(state);
+    }
+}
+
+/// The solver's segmented room, whose mesh indices only match the solver's own
+/// surfaces, so the viewer draws it without transforms.json.
+fn scene(state: &LayoutState) -> SceneUpdate {
+    let context = state.context();
+    SceneUpdate::Live(ScenePayload {
+        space: context.space.clone(),
+        placements: context.items.clone(),
+    })
+}
+
+/// One transform per placed item, indexed by item id into the live scene's catalog.
+fn frame(state: &LayoutState, seed: u64) -> LayoutFrame {
+    LayoutFrame {
+        transforms: state
+            .transforms(seed)
+            .into_iter()
+            .map(|(item_id, matrix)| (item_id as usize, matrix))
+            .collect(),
+        score: state.score(),
+        placed: state.placed().len(),
+        complete: state.is_complete() && state.counts_met(),
+    }
+}
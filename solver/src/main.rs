@@ -1,48 +1,96 @@
 mod config;
 mod export;
+#[cfg(feature = "viewer")]
+mod live;
 mod logging;
+mod solve;
 
-use config::load_scene_config;
+use config::{load_scene_config, SceneConfig};
 use export::{
-    export_debug_boundary_json, export_debug_points_json, export_scene_json, export_transforms_json,
+    ensure_transforms_json, export_alternatives_json, export_debug_boundary_json, export_debug_circulation_json,
+    export_debug_points_json, export_scene_json, export_transforms_json,
 };
 use logging::init_logging;
 use geometry_core::geometry_ops::{convex_hull_xz, sample_points_uv};
+use solve::SolveProgress;
 use utils::time_ms;
 
 fn main() {
     init_logging();
+    #[cfg(feature = "viewer")]
+    live::run_with_viewer();
+    #[cfg(not(feature = "viewer"))]
     run_backend();
 }
 
-fn run_backend() {
+fn load_backend_config() -> Result<SceneConfig, String> {
     let config_path = std::env::var("ASSET_IMPORT_CONFIG")
         .unwrap_or_else(|_| "assets/config/asset_import.toml".to_string());
-    match load_scene_config(&config_path) {
-        Ok(config) => {
-            if let Err(err) = export_scene_json(&config) {
-                log::error!("Failed to export scene.json: {err}");
-            } else {
-                log::info!("Exported scene.json");
+    load_scene_config(&config_path)
+}
+
+#[cfg(not(feature = "viewer"))]
+fn run_backend() {
+    match load_backend_config() {
+        Ok(config) => solve_and_export(&config, &SolveProgress::default()),
+        Err(err) => {
+            log::error!("Failed to load backend config: {err}");
+        }
+    }
+    println!("Running backend-only mode (no viewer).");
+}
+
+/// Solves the layout and writes every output file under /tmp/spaceforge.
+fn solve_and_export(config: &SceneConfig, progress: &SolveProgress) {
+    if let Err(err) = export_scene_json(config) {
+        log::error!("Failed to export scene.json: {err}");
+    } else {
+        log::info!("Exported scene.json");
+    }
+    match solve::solve(config, progress) {
+        Ok(result) => {
+            if !result.complete {
+                let unmet = result.counts.iter().filter(|c| !c.is_met()).count();
+                log::warn!(
+                    "Layout incomplete: placed {} items, {} counts not met ({:?})",
+                    result.placements.len(),
+                    unmet,
+                    result.stats.stop_reason
+                );
             }
-            if let Err(err) = export_transforms_json() {
+            log::info!("Layout score {:.4}", result.breakdown.total);
+            if let Err(err) = export_transforms_json(&result.transforms) {
                 log::error!("Failed to export transforms.json: {err}");
             } else {
                 log::info!("Exported transforms.json");
             }
-
-            if let Err(err) = export_debug_points(&config) {
-                log::error!("Failed to export debug_points.json: {err}");
+            if let Err(err) = export_alternatives_json(&result.alternatives) {
+                log::error!("Failed to export layout alternatives: {err}");
             } else {
-                log::info!("Exported debug_points.json");
+                log::info!(
+                    "Exported {} layout alternatives and results_summary.json",
+                    result.alternatives.len()
+                );
+            }
+            if let Err(err) = export_debug_circulation_json(&result.circulation_paths) {
+                log::error!("Failed to export debug_circulation.json: {err}");
+            } else {
+                log::info!("Exported debug_circulation.json");
             }
         }
         Err(err) => {
-            log::error!("Failed to load backend config: {err}");
+            log::error!("Layout solve failed: {err}");
+            if let Err(err) = ensure_transforms_json() {
+                log::error!("Failed to export transforms.json: {err}");
+            }
         }
     }
-    // TODO: wire geometry_core search/layout execution here.
-    println!("Running backend-only mode (no viewer).");
+
+    if let Err(err) = export_debug_points(config) {
+        log::error!("Failed to export debug_points.json: {err}");
+    } else {
+        log::info!("Exported debug_points.json");
+    }
 }
 
 fn export_debug_points(config: &config::SceneConfig) -> Result<(), String> {
@@ -60,7 +108,7 @@ fn export_debug_points(config: &config::SceneConfig) -> Result<(), String> {
 
     let mesh = space
         .meshes
-        .get(0)
+        .first()
         .ok_or_else(|| "Space has no meshes (index 0 missing)".to_string())?;
     let sampled = time_ms("sample_points_uv", || sample_points_uv(mesh, 100.0));
     log::info!("sample_points_uv points={}", sampled.len());
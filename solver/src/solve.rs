@@ -0,0 +1,380 @@
+use crate::config::{apply_inventory, FrontierKind, SceneConfig, SearchConfig};
+use geometry_core::geometry_ops::segment_space;
+use geometry_core::layout::{
+    CirculationReport, ConstraintSet, CountReport, LayoutContext, LayoutSnapshot, LayoutState,
+    Placement, ScoreBreakdown,
+};
+use geometry_core::search::{
+    anneal, BeamFrontier, DiverseSet, Frontier, HeapFrontier, SearchCheckpoint, SearchEngine, SearchNode,
+    SearchStats, SearchTrace,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use utils::time_ms;
+
+pub struct SolveResult {
+    /// Placed items, with groups expanded into their members.
+    pub placements: Vec<Placement>,
+    /// (PlacementRegion index, world matrix) per placed item.
+    pub transforms: Vec<(usize, [[f32; 4]; 4])>,
+    pub breakdown: ScoreBreakdown,
+    pub stats: SearchStats,
+    /// Requested vs placed count per PlacementRegion.
+    pub counts: Vec<CountReport>,
+    /// Door-to-item walking paths of the final layout, in world space.
+    pub circulation_paths: Vec<Vec<[f32; 3]>>,
+    /// Search finished and every requested count is met.
+    pub complete: bool,
+    /// Diverse alternatives ranked by score; the first is the layout above.
+    pub alternatives: Vec<Alternative>,
+}
+
+/// One of the top-K diverse layouts.
+pub struct Alternative {
+    pub transforms: Vec<(usize, [[f32; 4]; 4])>,
+    pub breakdown: ScoreBreakdown,
+    pub placed: usize,
+    pub complete: bool,
+}
+
+/// Receives layouts while a solve is running, on the solving thread.
+pub trait LayoutObserver: Send + Sync {
+    /// The best layout found so far improved; may be partial.
+    fn improved(&self, state: &LayoutState);
+    /// The final layout after annealing.
+    fn finished(&self, state: &LayoutState) {
+        self.improved(state);
+    }
+}
+
+/// Hooks for watching and stopping a running solve.
+#[derive(Clone, Default)]
+pub struct SolveProgress {
+    /// Set to stop the search early; the best layout so far is still returned.
+    pub cancel: Arc<AtomicBool>,
+    pub observer: Option<Arc<dyn LayoutObserver>>,
+}
+
+impl SolveProgress {
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+pub fn solve(config: &SceneConfig, progress: &SolveProgress) -> Result<SolveResult, String> {
+    let regions_type_ids = assets_import::load_regions_type_registry(&config.regions_type_path)?;
+    let mut space = assets_import::load_space_model_from_usda(
+        &config.space_usda_path,
+        &regions_type_ids,
+        config.usda_scale,
+    )?;
+    let keep_out = config.keep_out.build(&regions_type_ids)?;
+    let openings = keep_out.doors | keep_out.windows;
+    if let Some(segment) = config.segmentation.build(&regions_type_ids, openings)? {
+        let unlabelled = space.surface_metas.iter().filter(|m| m.regions_type_mask.is_empty()).count();
+        space = time_ms("planar segmentation", || segment_space(&space, &segment));
+        log::info!(
+            "segmented {} unlabelled meshes: {} surfaces in total",
+            unlabelled,
+            space.meshes.len()
+        );
+    }
+    let mut items = assets_import::load_placement_regions_from_dir(
+        std::path::Path::new(&config.placement_region_usda_dir),
+        &regions_type_ids,
+        config.usda_scale,
+    )?;
+    apply_inventory(&config.inventory, &regions_type_ids, &mut items)?;
+    let objective = config.objective.build(&regions_type_ids)?;
+    let constraints = match &config.constraints_path {
+        Some(path) => assets_import::load_constraints(path, &regions_type_ids)?,
+        None => ConstraintSet::default(),
+    };
+    log::info!("relational constraints: {}", constraints.len());
+    let circulation = config.circulation.build()?;
+    let groups = config
+        .groups
+        .iter()
+        .map(|group| group.build(&regions_type_ids, &items))
+        .collect::<Result<Vec<_>, String>>()?;
+    let pinned = config.pinned.load(&regions_type_ids, &items)?;
+    log::info!("pinned placements: {}", pinned.len());
+
+    let mut context = LayoutContext::new(space, items, config.search.candidates())
+        .with_objective(objective)
+        .with_constraints(constraints)
+        .with_keep_outs(&keep_out)
+        .with_circulation(circulation)
+        .with_key_config(config.search.key_config())
+        .with_seed(config.search.seed)
+        .with_groups(groups)?
+        .with_pinned(pinned)?;
+    // Built from the extruded floor outline: the raw shell has seams and door/window
+    // openings, so its own SDF would not have a reliable inside/outside sign.
+    let shell_sdf = context
+        .shell
+        .closed_mesh()
+        .ok_or_else(|| "room has no floor outline or height".to_string())
+        .and_then(|mesh| assets_import::build_shell_sdf(&mesh));
+    match shell_sdf {
+        Ok(sdf) => context = context.with_shell_sdf(sdf),
+        Err(err) => log::warn!("room shell sdf unavailable, containment uses planes only: {err}"),
+    }
+    log::info!("door/window keep-out zones: {}", context.keep_outs.len());
+    log::info!("item groups: {}", context.groups.len());
+    let context = Arc::new(context);
+    let root = LayoutState::new(Arc::clone(&context), context.initial_inventory());
+    let seed = config.search.seed;
+    let (results, stats) = match config.search.frontier {
+        FrontierKind::Heap => {
+            run_search(HeapFrontier::with_seed(seed), root, &config.search, progress)?
+        }
+        FrontierKind::Beam => run_search(
+            BeamFrontier::with_seed(config.search.beam_width, seed),
+            root,
+            &config.search,
+            progress,
+        )?,
+    };
+    if results.is_empty() {
+        return Err("search produced no layout".to_string());
+    }
+    // Annealing may pull alternatives together; filter them again afterwards.
+    let mut diverse = DiverseSet::new(config.search.results, config.search.diversity_mm);
+    let mut refined: Vec<LayoutState> = results
+        .into_iter()
+        .map(|state| refine(state, config, seed, progress))
+        .collect();
+    refined.sort_by(|a, b| b.score().total_cmp(&a.score()));
+    for state in &refined {
+        diverse.offer(state);
+    }
+    let mut results = diverse.into_nodes();
+    let alternatives: Vec<Alternative> = results
+        .iter()
+        .map(|state| Alternative {
+            transforms: export_transforms(state, seed),
+            breakdown: state.breakdown(),
+            placed: state.placed().len(),
+            complete: state.is_complete() && state.counts_met(),
+        })
+        .collect();
+    if config.search.results > 1 {
+        log::info!(
+            "kept {} of {} diverse layouts (min distance {}mm)",
+            alternatives.len(),
+            config.search.results,
+            config.search.diversity_mm
+        );
+    }
+    let best = results.swap_remove(0);
+    if let Some(observer) = &progress.observer {
+        observer.finished(&best);
+    }
+    let breakdown = best.breakdown();
+    log::info!("best layout score={:.4} placed={}", best.score(), best.placed().len());
+    for term in &breakdown.terms {
+        log::info!("  {} = {:.4} (weight {})", term.name, term.value, term.weight);
+    }
+
+    log_constraints(&best);
+    let counts = best.count_report();
+    log_counts(&counts);
+    let circulation = best.circulation_report();
+    log_circulation(&best, &circulation);
+
+    // Groups are searched as one item; export their members individually.
+    let placements = best.refine_members(seed);
+    let transforms = export_transforms(&best, seed);
+
+    Ok(SolveResult {
+        placements,
+        transforms,
+        breakdown,
+        stats,
+        circulation_paths: circulation_paths(&best, &circulation),
+        complete: best.is_complete() && counts.iter().all(CountReport::is_met),
+        counts,
+        alternatives,
+    })
+}
+
+/// Anneals a complete layout when enabled; incomplete layouts and cancelled solves are returned as is.
+fn refine(state: LayoutState, config: &SceneConfig, seed: u64, progress: &SolveProgress) -> LayoutState {
+    if !config.anneal.enabled || !state.is_complete() || progress.is_cancelled() {
+        return state;
+    }
+    let schedule = config.anneal.schedule();
+    let (refined, anneal_stats) = time_ms("layout anneal", || anneal(state, &schedule, seed));
+    log::info!(
+        "anneal: {:.4} -> {:.4} iterations={} accepted={} rejected={} invalid={} improved={}",
+        anneal_stats.initial_score,
+        anneal_stats.final_score,
+        anneal_stats.iterations,
+        anneal_stats.accepted,
+        anneal_stats.rejected,
+        anneal_stats.invalid,
+        anneal_stats.improved
+    );
+    refined
+}
+
+fn export_transforms(state: &LayoutState, seed: u64) -> Vec<(usize, [[f32; 4]; 4])> {
+    state
+        .transforms(seed)
+        .into_iter()
+        .map(|(item_id, matrix)| (item_id as usize, matrix))
+        .collect()
+}
+
+fn run_search<F: Frontier<LayoutState>>(
+    frontier: F,
+    root: LayoutState,
+    search: &SearchConfig,
+    progress: &SolveProgress,
+) -> Result<(Vec<LayoutState>, SearchStats), String> {
+    let budget = search.budget().with_cancel(Arc::clone(&progress.cancel));
+    let parallel = search.parallel();
+    let checkpoint_path = std::path::Path::new(&search.checkpoint_path);
+    let mut engine = if search.resume && checkpoint_path.exists() {
+        let saved = SearchCheckpoint::<LayoutSnapshot>::load(checkpoint_path)?;
+        log::info!(
+            "resuming search from {} at step {}",
+            checkpoint_path.display(),
+            saved.step_count
+        );
+        SearchEngine::resume(frontier, saved, root.context())?
+    } else {
+        SearchEngine::with_root(frontier, root)
+    };
+    if search.results > 1 {
+        engine = engine.with_alternatives(search.results, search.diversity_mm);
+    }
+    if let Some(observer) = &progress.observer {
+        let observer = Arc::clone(observer);
+        engine = engine.with_progress(move |state: &LayoutState| observer.improved(state));
+    }
+    if search.trace {
+        let path = std::path::Path::new("/tmp/spaceforge/search_trace.jsonl");
+        let trace = SearchTrace::create(path, search.trace_max_mb * 1024 * 1024)?;
+        engine = engine.with_trace(trace);
+    }
+
+    let stats = time_ms("layout search", || match search.checkpoint() {
+        Some(checkpoint) => engine.run_checkpointed(&budget, parallel.as_ref(), &checkpoint),
+        None => Ok(match &parallel {
+            Some(parallel) => engine.run_parallel(&budget, parallel),
+            None => engine.run(&budget),
+        }),
+    })?;
+    log::info!(
+        "search stopped: {:?} steps={} expanded={} pruned={} duplicates={} peak_frontier={}",
+        stats.stop_reason,
+        engine.steps(),
+        stats.expanded,
+        stats.pruned,
+        stats.duplicates,
+        stats.peak_frontier
+    );
+    engine.flush_trace()?;
+    if let Some(trace) = engine.trace() {
+        log::info!(
+            "search trace: {} records, {} bytes",
+            trace.records_written(),
+            trace.bytes_written()
+        );
+        if trace.is_truncated() {
+            log::warn!("search trace hit the {} MB cap and was truncated", search.trace_max_mb);
+        }
+    }
+    Ok((engine.into_results(), stats))
+}
+
+/// Reports how well the final layout meets each relational constraint.
+fn log_constraints(state: &LayoutState) {
+    let constraints = state.context().constraints.constraints();
+    for (constraint, status) in constraints.iter().zip(state.constraint_report()) {
+        let kind = if constraint.is_hard() { "hard" } else { "soft" };
+        if status.violated > 0 {
+            log::warn!(
+                "constraint '{}' ({kind}) violated {}/{}",
+                constraint.label,
+                status.violated,
+                status.total()
+            );
+        } else {
+            log::info!(
+                "constraint '{}' ({kind}) satisfied {}/{} pending={}",
+                constraint.label,
+                status.satisfied,
+                status.total(),
+                status.pending
+            );
+        }
+    }
+}
+
+/// Reports requested vs placed counts, warning about the ones not met.
+fn log_counts(counts: &[CountReport]) {
+    for report in counts {
+        let demand = &report.demand;
+        let range = if demand.min == demand.max {
+            demand.max.to_string()
+        } else {
+            format!("{}..{}", demand.min, demand.max)
+        };
+        if report.is_met() {
+            log::info!(
+                "count item {} (type {}): placed {} of {range}",
+                report.item_id,
+                report.regions_type,
+                report.placed
+            );
+        } else {
+            log::warn!(
+                "count item {} (type {}): placed {} of {range}, not met",
+                report.item_id,
+                report.regions_type,
+                report.placed
+            );
+        }
+    }
+}
+
+/// Reports door connectivity and, per placed item, the walking path from the doors.
+fn log_circulation(state: &LayoutState, report: &CirculationReport) {
+    if report.doors == 0 {
+        log::info!("circulation: no doors, walkability not checked");
+        return;
+    }
+    if !report.doors_connected {
+        log::warn!("circulation: {} doors are not connected to each other", report.doors);
+    }
+    for path in &report.paths {
+        let item_id = state.placed()[path.index].item_id;
+        match (path.reachable, path.length_mm, path.min_width_mm) {
+            (true, Some(length), Some(width)) => log::info!(
+                "circulation placement {} (item {item_id}): path {length:.0}mm, narrowest {width:.0}mm",
+                path.index
+            ),
+            (true, _, _) => {}
+            (false, _, _) => log::warn!(
+                "circulation placement {} (item {item_id}) is not reachable from any door",
+                path.index
+            ),
+        }
+    }
+}
+
+/// Lifts the XZ walking paths onto the floor for the debug export.
+fn circulation_paths(state: &LayoutState, report: &CirculationReport) -> Vec<Vec<[f32; 3]>> {
+    let context = state.context();
+    report
+        .polylines()
+        .map(|line| {
+            line.iter()
+                .map(|[x, z]| [*x, context.elevation_at(*x, *z), *z])
+                .collect()
+        })
+        .collect()
+}
@@ -17,7 +17,14 @@ struct PlacementRegionUsdJson {
     #[serde(rename = "regionsTypeName")]
     regions_type_name: String,
     name: String,
+    /// `count`, `countMin` and `reward` mirror the custom attributes of the same name
+    /// authored on the placement-region prim (`int count`, `int countMin`, `float reward`).
+    /// The parse script emits a key only when the attribute is authored; a missing key
+    /// means one exact item with no reward. `countMin` is not validated here.
     count: Option<i32>,
+    #[serde(rename = "countMin")]
+    count_min: Option<i32>,
+    reward: Option<f32>,
     #[serde(rename = "heightRange")]
     height_range: Option<[f32; 2]>,
     #[serde(rename = "restrictedRegion")]
@@ -42,6 +49,8 @@ pub fn load_placement_region_usda(path: &str) -> Result<PlacementRegionUsd, Stri
         regions_type_name: region.regions_type_name,
         name: region.name,
         count: region.count,
+        count_min: region.count_min,
+        reward: region.reward,
         height_range: region.height_range,
         restricted_region: region.restricted_region.map(placement_mesh_from_json),
         forbidden_region: region.forbidden_region.map(placement_mesh_from_json),
@@ -23,6 +23,8 @@ pub struct PlacementRegionUsd {
     pub regions_type_name: String,
     pub name: String,
     pub count: Option<i32>,
+    pub count_min: Option<i32>,
+    pub reward: Option<f32>,
     pub height_range: Option<[f32; 2]>,
     pub restricted_region: Option<UsdMesh>,
     pub forbidden_region: Option<UsdMesh>,
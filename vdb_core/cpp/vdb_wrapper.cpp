@@ -1,4 +1,5 @@
 #include <openvdb/openvdb.h>
+#include <openvdb/tools/Interpolation.h>
 #include <openvdb/tools/LevelSetSphere.h>
 #include <openvdb/tools/MeshToVolume.h>
 #include <openvdb/tools/VolumeToMesh.h>
@@ -221,4 +222,23 @@ void vdb_active_voxel_coords_free(int* coords)
     std::free(coords);
 }
 
+// 世界坐标下的三线性采样；窄带之外返回背景值（±半带宽）
+int vdb_sample_world(openvdb::FloatGrid* grid,
+                     const float* positions,
+                     int count,
+                     float* out_values)
+{
+    if (!grid || !positions || !out_values || count < 0) {
+        return 0;
+    }
+
+    openvdb::tools::GridSampler<openvdb::FloatGrid, openvdb::tools::BoxSampler> sampler(*grid);
+    for (int i = 0; i < count; ++i) {
+        const int base = i * 3;
+        const openvdb::Vec3d world(positions[base + 0], positions[base + 1], positions[base + 2]);
+        out_values[i] = sampler.wsSample(world);
+    }
+    return 1;
+}
+
 }
@@ -35,4 +35,10 @@ extern "C" {
         out_count: *mut i32,
     ) -> i32;
     pub(crate) fn vdb_active_voxel_coords_free(coords: *mut i32);
+    pub(crate) fn vdb_sample_world(
+        grid: *mut Grid,
+        positions: *const f32,
+        count: i32,
+        out_values: *mut f32,
+    ) -> i32;
 }
@@ -163,6 +163,27 @@ impl VdbGrid {
         unsafe { ffi::vdb_active_voxel_coords_free(coords_ptr) };
         Ok(out)
     }
+
+    /// Samples the signed distance at world-space points (trilinear).
+    /// Points outside the narrow band get the background value.
+    pub fn sample_world(&self, points: &[[f32; 3]]) -> Result<Vec<f32>, String> {
+        if points.len() > i32::MAX as usize {
+            return Err("too many sample points".to_string());
+        }
+        let mut values = vec![0.0f32; points.len()];
+        let ok = unsafe {
+            ffi::vdb_sample_world(
+                self.as_ptr(),
+                points.as_ptr() as *const f32,
+                points.len() as i32,
+                values.as_mut_ptr(),
+            )
+        };
+        if ok == 0 {
+            return Err("failed to sample VDB grid".to_string());
+        }
+        Ok(values)
+    }
 }
 
 // NOTE: We do not implement Drop because there is no FFI release function yet.
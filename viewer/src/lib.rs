@@ -6,7 +6,7 @@ mod scene;
 mod ui;
 
 pub use config::ViewerConfig;
-pub use scene::ScenePayload;
+pub use scene::{LayoutFrame, ScenePayload, SceneUpdate};
 
 pub fn run() {
     run_with_config(ViewerConfig::default());
@@ -18,7 +18,7 @@ pub fn run_with_config(config: ViewerConfig) {
 
 pub fn run_with_config_and_rx(
     config: ViewerConfig,
-    rx: Option<crossbeam_channel::Receiver<ScenePayload>>,
+    rx: Option<crossbeam_channel::Receiver<SceneUpdate>>,
 ) {
     App::new()
         .add_plugins(DefaultPlugins)
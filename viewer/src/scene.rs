@@ -2,6 +2,7 @@ use bevy::log::{error, info};
 use bevy::math::vec3;
 use bevy::prelude::*;
 use bevy::render::mesh::Mesh;
+use bevy::window::PrimaryWindow;
 use crossbeam_channel::{Receiver, Sender};
 use notify::{Config as NotifyConfig, RecommendedWatcher, RecursiveMode, Watcher};
 
@@ -25,8 +26,73 @@ pub struct ScenePayload {
     pub placements: Vec<PlacementRegion>,
 }
 
+/// One layout streamed from a running solve.
+#[derive(Clone, Debug)]
+pub struct LayoutFrame {
+    /// (index into the live `ScenePayload::placements`, world matrix), one per placed item
+    pub transforms: Vec<(usize, [[f32; 4]; 4])>,
+    pub score: f32,
+    pub placed: usize,
+    pub complete: bool,
+}
+
+/// Messages accepted by `run_with_config_and_rx`.
+#[derive(Clone)]
+pub enum SceneUpdate {
+    /// A complete scene, placed by transforms.json like a scene loaded from disk.
+    Scene(ScenePayload),
+    /// The room and item catalog of a running solve, sent once before its layouts.
+    /// The room is the solver's own geometry and is drawn without transforms.json.
+    Live(ScenePayload),
+    /// A layout over the last `Live` scene.
+    Layout(LayoutFrame),
+}
+
 #[derive(Resource)]
-pub struct SceneReceiver(pub Option<Receiver<ScenePayload>>);
+pub struct SceneReceiver(pub Option<Receiver<SceneUpdate>>);
+
+/// The last `Live` scene and the layout currently drawn over it.
+#[derive(Default)]
+pub(crate) struct LiveScene {
+    payload: Option<ScenePayload>,
+    frame: Option<LayoutFrame>,
+}
+
+/// What `apply_payload` draws: the room and each item with its world matrix, if any.
+struct SceneView<'a> {
+    space: &'a Space,
+    space_transforms: &'a [IndexedTransform],
+    placements: Vec<(&'a PlacementRegion, Option<&'a [[f32; 4]; 4]>)>,
+}
+
+impl<'a> SceneView<'a> {
+    /// A scene placed by transforms.json.
+    fn from_files(payload: &'a ScenePayload, transforms: &'a SceneTransforms) -> Self {
+        Self {
+            space: &payload.space,
+            space_transforms: &transforms.space_meshes,
+            placements: payload
+                .placements
+                .iter()
+                .enumerate()
+                .map(|(idx, placement)| (placement, find_transform(&transforms.placements, idx)))
+                .collect(),
+        }
+    }
+
+    /// A live scene: the room as sent, items only where the layout placed them.
+    fn live(payload: &'a ScenePayload, frame: Option<&'a LayoutFrame>) -> Self {
+        Self {
+            space: &payload.space,
+            space_transforms: &[],
+            placements: frame
+                .into_iter()
+                .flat_map(|frame| &frame.transforms)
+                .filter_map(|(index, matrix)| Some((payload.placements.get(*index)?, Some(matrix))))
+                .collect(),
+        }
+    }
+}
 
 #[derive(Resource, Debug, Clone, Copy)]
 pub(crate) enum PlacementRenderMode {
@@ -152,14 +218,13 @@ pub fn load_scene_from_file(
     let path = std::env::var("SCENE_JSON").unwrap_or_else(|_| "/tmp/spaceforge/scene.json".into());
     if let Some(payload) = load_scene_from_json(&path) {
         apply_payload(
-            &payload,
+            &SceneView::from_files(&payload, &transforms),
             &mut commands,
             &mut meshes,
             &mut materials,
             &mut cam,
             &mut scene_info,
             &mut entities,
-            &transforms,
             *render_mode,
         );
     }
@@ -192,31 +257,78 @@ pub fn apply_scene_updates(
     transforms: Res<SceneTransforms>,
     receiver: Res<SceneReceiver>,
     render_mode: Res<PlacementRenderMode>,
+    mut live: Local<LiveScene>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
 ) {
     let Some(rx) = receiver.0.as_ref() else {
         return;
     };
 
-    let mut latest = None;
-    while let Ok(payload) = rx.try_recv() {
-        latest = Some(payload);
+    let mut scene = None;
+    let mut live_changed = false;
+    while let Ok(update) = rx.try_recv() {
+        match update {
+            SceneUpdate::Scene(payload) => {
+                scene = Some(payload);
+                live_changed = false;
+            }
+            SceneUpdate::Live(payload) => {
+                *live = LiveScene {
+                    payload: Some(payload),
+                    frame: None,
+                };
+                scene = None;
+                live_changed = true;
+            }
+            SceneUpdate::Layout(frame) => {
+                live.frame = Some(frame);
+                live_changed = live.payload.is_some();
+            }
+        }
     }
 
-    let Some(payload) = latest else {
-        return;
-    };
-
+    if let Some(payload) = scene {
         apply_payload(
-            &payload,
+            &SceneView::from_files(&payload, &transforms),
             &mut commands,
             &mut meshes,
             &mut materials,
             &mut cam,
             &mut scene_info,
             &mut entities,
-            &transforms,
             *render_mode,
         );
+        return;
+    }
+    let (true, Some(payload)) = (live_changed, &live.payload) else {
+        return;
+    };
+
+    // Keep the user's camera while the layout keeps changing; only the items move.
+    let keep_camera = !entities.entities.is_empty();
+    let (target, distance) = (cam.target, cam.distance);
+    apply_payload(
+        &SceneView::live(payload, live.frame.as_ref()),
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut cam,
+        &mut scene_info,
+        &mut entities,
+        *render_mode,
+    );
+    if keep_camera {
+        cam.target = target;
+        cam.distance = distance;
+    }
+    if let (Some(layout), Ok(mut window)) = (&live.frame, windows.get_single_mut()) {
+        window.title = format!(
+            "spaceforge - score {:.4}, {} placed{}",
+            layout.score,
+            layout.placed,
+            if layout.complete { "" } else { " (incomplete)" }
+        );
+    }
 }
 
 pub fn apply_file_watch_updates(
@@ -263,14 +375,13 @@ pub fn apply_file_watch_updates(
         *transforms = load_transforms_from_path(&watcher.transforms_path);
         if let Some(payload) = load_scene_from_json(&watcher.scene_path) {
             apply_payload(
-                &payload,
+                &SceneView::from_files(&payload, &transforms),
                 &mut commands,
                 &mut meshes,
                 &mut materials,
                 &mut cam,
                 &mut scene_info,
                 &mut entities,
-                &transforms,
                 *render_mode,
             );
         }
@@ -295,21 +406,20 @@ pub fn apply_file_watch_updates(
 }
 
 fn apply_payload(
-    payload: &ScenePayload,
+    view: &SceneView,
     commands: &mut Commands,
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
     cam: &mut OrbitCamera,
     scene_info: &mut SceneInfo,
     entities: &mut SceneEntities,
-    transforms: &SceneTransforms,
     render_mode: PlacementRenderMode,
 ) {
     for e in entities.entities.drain(..) {
         commands.entity(e).despawn_recursive();
     }
 
-    let (min, max) = compute_bounds(&payload.space);
+    let (min, max) = compute_bounds(view.space);
     let center = (min + max) * 0.5;
     let extent = max - min;
     let size = vec3(extent.x.abs(), extent.y.abs(), extent.z.abs());
@@ -321,15 +431,15 @@ fn apply_payload(
     cam.target = center;
     cam.distance = focus_distance;
 
-    for (idx, (mesh, meta)) in payload
+    for (idx, (mesh, meta)) in view
         .space
         .meshes
         .iter()
-        .zip(payload.space.surface_metas.iter())
+        .zip(view.space.surface_metas.iter())
         .enumerate()
     {
         let mut positions = mesh.positions.clone();
-        if let Some(xform) = find_transform(&transforms.space_meshes, idx) {
+        if let Some(xform) = find_transform(view.space_transforms, idx) {
             apply_transform_positions(&mut positions, xform);
         }
         let mesh = mesh_from_geometry(positions, mesh.indices.clone());
@@ -351,8 +461,7 @@ fn apply_payload(
         entities.entities.push(id);
     }
 
-    for (idx, placement) in payload.placements.iter().enumerate() {
-        let placement_transform = find_transform(&transforms.placements, idx);
+    for &(placement, placement_transform) in &view.placements {
         let restricted_mesh = mesh_from_geometry(
             apply_optional_transform(
                 placement.regions.restricted_region.mesh.positions.clone(),